@@ -1,64 +1,182 @@
-use std::io;
+use std::{
+    collections::{HashMap, VecDeque},
+    io,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Duration,
+};
 
-use discord_game_sdk::Discord;
-use dotenv::var;
+use chrono::{DateTime, Utc};
 use log::{error, info};
 use ratatui::{
     DefaultTerminal, Frame,
-    crossterm::event::{self, Event, KeyEventKind},
+    crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers},
+    layout::{Constraint, Layout},
     widgets::ListState,
 };
 use tui_input::backend::crossterm::EventHandler as _;
 
 use crate::{
     Config,
-    event_handler::EventHandler,
-    game::{Game, gamefile::GameFile, gamelist::GameList},
+    domain::{
+        Repository,
+        asset::AssetManifest,
+        game::{
+            Change, Game, GameCreateRequest, GameDeleteRequest, GameFilter, GameId, GameService,
+            GameUpdateRequest, History, Party, Placeholders, Playtime, Secret, Secrets, Service,
+        },
+        platform::PlatformService,
+        presence::{
+            ActivityTemplates, FriendList, InviteKind, JoinReply, Presence, PresenceError,
+            PresenceEvent, PresencePublisher, User, UserId,
+            lobby::{
+                DEFAULT_LOBBY_CAPACITY, LobbyCommand, LobbyRoom, MAX_MESSAGE_LEN, is_lobby_secret,
+            },
+        },
+    },
+    game::{
+        friends::{Delivery, FriendPicker, FriendsPanel},
+        gamelist::GameList,
+        lobby::LobbyPane,
+        notice::{ErrorDetails, JoinPrompt, Notice},
+        preview::PresencePreview,
+    },
+    outbound::CommandHook,
 };
 
 use crate::app::state::State as AppState;
-use crate::tui::input::Input as AppInput;
+use crate::tui::{filter::FilterInput, input::Input as AppInput};
 
 mod state;
 
+/// How long to wait for input before giving the presence publisher and signals a look.
+const TICK: Duration = Duration::from_millis(100);
+
+/// Columns of the presence preview next to the game list.
+const PREVIEW_WIDTH: u16 = 48;
+
+/// Signals that end the app as if the user had quit. Closing the terminal hangs up only on Unix.
+const EXIT_SIGNALS: &[i32] = &[
+    signal_hook::consts::SIGINT,
+    signal_hook::consts::SIGTERM,
+    #[cfg(unix)]
+    signal_hook::consts::SIGHUP,
+];
+
 #[derive(Debug)]
-pub(crate) struct App<'a> {
-    list: GameList,
+pub(crate) struct App<R: Repository, P: PresencePublisher> {
+    service: Service<R>,
     exit: bool,
+    /// Set by the signal handlers for [`EXIT_SIGNALS`].
+    terminate: Arc<AtomicBool>,
     list_state: ListState,
-    discord: Discord<'a, EventHandler>,
-    gamefile: GameFile,
+    presence: P,
     state: AppState,
+    active_game: Option<GameId>,
+    /// When the active game was activated, for the elapsed time.
+    session_start: Option<DateTime<Utc>>,
+    /// Whether games show the elapsed time unless they say otherwise.
+    show_elapsed: bool,
+    templates: ActivityTemplates,
+    /// Whole minutes into the session when the presence was last rendered, to know when time
+    /// placeholders need a refresh.
+    rendered_minutes: i64,
+    assets: AssetManifest,
+    filter: GameFilter,
+    history: History,
+    /// Shown in the status bar until it expires.
+    notice: Option<Notice>,
+    last_error: Option<Notice>,
+    /// Whether the details of `last_error` are open.
+    showing_error: bool,
+    /// Discord users asking to join, oldest first. The oldest one is prompted for.
+    join_requests: VecDeque<User>,
+    join_hook: Option<CommandHook>,
+    friends: FriendList,
+    /// The invites sent this session, by friend.
+    invites: HashMap<UserId, Delivery>,
+    /// Selection in the friend picker while it is open.
+    friend_picker: Option<ListState>,
+    /// Selection in the friends panel, which replaces the preview while it is open.
+    friends_panel: Option<ListState>,
+    /// The lobby the user is in, if any.
+    lobby: Option<LobbyRoom>,
+    /// The lobby pane, which replaces the preview while it is open.
+    lobby_pane: Option<LobbyPane>,
 }
 
-impl<'a> App<'a> {
-    pub(crate) fn new() -> Result<Self, crate::Error> {
-        Ok(Self {
-            list: Default::default(),
+impl<R: Repository, P: PresencePublisher> App<R, P> {
+    pub(crate) fn new(service: Service<R>, presence: P, assets: AssetManifest) -> Self {
+        Self {
+            service,
             exit: Default::default(),
+            terminate: Arc::default(),
             list_state: Default::default(),
-            discord: Self::init_discord()?,
-            gamefile: GameFile::new()?,
+            presence,
             state: AppState::default(),
-        })
-    }
-
-    fn add_games<T: std::iter::IntoIterator<Item = Game>>(&mut self, games: T) {
-        for game in games {
-            self.list.push(game);
+            active_game: None,
+            session_start: None,
+            show_elapsed: false,
+            templates: ActivityTemplates::default(),
+            rendered_minutes: 0,
+            assets,
+            filter: GameFilter::default(),
+            history: History::new(),
+            notice: None,
+            last_error: None,
+            showing_error: false,
+            join_requests: VecDeque::new(),
+            join_hook: None,
+            friends: FriendList::new(),
+            invites: HashMap::new(),
+            friend_picker: None,
+            friends_panel: None,
+            lobby: None,
+            lobby_pane: None,
         }
     }
 
-    fn add_game(&mut self, game: Game) {
-        self.list.push(game);
+    fn add_game(&mut self, req: &GameCreateRequest) -> bool {
+        match self.service.create_game(req) {
+            Ok(game) => {
+                info!("Added: {game:?}");
+                self.history.record(Change::Create(game));
+                true
+            }
+            Err(err) => {
+                error!("Error while adding the game: {err}");
+                false
+            }
+        }
     }
 
-    fn replace_current_selection(&mut self, game: Game) -> Option<usize> {
+    fn replace_current_selection(&mut self, game: &GameCreateRequest) -> Option<usize> {
         let selected = self.list_state.selected()?;
 
-        let selected_game = self.list.get_mut(selected)?;
+        let selected_game = self.selected_game()?;
+        let req = GameUpdateRequest::builder()
+            .id(selected_game.id())
+            .game(game.clone())
+            .build();
 
-        *selected_game = game;
+        match self.service.update_game(&req) {
+            Ok(game) => {
+                info!("Updated: {game:?}");
+                self.history.record(Change::Update {
+                    before: Box::new(selected_game),
+                    after: Box::new(game.clone()),
+                });
+                if self.active_game == Some(game.id()) {
+                    self.activate(game);
+                }
+            }
+            Err(err) => {
+                error!("Error while updating the game: {err}");
+                return None;
+            }
+        }
 
         Some(selected)
     }
@@ -68,39 +186,130 @@ impl<'a> App<'a> {
         terminal: &mut DefaultTerminal,
         config: Config,
     ) -> Result<(), crate::error::Error> {
-        info!("timestamp: {}", config.timestamp);
+        self.show_elapsed = config.timestamp;
+        self.templates = ActivityTemplates::new(config.state_template, config.details_template);
+        self.join_hook = config.join_hook.map(CommandHook::new);
+        for &signal in EXIT_SIGNALS {
+            signal_hook::flag::register(signal, Arc::clone(&self.terminate))?;
+        }
 
-        self.add_games(self.gamefile.owned_game_iter());
+        if let Some(id) = config.game {
+            match self.service.get_game(id) {
+                Ok(game) => self.activate(game),
+                Err(err) => error!("Cannot activate game {id}: {err}"),
+            }
+        }
 
-        while !self.exit {
+        while !self.should_exit() {
             terminal.draw(|frame| self.draw(frame))?;
             self.handle_events()?;
-            self.discord.run_callbacks()?;
+            self.refresh_time();
+            self.expire_notice();
+            let polled = self.presence.poll();
+            self.report("Presence error", polled);
+            self.handle_presence_events();
         }
+        self.shutdown();
+
         Ok(())
     }
 
-    fn init_discord() -> Result<Discord<'a, EventHandler>, crate::Error> {
-        let client_id: i64 = var("CLIENT_ID")?.parse()?;
-
-        info!("init discord");
-        let mut discord = discord_game_sdk::Discord::new(client_id)?;
-        *discord.event_handler_mut() = Some(crate::event_handler::EventHandler);
+    fn should_exit(&self) -> bool {
+        self.exit || self.terminate.load(Ordering::Relaxed)
+    }
 
-        Ok(discord)
+    /// Takes the presence down so it does not outlive the app.
+    fn shutdown(&mut self) {
+        self.leave_lobby();
+        self.clear_presence();
+        // Sends the clear before we go, even if the publisher would rather wait.
+        let flushed = self.presence.flush();
+        self.report("Presence error", flushed);
     }
 
     fn draw(&mut self, frame: &mut Frame) {
+        let list = || -> GameList {
+            self.visible_games()
+                .into_iter()
+                .collect::<GameList>()
+                .with_filter(self.filter.clone())
+                .with_history(&self.history)
+                .with_status(self.presence.status())
+        };
+
+        let [main_area, status_area] = Layout::vertical([
+            Constraint::Fill(1),
+            Constraint::Length(self.notice.is_some().into()),
+        ])
+        .areas(frame.area());
+        let [games_area, preview_area] =
+            Layout::horizontal([Constraint::Fill(1), Constraint::Length(PREVIEW_WIDTH)])
+                .areas(main_area);
+
         match &self.state {
             AppState::Selection => {
-                frame.render_stateful_widget(&self.list, frame.area(), &mut self.list_state)
+                frame.render_stateful_widget(&list(), games_area, &mut self.list_state);
+                if let Some(pane) = &self.lobby_pane {
+                    let game = self
+                        .active_game
+                        .and_then(|id| self.service.get_game(id).ok())
+                        .map(|game| game.name().to_string())
+                        .unwrap_or_default();
+                    pane.render(preview_area, frame, self.lobby.as_ref(), &game);
+                } else if let Some(panel) = &mut self.friends_panel {
+                    let library = self.service.list_games().unwrap_or_default();
+                    let widget = FriendsPanel {
+                        friends: &self.friends,
+                        library: &library,
+                    };
+                    frame.render_stateful_widget(widget, preview_area, panel);
+                } else {
+                    frame.render_widget(&self.preview(), preview_area);
+                }
+            }
+            AppState::Filtering(input) => {
+                let [list_area, input_area] =
+                    Layout::vertical([Constraint::Fill(1), Constraint::Length(3)])
+                        .areas(games_area);
+                frame.render_stateful_widget(&list(), list_area, &mut self.list_state);
+                input.render(input_area, frame);
+                frame.render_widget(&self.preview(), preview_area);
             }
-            AppState::Editing(input) => input.render(frame.area(), frame),
-            AppState::Adding(input) => input.render(frame.area(), frame),
+            AppState::Editing(input) => input.render(main_area, frame, &self.assets),
+            AppState::Adding(input) => input.render(main_area, frame, &self.assets),
+        }
+
+        if let Some(notice) = &self.notice {
+            frame.render_widget(notice, status_area);
+        }
+        if self.showing_error
+            && let Some(error) = &self.last_error
+        {
+            frame.render_widget(ErrorDetails(error), frame.area());
+        } else if self.state == AppState::Selection
+            && let Some(user) = self.join_requests.front()
+        {
+            frame.render_widget(JoinPrompt(user), frame.area());
+        } else if let Some(picker) = &mut self.friend_picker {
+            let game = self
+                .active_game
+                .and_then(|id| self.service.get_game(id).ok())
+                .map(|game| game.name().to_string())
+                .unwrap_or_default();
+            let widget = FriendPicker {
+                game: &game,
+                friends: &self.friends,
+                deliveries: &self.invites,
+            };
+            frame.render_stateful_widget(widget, frame.area(), picker);
         }
     }
 
     fn handle_events(&mut self) -> io::Result<()> {
+        if !event::poll(TICK)? {
+            return Ok(());
+        }
+
         let event = event::read()?;
         match event {
             // it's important to check that the event is a key press event as
@@ -114,23 +323,124 @@ impl<'a> App<'a> {
     }
 
     fn handle_key_event(&mut self, event: Event) {
+        // Raw mode turns Ctrl+C into a key press instead of SIGINT.
+        if let Event::Key(key_event) = event
+            && key_event.code == KeyCode::Char('c')
+            && key_event.modifiers.contains(KeyModifiers::CONTROL)
+        {
+            self.exit();
+            return;
+        }
+
+        if self.showing_error {
+            if let Event::Key(key_event) = event
+                && matches!(key_event.code, KeyCode::Esc | KeyCode::Char('!'))
+            {
+                self.showing_error = false;
+            }
+            return;
+        }
+
+        if self.state == AppState::Selection && !self.join_requests.is_empty() {
+            if let Event::Key(key_event) = event {
+                match key_event.code {
+                    KeyCode::Char('y') => self.answer_join_request(JoinReply::Accept),
+                    KeyCode::Char('n') => self.answer_join_request(JoinReply::Decline),
+                    // Ignored without a reply, Discord drops the request after a while.
+                    KeyCode::Esc => {
+                        self.join_requests.pop_front();
+                    }
+                    _ => {}
+                }
+            }
+            return;
+        }
+
+        if let Some(picker) = &mut self.friend_picker {
+            if let Event::Key(key_event) = event {
+                match key_event.code {
+                    KeyCode::Up => picker.select_previous(),
+                    KeyCode::Down => picker.select_next(),
+                    KeyCode::Char('j') => self.invite_selected(InviteKind::Join),
+                    KeyCode::Char('s') => self.invite_selected(InviteKind::Spectate),
+                    KeyCode::Esc | KeyCode::Char('i') => self.friend_picker = None,
+                    _ => {}
+                }
+            }
+            return;
+        }
+
+        if let Some(panel) = &mut self.friends_panel {
+            if let Event::Key(key_event) = event {
+                match key_event.code {
+                    KeyCode::Up => panel.select_previous(),
+                    KeyCode::Down => panel.select_next(),
+                    KeyCode::Enter => self.jump_to_friend_game(),
+                    KeyCode::Esc | KeyCode::Char('f') => self.friends_panel = None,
+                    _ => {}
+                }
+            }
+            return;
+        }
+
+        if let Some(pane) = &mut self.lobby_pane {
+            if let Event::Key(key_event) = event {
+                let control = key_event.modifiers.contains(KeyModifiers::CONTROL);
+                match key_event.code {
+                    KeyCode::Esc => self.lobby_pane = None,
+                    KeyCode::Enter => self.submit_lobby_input(),
+                    KeyCode::Char('n') if control => self.open_lobby(),
+                    KeyCode::Char('l') if control => self.leave_lobby(),
+                    _ => {
+                        pane.handle_event(&event);
+                    }
+                }
+            }
+            return;
+        }
+
+        if self.state.is_filtering() {
+            self.handle_filter_event(event);
+            return;
+        }
+
         if self.state == AppState::Selection
             && let Event::Key(key_event) = event
         {
             match key_event.code {
                 event::KeyCode::Char('q') => self.exit(),
                 event::KeyCode::Char('u') => self.select_none(),
+                event::KeyCode::Char('c') => {
+                    self.notice = Some(Notice::info("Presence cleared"));
+                    self.clear_presence();
+                }
+                event::KeyCode::Char('!') if self.last_error.is_some() => {
+                    self.showing_error = true;
+                }
+                event::KeyCode::Char('i') if self.active_game.is_some() => {
+                    self.friend_picker = Some(ListState::default().with_selected(Some(0)));
+                }
+                event::KeyCode::Char('i') => {
+                    self.notice = Some(Notice::error("Activate a game to invite friends to it"));
+                }
+                event::KeyCode::Char('f') => {
+                    self.friends_panel = Some(ListState::default().with_selected(Some(0)));
+                }
+                event::KeyCode::Char('l') => self.lobby_pane = Some(LobbyPane::default()),
                 event::KeyCode::Char('e') if let Some(game) = self.selected_game() => {
-                    self.switch_state_to(AppState::Editing(game.into()));
+                    self.switch_state_to(AppState::Editing(AppInput::from(&game)));
                 }
                 event::KeyCode::Char('a') => {
                     self.switch_state_to(AppState::Adding(AppInput::default()))
                 }
                 event::KeyCode::Char('d') => self.delete_selected(),
-                event::KeyCode::Char('s') => {
-                    if let Err(err) = self.update_gamefile() {
-                        error!("Error while saving the config: {err}")
-                    }
+                event::KeyCode::Char('D') => self.delete_filtered(),
+                event::KeyCode::Char('+') => self.bump_party(1),
+                event::KeyCode::Char('-') => self.bump_party(-1),
+                event::KeyCode::Char('z') => self.undo(),
+                event::KeyCode::Char('y') => self.redo(),
+                event::KeyCode::Char('/') => {
+                    self.switch_state_to(AppState::Filtering(FilterInput::new(&self.filter)))
                 }
                 event::KeyCode::Up => self.select_previous(),
                 event::KeyCode::Down => self.select_next(),
@@ -146,14 +456,27 @@ impl<'a> App<'a> {
         {
             match key_event.code {
                 event::KeyCode::Enter => {
-                    let game: Game = if let AppState::Adding(ref input) = self.state {
-                        input.into()
+                    let req = if let AppState::Adding(ref mut input) = self.state {
+                        if let Err((selection, err)) = input.check_assets(&self.assets) {
+                            error!("Invalid image key: {err}");
+                            input.select(selection);
+                            return;
+                        }
+                        if let Err((selection, err)) = input.check_templates() {
+                            error!("Invalid template: {err}");
+                            input.select(selection);
+                            return;
+                        }
+                        GameCreateRequest::try_from(&*input)
                     } else {
                         unreachable!()
                     };
 
-                    self.add_game(game);
-                    self.state = AppState::Selection;
+                    match req {
+                        Ok(req) if self.add_game(&req) => self.state = AppState::Selection,
+                        Ok(_) => {}
+                        Err(err) => error!("Invalid game: {err}"),
+                    }
                 }
                 event::KeyCode::Esc => self.state = AppState::Selection,
                 _ if let AppState::Adding(ref mut input) = self.state => {
@@ -168,14 +491,29 @@ impl<'a> App<'a> {
         {
             match key_event.code {
                 event::KeyCode::Enter => {
-                    let game: Game = if let AppState::Editing(ref input) = self.state {
-                        input.into()
+                    let req = if let AppState::Editing(ref mut input) = self.state {
+                        if let Err((selection, err)) = input.check_assets(&self.assets) {
+                            error!("Invalid image key: {err}");
+                            input.select(selection);
+                            return;
+                        }
+                        if let Err((selection, err)) = input.check_templates() {
+                            error!("Invalid template: {err}");
+                            input.select(selection);
+                            return;
+                        }
+                        GameCreateRequest::try_from(&*input)
                     } else {
                         unreachable!()
                     };
 
-                    self.replace_current_selection(game);
-                    self.state = AppState::Selection;
+                    match req {
+                        Ok(req) if self.replace_current_selection(&req).is_some() => {
+                            self.state = AppState::Selection
+                        }
+                        Ok(_) => {}
+                        Err(err) => error!("Invalid game: {err}"),
+                    }
                 }
                 event::KeyCode::Esc => self.state = AppState::Selection,
                 _ if let AppState::Editing(ref mut input) = self.state => {
@@ -186,6 +524,33 @@ impl<'a> App<'a> {
         }
     }
 
+    fn handle_filter_event(&mut self, event: Event) {
+        let AppState::Filtering(ref mut input) = self.state else {
+            return;
+        };
+
+        if let Event::Key(key_event) = event {
+            match key_event.code {
+                event::KeyCode::Enter => self.state = AppState::Selection,
+                event::KeyCode::Esc => {
+                    self.filter = GameFilter::default();
+                    self.state = AppState::Selection;
+                    self.select_first();
+                }
+                _ => {
+                    input.handle_event(&event);
+                    // Apply as the user types, keeping the last valid filter on errors.
+                    if let Ok(filter) = input.filter()
+                        && filter != self.filter
+                    {
+                        self.filter = filter;
+                        self.select_first();
+                    }
+                }
+            }
+        }
+    }
+
     const fn exit(&mut self) {
         self.exit = true;
     }
@@ -215,9 +580,115 @@ impl<'a> App<'a> {
     }
 
     fn delete_selected(&mut self) {
-        if let Some(index) = self.list_state.selected() {
-            let game = self.list.remove(index);
-            info!("Deleted: {game:?}");
+        // Takes the presence of the active game down first, so the playtime of its session is
+        // part of the game an undo restores.
+        if let Some(game) = self.selected_game()
+            && self.active_game == Some(game.id())
+        {
+            self.clear_presence();
+        }
+
+        if let Some(game) = self.selected_game() {
+            match self
+                .service
+                .delete_game(&GameDeleteRequest::from(game.id()))
+            {
+                Ok(()) => {
+                    info!("Deleted: {game:?}");
+                    self.history.record(Change::Delete(game));
+                }
+                Err(err) => error!("Error while deleting the game: {err}"),
+            }
+        }
+    }
+
+    /// Deletes every game the filter lets through, as one change to undo.
+    fn delete_filtered(&mut self) {
+        if self.filter.is_empty() {
+            self.notice = Some(Notice::error(
+                "Filter the list to delete the games it shows",
+            ));
+            return;
+        }
+        if self
+            .active_game
+            .is_some_and(|id| self.visible_games().iter().any(|game| game.id() == id))
+        {
+            self.clear_presence();
+        }
+
+        let mut deleted = Vec::new();
+        for game in self.visible_games() {
+            match self
+                .service
+                .delete_game(&GameDeleteRequest::from(game.id()))
+            {
+                Ok(()) => deleted.push(Change::Delete(game)),
+                Err(err) => error!("Error while deleting {}: {err}", game.name()),
+            }
+        }
+        if deleted.is_empty() {
+            return;
+        }
+
+        info!("Deleted {} games", deleted.len());
+        self.notice = Some(Notice::info(format!("Deleted {} games", deleted.len())));
+        self.history.record(Change::Batch(deleted));
+    }
+
+    /// Changes the party size of the selected game by `delta` members, within the party's limits.
+    fn bump_party(&mut self, delta: i32) {
+        let Some(game) = self.selected_game() else {
+            return;
+        };
+        let Some(party) = game.party() else {
+            return;
+        };
+
+        let bumped = party
+            .clone()
+            .with_size(party.size().saturating_add_signed(delta));
+        if bumped != *party {
+            let req = GameCreateRequest::from(&game).with_party(Some(bumped));
+            self.replace_current_selection(&req);
+        }
+    }
+
+    fn undo(&mut self) {
+        match self.history.undo(&self.service) {
+            Ok(Some(change)) => {
+                info!("Undone: {change}");
+                self.refresh_active(&change);
+            }
+            Ok(None) => {}
+            Err(err) => error!("Error while undoing: {err}"),
+        }
+    }
+
+    fn redo(&mut self) {
+        match self.history.redo(&self.service) {
+            Ok(Some(change)) => {
+                info!("Redone: {change}");
+                self.refresh_active(&change);
+            }
+            Ok(None) => {}
+            Err(err) => error!("Error while redoing: {err}"),
+        }
+    }
+
+    /// Keeps the presence in line with the active game after `change` was undone or redone.
+    fn refresh_active(&mut self, change: &Change) {
+        let Some(id) = self.active_game.filter(|&id| change.affects(id)) else {
+            return;
+        };
+
+        match self.service.get_game(id) {
+            Ok(game) => self.activate(game),
+            Err(_) => {
+                // The game is gone, and its session with it.
+                self.session_start = None;
+                self.clear_presence();
+            }
         }
     }
 
@@ -225,33 +696,1149 @@ impl<'a> App<'a> {
         self.state = state;
     }
 
+    /// The games matching the current filter, in list order.
+    fn visible_games(&self) -> Vec<Game> {
+        let mut games = self.service.list_games().unwrap_or_default();
+        games.retain(|game| self.filter.matches(game));
+        games
+    }
+
     #[inline]
     fn selected_game(&self) -> Option<Game> {
-        self.list.get(self.list_state.selected()?).cloned()
+        let games = self.visible_games();
+        games.get(self.list_state.selected()?).cloned()
     }
 
+    /// Activates the selected game, or clears the presence if nothing is selected.
     fn activate_current(&mut self) {
-        let mut game = match self.selected_game() {
-            Some(game) => game.clone(),
-            None => Game::default(),
+        // Announced up front, a failed update replaces the notice with its error.
+        match self.selected_game() {
+            Some(game) => {
+                self.notice = Some(Notice::info(format!("Showing {}", game.name())));
+                self.activate(game);
+            }
+            None => {
+                self.notice = Some(Notice::info("Presence cleared"));
+                self.clear_presence();
+            }
+        }
+    }
+
+    /// Shows `game` as the current activity. The elapsed time keeps counting while the same game
+    /// stays active, e.g. when it is edited, and restarts when another game is activated.
+    fn activate(&mut self, game: Game) {
+        if self.active_game != Some(game.id()) {
+            self.end_session();
+            self.session_start = Some(Utc::now());
+        }
+        self.active_game = Some(game.id());
+
+        let now = Utc::now();
+        let game = self.resolve(&game);
+        self.rendered_minutes = (now - self.session_start.unwrap_or(now)).num_minutes();
+        let presence = self.render_presence(&game, self.session_start, now);
+
+        let result = self.presence.set(&presence);
+        self.report("Cannot update the presence", result);
+    }
+
+    /// `game` with the defaults of its platform filled in.
+    fn resolve(&self, game: &Game) -> Game {
+        self.service.resolve_game(game).unwrap_or_else(|err| {
+            error!("Cannot look up the platform of {}: {err}", game.name());
+            game.clone()
+        })
+    }
+
+    /// The presence of the resolved `game` at `now`, in a session that started at `start`.
+    fn render_presence(
+        &self,
+        game: &Game,
+        start: Option<DateTime<Utc>>,
+        now: DateTime<Utc>,
+    ) -> Presence {
+        let elapsed = Playtime::from(now - start.unwrap_or(now));
+        let presence = self.with_lobby(game, Presence::render(game, &self.templates, elapsed));
+        if game.show_elapsed().unwrap_or(self.show_elapsed) {
+            presence.with_start(start)
+        } else {
+            presence
+        }
+    }
+
+    /// `presence` of `game`, letting friends join the lobby instead if it was opened for `game`.
+    /// Discord only offers to join with a party, so the lobby stands in for one the game lacks.
+    fn with_lobby(&self, game: &Game, presence: Presence) -> Presence {
+        let Some(room) = self
+            .lobby
+            .as_ref()
+            .filter(|room| room.game() == Some(game.id()))
+        else {
+            return presence;
+        };
+        let lobby = room.lobby();
+        let Ok(secret) = Secret::new(lobby.secret()) else {
+            return presence;
+        };
+
+        let party = presence.party().cloned().or_else(|| {
+            let size = u32::try_from(room.members().len()).unwrap_or(u32::MAX);
+            Party::new(&lobby.id().to_string(), size.max(1), lobby.capacity()).ok()
+        });
+        let spectate = presence.secrets().spectate().cloned();
+        presence
+            .with_secrets(Secrets::new(Some(secret), spectate))
+            .with_party(party)
+    }
+
+    /// What the selected game shows once activated, or shows already if it is active.
+    fn preview(&self) -> PresencePreview {
+        let Some(game) = self.selected_game() else {
+            return PresencePreview::default();
+        };
+
+        let now = Utc::now();
+        let start = if self.active_game == Some(game.id()) {
+            self.session_start
+        } else {
+            Some(now)
         };
+        let game = self.resolve(&game);
+        let values = Placeholders::new(&game, Playtime::from(now - start.unwrap_or(now)));
 
-        game.generate_activity();
-        let activity = game.activity;
+        PresencePreview::new(self.render_presence(&game, start, now)).with_cut(
+            self.templates.state_for(&game).overflows(&values),
+            self.templates.details_for(&game).overflows(&values),
+        )
+    }
+
+    /// Re-renders the presence once a minute if the active game shows the time as text.
+    fn refresh_time(&mut self) {
+        let (Some(id), Some(start)) = (self.active_game, self.session_start) else {
+            return;
+        };
+        let minutes = (Utc::now() - start).num_minutes();
+        if minutes == self.rendered_minutes {
+            return;
+        }
+        self.rendered_minutes = minutes;
+
+        match self.service.get_game(id) {
+            Ok(game) if self.templates.mentions_time(&game) => self.activate(game),
+            Ok(_) => {}
+            Err(err) => error!("Cannot refresh the presence: {err}"),
+        }
+    }
+
+    /// Adds the session of the active game to its playtime and ends the session.
+    fn end_session(&mut self) {
+        if let (Some(id), Some(start)) = (self.active_game.take(), self.session_start.take())
+            && let Err(err) = self
+                .service
+                .add_playtime(id, Playtime::from(Utc::now() - start))
+        {
+            error!("Cannot record the playtime: {err}");
+        }
+    }
 
-        if let Some(ref activity) = activity {
-            self.discord.update_activity(activity, |_discord, result| {
-                if let Err(error) = result {
-                    eprintln!("failed to update activity: {error}");
+    /// Queues join requests for the prompt and runs the hook when we join someone else's game.
+    fn handle_presence_events(&mut self) {
+        for event in self.presence.take_events() {
+            match event {
+                PresenceEvent::JoinRequest(user) => {
+                    self.notice = Some(Notice::info(format!("{} asks to join", user.name())));
+                    self.join_requests.push_back(user);
+                }
+                PresenceEvent::Join(secret) => {
+                    self.notice = Some(Notice::info("Joining through Discord"));
+                    self.run_join_hook("join", &[("CONSOLE_PLAYER_SECRET", &secret)]);
+                    if self.lobby.is_none() && is_lobby_secret(&secret) {
+                        let result = self.presence.lobby(LobbyCommand::Join(secret));
+                        self.report("Cannot join the lobby", result);
+                    }
+                }
+                PresenceEvent::Spectate(secret) => {
+                    self.notice = Some(Notice::info("Spectating through Discord"));
+                    self.run_join_hook("spectate", &[("CONSOLE_PLAYER_SECRET", &secret)]);
+                }
+                PresenceEvent::InviteSent(user) => {
+                    if let Some(Delivery::Sending(kind)) = self.invites.get(&user) {
+                        self.invites.insert(user, Delivery::Sent(*kind));
+                    }
+                    let name = self.friend_name(user);
+                    self.notice = Some(Notice::info(format!("Invite to {name} delivered")));
+                }
+                PresenceEvent::InviteFailed(user, reason) => {
+                    let name = self.friend_name(user);
+                    self.report(
+                        &format!("Cannot invite {name}"),
+                        Err(anyhow::anyhow!(reason.clone()).into()),
+                    );
+                    self.invites.insert(user, Delivery::Failed(reason));
+                }
+                PresenceEvent::LobbyJoined(lobby) => {
+                    self.notice = Some(Notice::info(format!("In lobby {}", lobby.id())));
+                    self.lobby = Some(LobbyRoom::new(lobby, self.active_game));
+                    self.refresh_active_presence();
+                }
+                PresenceEvent::LobbyClosed(id) => {
+                    if self
+                        .lobby
+                        .as_ref()
+                        .is_some_and(|room| room.lobby().id() == id)
+                    {
+                        self.notice = Some(Notice::info(format!("Left lobby {id}")));
+                        self.lobby = None;
+                        self.refresh_active_presence();
+                    }
                 }
-            });
+                PresenceEvent::LobbyFailed(reason) => {
+                    self.report("Lobby error", Err(anyhow::anyhow!(reason).into()));
+                }
+                event @ (PresenceEvent::LobbyMemberJoined(..)
+                | PresenceEvent::LobbyMemberLeft(..)) => {
+                    if let Some(room) = &mut self.lobby
+                        && room.apply(&event)
+                    {
+                        // The party size follows the members.
+                        self.refresh_active_presence();
+                    }
+                }
+                event @ PresenceEvent::LobbyMessage(..) => {
+                    if let Some(room) = &mut self.lobby
+                        && room.apply(&event)
+                        && self.lobby_pane.is_none()
+                        && let Some((sender, _)) = room.messages().next_back()
+                    {
+                        self.notice = Some(Notice::info(format!("Lobby message from {sender}")));
+                    }
+                }
+                event => {
+                    self.friends.apply(&event);
+                }
+            }
         }
     }
 
-    fn update_gamefile(&mut self) -> Result<(), crate::Error> {
-        self.gamefile = GameFile::try_from(&self.list)?;
-        self.gamefile.write()?;
+    /// Shows the active game again, e.g. after the lobby tied to it changed.
+    fn refresh_active_presence(&mut self) {
+        let Some(id) = self.active_game else {
+            return;
+        };
+        match self.service.get_game(id) {
+            Ok(game) => self.activate(game),
+            Err(err) => error!("Cannot refresh the presence: {err}"),
+        }
+    }
 
-        Ok(())
+    /// Sends the text typed in the lobby pane to the lobby, or joins the lobby whose secret was
+    /// typed if not in one.
+    fn submit_lobby_input(&mut self) {
+        let Some(pane) = &mut self.lobby_pane else {
+            return;
+        };
+        let text = pane.value().to_string();
+        if text.is_empty() {
+            return;
+        }
+
+        let Some(room) = &mut self.lobby else {
+            if !is_lobby_secret(&text) {
+                self.notice = Some(Notice::error(format!("{text:?} is not a lobby secret")));
+                return;
+            }
+            pane.reset();
+            self.notice = Some(Notice::info("Joining the lobby"));
+            let result = self.presence.lobby(LobbyCommand::Join(text));
+            self.report("Cannot join the lobby", result);
+            return;
+        };
+
+        let len = text.chars().count();
+        if len > MAX_MESSAGE_LEN {
+            self.notice = Some(Notice::error(format!(
+                "Messages are at most {MAX_MESSAGE_LEN} characters, this one has {len}"
+            )));
+            return;
+        }
+        let result = self
+            .presence
+            .lobby(LobbyCommand::Send(room.lobby().id(), text.clone()));
+        if result.is_err() {
+            self.report("Cannot send the message", result);
+            return;
+        }
+        room.sent(text);
+        pane.reset();
+    }
+
+    /// Opens a lobby for the active game, with room for its party.
+    fn open_lobby(&mut self) {
+        if let Some(room) = &self.lobby {
+            self.notice = Some(Notice::error(format!(
+                "Leave lobby {} before opening another",
+                room.lobby().id()
+            )));
+            return;
+        }
+        let Some(game) = self
+            .active_game
+            .and_then(|id| self.service.get_game(id).ok())
+        else {
+            self.notice = Some(Notice::error("Activate a game to open a lobby for it"));
+            return;
+        };
+
+        let capacity = game.party().map_or(DEFAULT_LOBBY_CAPACITY, Party::max);
+        self.notice = Some(Notice::info(format!("Opening a lobby for {}", game.name())));
+        let result = self.presence.lobby(LobbyCommand::Create {
+            capacity,
+            game: game.name().to_string(),
+        });
+        self.report("Cannot open a lobby", result);
+    }
+
+    fn leave_lobby(&mut self) {
+        let Some(room) = &self.lobby else {
+            return;
+        };
+        let result = self.presence.lobby(LobbyCommand::Leave(room.lobby().id()));
+        self.report("Cannot leave the lobby", result);
+    }
+
+    /// Invites the friend selected in the picker to the active game.
+    fn invite_selected(&mut self, kind: InviteKind) {
+        let Some(friend) = self
+            .friend_picker
+            .as_ref()
+            .and_then(ListState::selected)
+            .and_then(|index| self.friends.get(index))
+            .cloned()
+        else {
+            return;
+        };
+        let Some(game) = self
+            .active_game
+            .and_then(|id| self.service.get_game(id).ok())
+        else {
+            return;
+        };
+
+        let secret = match kind {
+            InviteKind::Join => game.secrets().join(),
+            InviteKind::Spectate => game.secrets().spectate(),
+        };
+        if secret.is_none() {
+            self.notice = Some(Notice::error(format!(
+                "{} has no {kind} secret to invite with",
+                game.name()
+            )));
+            return;
+        }
+
+        let user = friend.user();
+        let result = self.presence.send_invite(user.id(), kind);
+        if result.is_err() {
+            self.report(&format!("Cannot invite {}", user.name()), result);
+            return;
+        }
+        self.invites.insert(user.id(), Delivery::Sending(kind));
+        self.notice = Some(Notice::info(format!(
+            "Inviting {} to {kind} {}",
+            user.name(),
+            game.name()
+        )));
+    }
+
+    /// Selects the library game the friend selected in the panel is playing, and closes the
+    /// panel to show its preview.
+    fn jump_to_friend_game(&mut self) {
+        let Some(friend) = self
+            .friends_panel
+            .as_ref()
+            .and_then(ListState::selected)
+            .and_then(|index| self.friends.get(index))
+        else {
+            return;
+        };
+        let name = friend.user().name().to_string();
+        let game = friend.activity().and_then(|activity| {
+            let games = self.service.list_games().ok()?;
+            games.into_iter().find(|game| activity.is_playing(game))
+        });
+        let Some(game) = game else {
+            self.notice = Some(Notice::error(format!(
+                "{name} is not playing a game from the library"
+            )));
+            return;
+        };
+
+        if !self.filter.matches(&game) {
+            self.filter = GameFilter::default();
+        }
+        let index = self
+            .visible_games()
+            .iter()
+            .position(|visible| visible.id() == game.id());
+        self.select(index);
+        self.friends_panel = None;
+        self.notice = Some(Notice::info(format!("{name} is playing {}", game.name())));
+    }
+
+    /// The name of the friend `id`, or the id if they are not known.
+    fn friend_name(&self, id: UserId) -> String {
+        self.friends
+            .find(id)
+            .map_or_else(|| id.to_string(), |friend| friend.user().name().to_string())
+    }
+
+    /// Replies to the oldest join request and runs the hook if the user was let in.
+    fn answer_join_request(&mut self, reply: JoinReply) {
+        let Some(user) = self.join_requests.pop_front() else {
+            return;
+        };
+
+        let result = self.presence.reply_to_join(user.id(), reply);
+        if result.is_err() {
+            self.report("Cannot answer the join request", result);
+            return;
+        }
+
+        match reply {
+            JoinReply::Accept => {
+                self.notice = Some(Notice::info(format!("Let {} join", user.name())));
+                let game = self
+                    .active_game
+                    .and_then(|id| self.service.get_game(id).ok());
+                let name = game.as_ref().map_or("", |game| game.name().as_str());
+                let secret = game
+                    .as_ref()
+                    .and_then(|game| game.secrets().join())
+                    .map_or("", |secret| secret.as_str());
+                let id = user.id().to_string();
+                self.run_join_hook(
+                    "accept",
+                    &[
+                        ("CONSOLE_PLAYER_SECRET", secret),
+                        ("CONSOLE_PLAYER_USER", user.name()),
+                        ("CONSOLE_PLAYER_USER_ID", &id),
+                        ("CONSOLE_PLAYER_GAME", name),
+                    ],
+                );
+            }
+            JoinReply::Decline => {
+                self.notice = Some(Notice::info(format!("Declined {}", user.name())));
+            }
+        }
+    }
+
+    /// Runs the `--join-hook`, if any, for `event` with `vars` describing it.
+    fn run_join_hook(&mut self, event: &str, vars: &[(&str, &str)]) {
+        let Some(hook) = &self.join_hook else {
+            return;
+        };
+
+        let vars = vars
+            .iter()
+            .copied()
+            .chain([("CONSOLE_PLAYER_EVENT", event)]);
+        if let Err(err) = hook.run(vars) {
+            error!("Cannot run the join hook {:?}: {err}", hook.command());
+            self.notice = Some(Notice::error(format!("Cannot run the join hook: {err}")));
+        }
+    }
+
+    /// Shows a failed presence operation in the status bar and keeps it for the details view.
+    fn report(&mut self, context: &str, result: Result<(), PresenceError>) {
+        let Err(err) = result else {
+            return;
+        };
+
+        error!("{context}: {err}");
+        self.notice = Some(Notice::error(format!("{context}: {err}")));
+        self.last_error = Some(Notice::error(format!("{context}: {err:#}")));
+    }
+
+    fn expire_notice(&mut self) {
+        if self
+            .notice
+            .as_ref()
+            .is_some_and(|notice| notice.has_expired(Utc::now()))
+        {
+            self.notice = None;
+        }
+    }
+
+    /// Removes the presence altogether and ends the session.
+    fn clear_presence(&mut self) {
+        self.end_session();
+
+        let result = self.presence.clear();
+        self.report("Cannot clear the presence", result);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+
+    use crate::{
+        app::App,
+        domain::{
+            asset::AssetManifest,
+            game::{
+                Game, GameCreateRequest, GameFilter, GameId, GameService, Image, Name, Party,
+                Platform, Playtime, Secret, Secrets, Service, Template, Tooltip,
+            },
+            platform::{PlatformDefinition, PlatformService},
+            presence::{
+                ActivityTemplates, Friend, FriendActivity, InviteKind, JoinReply, OnlineStatus,
+                Presence, PresenceEvent, PresencePublisher, PresenceStatus, User, UserId,
+                lobby::{LobbyCommand, LobbyMessage, MAX_MESSAGE_LEN, Sender},
+            },
+        },
+        game::friends::Delivery,
+        outbound::{CommandHook, InMemoryGameRepository, InMemoryPresence},
+    };
+    #[cfg(unix)]
+    use crate::{
+        domain::presence::connection::Reconnecting,
+        outbound::{DiscordIpcPresence, FakeDiscordServer},
+    };
+    use std::{
+        fs,
+        sync::atomic::Ordering,
+        thread,
+        time::{Duration, Instant},
+    };
+
+    use chrono::{TimeDelta, Utc};
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    fn key(code: KeyCode) -> Event {
+        Event::Key(KeyEvent::new(code, KeyModifiers::NONE))
+    }
+
+    fn game(name: &str) -> Game {
+        let req = GameCreateRequest::builder()
+            .name(Name::new(name).unwrap())
+            .platform(Platform::new("Nintendo Switch").unwrap())
+            .build();
+        Game::new(GameId::generate(), &req)
+    }
+
+    #[test]
+    fn presence_follows_the_active_game() {
+        let mario = game("mario");
+        let repo: InMemoryGameRepository = [mario.clone()].into_iter().collect();
+        let mut app = App::new(
+            Service::new(repo),
+            InMemoryPresence::new(),
+            AssetManifest::default(),
+        );
+
+        app.select_first();
+        app.handle_key_event(key(KeyCode::Enter));
+        assert_eq!(app.presence.current(), Some(&Presence::from(&mario)));
+
+        app.replace_current_selection(&GameCreateRequest::from(&game("mario kart")));
+        assert_eq!(
+            app.presence.current().map(Presence::state),
+            Some("mario kart")
+        );
+
+        app.presence.fail_next();
+        app.handle_key_event(key(KeyCode::Enter));
+        assert_eq!(app.presence.published().len(), 2);
+    }
+
+    #[test]
+    fn presence_is_cleared_when_the_active_game_goes_away() {
+        let repo: InMemoryGameRepository = [game("mario")].into_iter().collect();
+        let mut app = App::new(
+            Service::new(repo),
+            InMemoryPresence::new(),
+            AssetManifest::default(),
+        );
+
+        app.select_first();
+        app.handle_key_event(key(KeyCode::Enter));
+        app.handle_key_event(key(KeyCode::Char('d')));
+        assert_eq!(app.presence.current(), None);
+        assert_eq!(app.active_game, None);
+
+        // Undoing the delete brings the game back, but does not show it again.
+        app.handle_key_event(key(KeyCode::Char('z')));
+        app.select_first();
+        app.handle_key_event(key(KeyCode::Enter));
+        assert!(app.presence.current().is_some());
+        app.redo();
+        assert_eq!(app.presence.current(), None);
+
+        app.add_game(&GameCreateRequest::from(&game("zelda")));
+        app.select_first();
+        app.handle_key_event(key(KeyCode::Enter));
+        assert_eq!(app.presence.current().map(Presence::state), Some("zelda"));
+        app.handle_key_event(key(KeyCode::Char('z')));
+        assert_eq!(app.presence.current(), None);
+        assert_eq!(app.active_game, None);
+    }
+
+    #[test]
+    fn filtered_games_are_deleted_as_one_change() {
+        let repo: InMemoryGameRepository = ["mario", "mario kart", "zelda"]
+            .into_iter()
+            .map(game)
+            .collect();
+        let mut app = App::new(
+            Service::new(repo),
+            InMemoryPresence::new(),
+            AssetManifest::default(),
+        );
+        let names = |app: &App<_, _>| {
+            app.visible_games()
+                .iter()
+                .map(|game| game.name().to_string())
+                .collect::<Vec<_>>()
+        };
+
+        app.handle_key_event(key(KeyCode::Char('D')));
+        assert_eq!(names(&app).len(), 3);
+
+        app.filter = "mario".parse().unwrap();
+        app.select_first();
+        app.handle_key_event(key(KeyCode::Enter));
+        app.handle_key_event(key(KeyCode::Char('D')));
+        assert!(names(&app).is_empty());
+        assert_eq!(app.presence.current(), None);
+        assert_eq!(
+            app.history.next_undo().map(ToString::to_string).as_deref(),
+            Some("delete 2 games")
+        );
+
+        app.handle_key_event(key(KeyCode::Char('z')));
+        app.filter = GameFilter::default();
+        let mut restored = names(&app);
+        restored.sort();
+        assert_eq!(restored, ["mario", "mario kart", "zelda"]);
+    }
+
+    #[test]
+    fn presence_failures_show_up_as_notices() {
+        let repo: InMemoryGameRepository = [game("mario")].into_iter().collect();
+        let mut app = App::new(
+            Service::new(repo),
+            InMemoryPresence::new(),
+            AssetManifest::default(),
+        );
+        let notice = |app: &App<_, _>| app.notice.as_ref().map(|n| n.message().to_string());
+
+        app.select_first();
+        app.handle_key_event(key(KeyCode::Enter));
+        assert_eq!(notice(&app).as_deref(), Some("Showing mario"));
+        app.handle_key_event(key(KeyCode::Char('!')));
+        assert!(!app.showing_error);
+
+        app.presence.fail_next();
+        app.handle_key_event(key(KeyCode::Char('c')));
+        assert_eq!(
+            notice(&app).as_deref(),
+            Some("Cannot clear the presence: injected presence failure")
+        );
+        app.handle_key_event(key(KeyCode::Char('!')));
+        assert!(app.showing_error);
+        // The details swallow keys until they are closed.
+        app.handle_key_event(key(KeyCode::Char('q')));
+        app.handle_key_event(key(KeyCode::Esc));
+        assert!(!app.showing_error && !app.exit);
+        assert!(app.last_error.is_some());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn activating_sends_the_resolved_activity_over_ipc() {
+        let server = FakeDiscordServer::start().unwrap();
+        let mario = Game::builder()
+            .id(GameId::generate())
+            .name(Name::new("Mario Kart 8").unwrap())
+            .platform(Platform::new("switch").unwrap())
+            .large_image(Image::from(String::from("mariokart8")))
+            .build();
+        let service = Service::new([mario].into_iter().collect::<InMemoryGameRepository>());
+        service
+            .create_platform(
+                &PlatformDefinition::builder()
+                    .name(Platform::new("Nintendo Switch").unwrap())
+                    .aliases(vec![Platform::new("Switch").unwrap()])
+                    .small_image(Image::from(String::from("switch")))
+                    .small_tooltip(Tooltip::from(String::from("Nintendo Switch")))
+                    .build(),
+            )
+            .unwrap();
+        let presence = DiscordIpcPresence::connect_to(server.path(), 42).unwrap();
+        let mut app = App::new(service, presence, AssetManifest::default());
+
+        app.select_first();
+        app.handle_key_event(key(KeyCode::Enter));
+
+        assert_eq!(
+            server.wait_for_activities(1),
+            [Some(json!({
+                "state": "Mario Kart 8",
+                "details": "Nintendo Switch",
+                "assets": {
+                    "large_image": "mariokart8",
+                    "small_image": "switch",
+                    "small_text": "Nintendo Switch",
+                },
+            }))]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn the_active_game_is_applied_once_connected() {
+        let server = FakeDiscordServer::start().unwrap();
+        let path = server.path().to_owned();
+        let presence = Reconnecting::new(move || {
+            DiscordIpcPresence::connect_to(&path, 42).map_err(|err| anyhow::Error::from(err).into())
+        });
+        let repo: InMemoryGameRepository = [game("mario")].into_iter().collect();
+        let mut app = App::new(Service::new(repo), presence, AssetManifest::default());
+
+        app.select_first();
+        app.handle_key_event(key(KeyCode::Enter));
+        assert_eq!(app.presence.status(), PresenceStatus::Connecting);
+        assert_eq!(server.activities(), []);
+
+        app.presence.poll().unwrap();
+        assert_eq!(app.presence.status(), PresenceStatus::Connected);
+        assert_eq!(
+            server.wait_for_activities(1)[0].as_ref().unwrap()["state"],
+            "mario"
+        );
+
+        server.disconnect();
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while app.presence.status() == PresenceStatus::Connected && Instant::now() < deadline {
+            let _ = app.presence.poll();
+            thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(app.presence.status(), PresenceStatus::Connecting);
+    }
+
+    #[test]
+    fn join_requests_are_prompted_for_and_accepts_run_the_hook() {
+        let mario = Game::builder()
+            .id(GameId::generate())
+            .name(Name::new("mario").unwrap())
+            .platform(Platform::new("switch").unwrap())
+            .secrets(Secrets::new(Some(Secret::new("room-42").unwrap()), None))
+            .build();
+        let repo: InMemoryGameRepository = [mario].into_iter().collect();
+        let mut app = App::new(
+            Service::new(repo),
+            InMemoryPresence::new(),
+            AssetManifest::default(),
+        );
+        let out = std::env::temp_dir().join(format!("console-player-{}-hook", std::process::id()));
+        let _ = fs::remove_file(&out);
+        app.join_hook = Some(CommandHook::new(format!(
+            r#"printf '%s %s %s %s' "$CONSOLE_PLAYER_EVENT" "$CONSOLE_PLAYER_USER" "$CONSOLE_PLAYER_USER_ID" "$CONSOLE_PLAYER_SECRET" > "{0}.tmp" && mv "{0}.tmp" "{0}""#,
+            out.display()
+        )));
+        let luigi = User::new(UserId::new(7), String::from("luigi"));
+        let peach = User::new(UserId::new(8), String::from("peach"));
+
+        app.select_first();
+        app.handle_key_event(key(KeyCode::Enter));
+        app.presence
+            .push_event(PresenceEvent::JoinRequest(luigi.clone()));
+        app.presence
+            .push_event(PresenceEvent::JoinRequest(peach.clone()));
+        app.handle_presence_events();
+        assert_eq!(app.join_requests, [luigi.clone(), peach.clone()]);
+
+        // The prompt swallows keys until it is answered.
+        app.handle_key_event(key(KeyCode::Char('q')));
+        assert!(!app.exit);
+        app.handle_key_event(key(KeyCode::Char('n')));
+        app.handle_key_event(key(KeyCode::Char('y')));
+        assert_eq!(
+            app.presence.replies(),
+            [
+                (luigi.id(), JoinReply::Decline),
+                (peach.id(), JoinReply::Accept)
+            ]
+        );
+        assert!(app.join_requests.is_empty());
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while !out.exists() && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(fs::read_to_string(&out).unwrap(), "accept peach 8 room-42");
+        let _ = fs::remove_file(&out);
+    }
+
+    #[test]
+    fn friends_are_invited_to_the_active_game() {
+        let mario = Game::builder()
+            .id(GameId::generate())
+            .name(Name::new("mario").unwrap())
+            .platform(Platform::new("switch").unwrap())
+            .secrets(Secrets::new(Some(Secret::new("room-42").unwrap()), None))
+            .build();
+        let repo: InMemoryGameRepository = [mario].into_iter().collect();
+        let mut app = App::new(
+            Service::new(repo),
+            InMemoryPresence::scripted(),
+            AssetManifest::default(),
+        );
+        let friend = |id, name: &str| {
+            Friend::new(
+                User::new(UserId::new(id), name.to_string()),
+                OnlineStatus::Online,
+            )
+        };
+        let notice = |app: &App<_, _>| app.notice.as_ref().map(|n| n.message().to_string());
+        app.presence.push_event(PresenceEvent::Friends(vec![
+            friend(7, "luigi"),
+            friend(8, "peach"),
+        ]));
+        app.handle_presence_events();
+
+        // Invites need an active game.
+        app.handle_key_event(key(KeyCode::Char('i')));
+        assert!(app.friend_picker.is_none());
+
+        app.select_first();
+        app.handle_key_event(key(KeyCode::Enter));
+        app.handle_key_event(key(KeyCode::Char('i')));
+        app.handle_key_event(key(KeyCode::Down));
+        app.handle_key_event(key(KeyCode::Char('s')));
+        assert_eq!(
+            notice(&app).as_deref(),
+            Some("mario has no spectate secret to invite with")
+        );
+        app.handle_key_event(key(KeyCode::Char('j')));
+        assert_eq!(app.presence.invites(), [(UserId::new(8), InviteKind::Join)]);
+        assert_eq!(
+            app.invites.get(&UserId::new(8)),
+            Some(&Delivery::Sending(InviteKind::Join))
+        );
+
+        app.handle_presence_events();
+        assert_eq!(
+            app.invites.get(&UserId::new(8)),
+            Some(&Delivery::Sent(InviteKind::Join))
+        );
+        assert_eq!(notice(&app).as_deref(), Some("Invite to peach delivered"));
+
+        app.presence.push_event(PresenceEvent::InviteFailed(
+            UserId::new(7),
+            String::from("not playing"),
+        ));
+        app.handle_presence_events();
+        assert_eq!(
+            notice(&app).as_deref(),
+            Some("Cannot invite luigi: not playing")
+        );
+        app.handle_key_event(key(KeyCode::Esc));
+        assert!(app.friend_picker.is_none());
+
+        // Offline, nothing is delivered.
+        app.presence = InMemoryPresence::new();
+        app.handle_key_event(key(KeyCode::Char('i')));
+        app.handle_key_event(key(KeyCode::Char('j')));
+        app.handle_presence_events();
+        assert_eq!(
+            notice(&app).as_deref(),
+            Some("Cannot invite luigi: not connected")
+        );
+        assert!(app.presence.invites().is_empty());
+    }
+
+    #[test]
+    fn friends_panel_jumps_to_the_game_a_friend_plays() {
+        let repo: InMemoryGameRepository = [game("mario"), game("zelda"), game("metroid")]
+            .into_iter()
+            .collect();
+        let mut app = App::new(
+            Service::new(repo),
+            InMemoryPresence::new(),
+            AssetManifest::default(),
+        );
+        let friend = |id, name: &str, playing: Option<&str>| {
+            Friend::new(
+                User::new(UserId::new(id), name.to_string()),
+                OnlineStatus::Online,
+            )
+            .with_activity(playing.map(|game| {
+                FriendActivity::new(
+                    String::from("Console Player"),
+                    game.to_string(),
+                    String::new(),
+                )
+            }))
+        };
+        let selected = |app: &App<_, _>| app.selected_game().map(|game| game.name().to_string());
+        // The relationship feed: the list, then updates as friends start playing.
+        for event in [
+            PresenceEvent::Friends(vec![friend(7, "luigi", None), friend(8, "peach", None)]),
+            PresenceEvent::FriendUpdate(friend(8, "peach", Some("Zelda"))),
+            PresenceEvent::FriendUpdate(friend(7, "luigi", Some("Half-Life"))),
+        ] {
+            app.presence.push_event(event);
+        }
+        app.handle_presence_events();
+        app.filter = "metroid".parse().unwrap();
+
+        app.handle_key_event(key(KeyCode::Char('f')));
+        app.handle_key_event(key(KeyCode::Enter));
+        assert_eq!(
+            app.notice.as_ref().map(|n| n.message()),
+            Some("luigi is not playing a game from the library")
+        );
+        assert!(app.friends_panel.is_some());
+
+        app.handle_key_event(key(KeyCode::Down));
+        app.handle_key_event(key(KeyCode::Enter));
+        assert!(app.friends_panel.is_none());
+        assert!(app.filter.is_empty());
+        assert_eq!(selected(&app).as_deref(), Some("zelda"));
+    }
+
+    #[test]
+    fn lobbies_for_the_active_game_are_chatted_in_and_joined_through() {
+        let repo: InMemoryGameRepository = [game("mario")].into_iter().collect();
+        let mut app = App::new(
+            Service::new(repo),
+            InMemoryPresence::scripted(),
+            AssetManifest::default(),
+        );
+        let control = |c| Event::Key(KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL));
+        let luigi = User::new(UserId::new(7), String::from("luigi"));
+
+        app.handle_key_event(key(KeyCode::Char('l')));
+        app.handle_key_event(control('n'));
+        assert!(app.presence.lobby_commands().is_empty());
+        app.handle_key_event(key(KeyCode::Esc));
+
+        app.select_first();
+        app.handle_key_event(key(KeyCode::Enter));
+        app.handle_key_event(key(KeyCode::Char('l')));
+        app.handle_key_event(control('n'));
+        app.handle_presence_events();
+        let id = app.lobby.as_ref().unwrap().lobby().id();
+        let joinable = app.presence.current().unwrap();
+        assert_eq!(
+            joinable.secrets().join(),
+            Some(&Secret::new(&format!("{id}:secret")).unwrap())
+        );
+        assert_eq!(joinable.party(), Some(&Party::new("1", 1, 4).unwrap()));
+
+        app.presence
+            .push_event(PresenceEvent::LobbyMemberJoined(id, luigi.clone()));
+        app.presence.push_event(PresenceEvent::LobbyMessage(
+            id,
+            luigi.id(),
+            LobbyMessage::decode(&[0xff, 0xfe]),
+        ));
+        app.handle_presence_events();
+        // Keys go to the message being typed.
+        for c in "q hi ".chars() {
+            app.handle_key_event(key(KeyCode::Char(c)));
+        }
+        app.handle_key_event(key(KeyCode::Enter));
+        assert!(!app.exit);
+        let room = app.lobby.as_ref().unwrap();
+        assert_eq!(room.members(), [luigi]);
+        assert_eq!(
+            room.messages().cloned().collect::<Vec<_>>(),
+            [
+                (
+                    Sender::Member(String::from("luigi")),
+                    LobbyMessage::Binary(2)
+                ),
+                (Sender::Me, LobbyMessage::Text(String::from("q hi"))),
+            ]
+        );
+
+        for _ in 0..=MAX_MESSAGE_LEN {
+            app.handle_key_event(key(KeyCode::Char('a')));
+        }
+        app.handle_key_event(key(KeyCode::Enter));
+        app.handle_key_event(control('l'));
+        app.handle_presence_events();
+        assert!(app.lobby.is_none());
+        assert_eq!(
+            app.presence.lobby_commands()[1..],
+            [
+                LobbyCommand::Send(id, String::from("q hi")),
+                LobbyCommand::Leave(id)
+            ]
+        );
+        assert!(app.presence.current().unwrap().secrets().is_empty());
+
+        // Accepting an invite to someone else's lobby joins it.
+        app.presence
+            .push_event(PresenceEvent::Join(String::from("9:elsewhere")));
+        app.handle_presence_events();
+        app.handle_presence_events();
+        assert_eq!(
+            app.lobby.as_ref().map(|room| room.lobby().secret()),
+            Some("9:elsewhere")
+        );
+
+        // Offline, there are no lobbies to get into.
+        app.presence = InMemoryPresence::new();
+        app.lobby = None;
+        app.handle_key_event(control('n'));
+        app.handle_presence_events();
+        assert!(app.lobby.is_none());
+        assert_eq!(
+            app.notice.as_ref().map(|n| n.message()),
+            Some("Cannot open a lobby: not connected")
+        );
+    }
+
+    #[test]
+    fn elapsed_time_survives_edits_and_restarts_on_switch() {
+        let repo: InMemoryGameRepository = [game("mario"), game("zelda")].into_iter().collect();
+        let mut app = App::new(
+            Service::new(repo),
+            InMemoryPresence::new(),
+            AssetManifest::default(),
+        );
+        app.show_elapsed = true;
+        let start = |app: &App<_, InMemoryPresence>| app.presence.current().unwrap().start();
+
+        app.select_first();
+        app.handle_key_event(key(KeyCode::Enter));
+        assert!(start(&app).is_some());
+
+        // Pretend the session started a while ago.
+        let earlier = Utc::now() - TimeDelta::minutes(5);
+        app.session_start = Some(earlier);
+        app.replace_current_selection(&GameCreateRequest::from(&game("mario kart")));
+        assert_eq!(start(&app), Some(earlier));
+
+        app.handle_key_event(key(KeyCode::Down));
+        app.handle_key_event(key(KeyCode::Enter));
+        assert!(start(&app) > Some(earlier));
+
+        let hidden = GameCreateRequest::builder()
+            .name(Name::new("zelda").unwrap())
+            .platform(Platform::new("Nintendo Switch").unwrap())
+            .show_elapsed(false)
+            .build();
+        app.replace_current_selection(&hidden);
+        assert_eq!(start(&app), None);
+    }
+
+    #[test]
+    fn quick_keys_bump_the_party_size() {
+        let couch = GameCreateRequest::from(&game("mario"))
+            .with_party(Some(Party::new("couch", 1, 2).unwrap()));
+        let repo: InMemoryGameRepository = [Game::new(GameId::generate(), &couch), game("zelda")]
+            .into_iter()
+            .collect();
+        let mut app = App::new(
+            Service::new(repo),
+            InMemoryPresence::new(),
+            AssetManifest::default(),
+        );
+        let size = |app: &App<_, InMemoryPresence>| Some(app.presence.current()?.party()?.size());
+
+        app.select_first();
+        app.handle_key_event(key(KeyCode::Enter));
+        for code in [KeyCode::Char('+'), KeyCode::Char('+')] {
+            app.handle_key_event(key(code));
+        }
+        assert_eq!(size(&app), Some(2));
+        assert_eq!(
+            app.history.next_undo().map(ToString::to_string),
+            Some("edit mario".into())
+        );
+
+        for code in [KeyCode::Char('-'), KeyCode::Char('-')] {
+            app.handle_key_event(key(code));
+        }
+        assert_eq!(size(&app), Some(1));
+        app.handle_key_event(key(KeyCode::Char('z')));
+        assert_eq!(size(&app), Some(2));
+
+        // Games without a party are left alone, so nothing new is recorded.
+        app.handle_key_event(key(KeyCode::Down));
+        app.handle_key_event(key(KeyCode::Char('+')));
+        assert_eq!(app.selected_game().unwrap().party(), None);
+        assert!(app.history.next_redo().is_some());
+    }
+
+    #[test]
+    fn time_placeholders_refresh_and_sessions_add_up() {
+        let mario = game("mario");
+        let repo: InMemoryGameRepository = [mario.clone(), game("zelda")].into_iter().collect();
+        let mut app = App::new(
+            Service::new(repo),
+            InMemoryPresence::new(),
+            AssetManifest::default(),
+        );
+        app.templates = ActivityTemplates::new(
+            Template::new("{name} for {elapsed}").unwrap(),
+            Template::new("{playtime} in total").unwrap(),
+        );
+        let state =
+            |app: &App<_, InMemoryPresence>| app.presence.current().unwrap().state().to_string();
+
+        app.select_first();
+        app.handle_key_event(key(KeyCode::Enter));
+        assert_eq!(state(&app), "mario for 0m");
+
+        app.session_start = Some(Utc::now() - TimeDelta::minutes(5));
+        app.refresh_time();
+        assert_eq!(state(&app), "mario for 5m");
+        assert_eq!(app.presence.published().len(), 2);
+        app.refresh_time();
+        assert_eq!(app.presence.published().len(), 2);
+
+        app.handle_key_event(key(KeyCode::Down));
+        app.handle_key_event(key(KeyCode::Enter));
+        let played = app.service.get_game(mario.id()).unwrap().playtime();
+        assert_eq!(played, Playtime::from_seconds(5 * 60));
+
+        app.handle_key_event(key(KeyCode::Up));
+        app.handle_key_event(key(KeyCode::Enter));
+        assert_eq!(app.presence.current().unwrap().details(), "5m in total");
+    }
+
+    #[test]
+    fn presence_is_cleared_on_request_and_on_exit() {
+        let repo: InMemoryGameRepository = [game("mario")].into_iter().collect();
+        let mut app = App::new(
+            Service::new(repo),
+            InMemoryPresence::new(),
+            AssetManifest::default(),
+        );
+
+        app.select_first();
+        app.handle_key_event(key(KeyCode::Enter));
+        app.handle_key_event(key(KeyCode::Char('c')));
+        assert_eq!(app.presence.current(), None);
+        assert_eq!(app.active_game, None);
+
+        app.handle_key_event(key(KeyCode::Enter));
+        app.handle_key_event(key(KeyCode::Char('u')));
+        app.handle_key_event(key(KeyCode::Enter));
+        assert_eq!(app.presence.current(), None);
+
+        app.handle_key_event(key(KeyCode::Down));
+        app.handle_key_event(key(KeyCode::Enter));
+        app.terminate.store(true, Ordering::Relaxed);
+        assert!(app.should_exit());
+        app.shutdown();
+        assert_eq!(app.presence.published().last(), Some(&None));
     }
 }
@@ -4,6 +4,7 @@ pub enum State {
     Selection,
     Editing(crate::tui::input::Input),
     Adding(crate::tui::input::Input),
+    Filtering(crate::tui::filter::FilterInput),
 }
 
 impl State {
@@ -12,6 +13,7 @@ impl State {
             State::Selection => false,
             State::Editing(_) => false,
             State::Adding(_) => true,
+            State::Filtering(_) => false,
         }
     }
 
@@ -21,6 +23,7 @@ impl State {
             State::Selection => true,
             State::Editing(_) => false,
             State::Adding(_) => false,
+            State::Filtering(_) => false,
         }
     }
 
@@ -29,6 +32,16 @@ impl State {
             State::Selection => false,
             State::Editing(_) => true,
             State::Adding(_) => false,
+            State::Filtering(_) => false,
+        }
+    }
+
+    pub const fn is_filtering(&self) -> bool {
+        match self {
+            State::Selection => false,
+            State::Editing(_) => false,
+            State::Adding(_) => false,
+            State::Filtering(_) => true,
         }
     }
 }
@@ -1,10 +1,16 @@
+use std::path::{Path, PathBuf};
+
 use directories::ProjectDirs;
 
 pub struct Config {
     database_url: String,
+    asset_manifest: PathBuf,
 }
 
 impl Config {
+    const DATABASE_FILE: &str = "games.db";
+    const ASSET_MANIFEST_FILE: &str = "assets.toml";
+
     pub fn from_env() -> Result<Self, std::env::VarError> {
         let project_dir = match ProjectDirs::from("de", "baumbus", "console-player") {
             Some(dir) => dir,
@@ -13,12 +19,34 @@ impl Config {
 
         let database_url = match std::env::var("CONSOLE_PLAYER_DATABASE_URL") {
             Ok(url) => url,
+            Err(e) if e.eq(&std::env::VarError::NotPresent) => project_dir
+                .data_dir()
+                .join(Self::DATABASE_FILE)
+                .display()
+                .to_string(),
+            Err(e) => return Err(e),
+        };
+
+        let asset_manifest = match std::env::var("CONSOLE_PLAYER_ASSET_MANIFEST") {
+            Ok(path) => PathBuf::from(path),
             Err(e) if e.eq(&std::env::VarError::NotPresent) => {
-                project_dir.data_dir().display().to_string()
+                project_dir.config_dir().join(Self::ASSET_MANIFEST_FILE)
             }
             Err(e) => return Err(e),
         };
 
-        Ok(Self { database_url })
+        Ok(Self {
+            database_url,
+            asset_manifest,
+        })
+    }
+
+    pub fn database_url(&self) -> &str {
+        &self.database_url
+    }
+
+    /// Location of the list of art assets uploaded to the Discord application.
+    pub fn asset_manifest(&self) -> &Path {
+        &self.asset_manifest
     }
 }
@@ -0,0 +1,167 @@
+use std::collections::BTreeSet;
+
+use derive_more::Display;
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::domain::game::{Game, Image, Name};
+
+/// Longest key the Game SDK can send: activity image fields are 128 byte C strings.
+pub const MAX_KEY_LEN: usize = 127;
+
+/// The art asset keys uploaded to the Discord application, as listed in the asset manifest:
+///
+/// ```toml
+/// assets = ["mariokart8", "switch"]
+/// ```
+///
+/// An empty manifest knows no assets and only the key rules are checked.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+pub struct AssetManifest {
+    #[serde(default)]
+    assets: BTreeSet<String>,
+}
+
+impl AssetManifest {
+    pub fn is_empty(&self) -> bool {
+        self.assets.is_empty()
+    }
+
+    pub fn contains(&self, key: &Image) -> bool {
+        self.assets.contains(key.as_str())
+    }
+
+    /// Checks `key` against Discord's key rules and, unless the manifest is empty, against the
+    /// listed assets.
+    pub fn check(&self, key: &Image) -> Result<(), AssetKeyError> {
+        check_key(key)?;
+
+        if !self.is_empty() && !self.contains(key) {
+            return Err(AssetKeyError::Unknown);
+        }
+
+        Ok(())
+    }
+
+    /// Every image key of `games` that [`AssetManifest::check`] rejects.
+    pub fn lint<'a>(&self, games: impl IntoIterator<Item = &'a Game>) -> Vec<AssetLint> {
+        games
+            .into_iter()
+            .flat_map(|game| {
+                [
+                    (ImageSlot::Large, game.large_image()),
+                    (ImageSlot::Small, game.small_image()),
+                ]
+                .into_iter()
+                .filter_map(|(slot, key)| {
+                    let key = key?;
+                    let error = self.check(key).err()?;
+                    Some(AssetLint {
+                        game: game.name().clone(),
+                        slot,
+                        key: key.clone(),
+                        error,
+                    })
+                })
+            })
+            .collect()
+    }
+}
+
+/// Discord's rules for art asset keys.
+pub fn check_key(key: &Image) -> Result<(), AssetKeyError> {
+    let key = key.as_str();
+
+    if key.is_empty() {
+        Err(AssetKeyError::Empty)
+    } else if key.len() > MAX_KEY_LEN {
+        Err(AssetKeyError::TooLong { len: key.len() })
+    } else if key.chars().any(char::is_whitespace) {
+        Err(AssetKeyError::Whitespace)
+    } else if key.chars().any(char::is_uppercase) {
+        Err(AssetKeyError::Uppercase)
+    } else {
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Error)]
+pub enum AssetKeyError {
+    #[error("asset key cannot be empty")]
+    Empty,
+    #[error("asset key is {len} bytes long, at most {MAX_KEY_LEN} are allowed")]
+    TooLong { len: usize },
+    #[error("asset key cannot contain whitespace")]
+    Whitespace,
+    #[error("asset key must be lowercase")]
+    Uppercase,
+    #[error("asset key is not in the asset manifest")]
+    Unknown,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Display)]
+pub enum ImageSlot {
+    #[display("large image")]
+    Large,
+    #[display("small image")]
+    Small,
+}
+
+/// An image key that would not show up on Discord.
+#[derive(Clone, Debug, PartialEq, Eq, Display)]
+#[display("{game}: {slot} \"{key}\": {error}")]
+pub struct AssetLint {
+    pub game: Name,
+    pub slot: ImageSlot,
+    pub key: Image,
+    pub error: AssetKeyError,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::domain::{
+        asset::{AssetKeyError, AssetManifest, ImageSlot, MAX_KEY_LEN, check_key},
+        game::{Game, GameId, Image, Name, Platform},
+    };
+    use pretty_assertions::assert_eq;
+
+    fn image(key: &str) -> Image {
+        Image::from(key.to_string())
+    }
+
+    #[test]
+    fn key_rules() {
+        assert_eq!(check_key(&image("mariokart8")), Ok(()));
+        assert_eq!(check_key(&image("")), Err(AssetKeyError::Empty));
+        assert_eq!(check_key(&image("Mario")), Err(AssetKeyError::Uppercase));
+        assert_eq!(
+            check_key(&image("mario kart")),
+            Err(AssetKeyError::Whitespace)
+        );
+        assert_eq!(
+            check_key(&image(&"a".repeat(MAX_KEY_LEN + 1))),
+            Err(AssetKeyError::TooLong {
+                len: MAX_KEY_LEN + 1
+            })
+        );
+    }
+
+    #[test]
+    fn lint_flags_unknown_and_malformed_keys() {
+        let manifest: AssetManifest = toml::from_str(r#"assets = ["mariokart8"]"#).unwrap();
+        let game = Game::builder()
+            .id(GameId::generate())
+            .name(Name::new("Mario Kart 8").unwrap())
+            .platform(Platform::new("Nintendo Switch").unwrap())
+            .large_image(image("mariokart8"))
+            .small_image(image("Switch"))
+            .build();
+
+        let lints = manifest.lint([&game]);
+
+        assert_eq!(lints.len(), 1);
+        assert_eq!(lints[0].slot, ImageSlot::Small);
+        assert_eq!(lints[0].error, AssetKeyError::Uppercase);
+        assert_eq!(AssetManifest::default().check(&image("anything")), Ok(()));
+    }
+}
@@ -0,0 +1,145 @@
+use std::{collections::BTreeSet, fmt, str::FromStr};
+
+use crate::domain::game::{Collection, Game, GameValidationError, Tag};
+
+/// Narrows the library down to the games that carry every given tag, belong to every given
+/// collection and whose name contains every given word.
+///
+/// The text form used by the TUI is a list of words: `#tag`, `@collection` or a bare word that
+/// must appear in the name. Collections with spaces are quoted: `@"Summer backlog"`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct GameFilter {
+    words: Vec<String>,
+    tags: BTreeSet<Tag>,
+    collections: BTreeSet<Collection>,
+}
+
+impl GameFilter {
+    pub fn new(
+        tags: impl IntoIterator<Item = Tag>,
+        collections: impl IntoIterator<Item = Collection>,
+    ) -> Self {
+        Self {
+            words: Vec::new(),
+            tags: tags.into_iter().collect(),
+            collections: collections.into_iter().collect(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty() && self.tags.is_empty() && self.collections.is_empty()
+    }
+
+    pub fn matches(&self, game: &Game) -> bool {
+        let name = game.name().as_str().to_lowercase();
+
+        self.words.iter().all(|word| name.contains(word))
+            && self.tags.is_subset(game.tags())
+            && self.collections.is_subset(game.collections())
+    }
+}
+
+/// Splits on whitespace, keeping double-quoted parts together and dropping the quotes.
+fn split_words(raw: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut quoted = false;
+
+    for c in raw.chars() {
+        match c {
+            '"' => quoted = !quoted,
+            c if c.is_whitespace() && !quoted => {
+                if !word.is_empty() {
+                    words.push(std::mem::take(&mut word));
+                }
+            }
+            c => word.push(c),
+        }
+    }
+    if !word.is_empty() {
+        words.push(word);
+    }
+
+    words
+}
+
+impl FromStr for GameFilter {
+    type Err = GameValidationError;
+
+    fn from_str(raw: &str) -> Result<Self, Self::Err> {
+        let mut filter = Self::default();
+
+        for word in split_words(raw) {
+            if let Some(tag) = word.strip_prefix('#') {
+                filter.tags.insert(Tag::new(tag)?);
+            } else if let Some(collection) = word.strip_prefix('@') {
+                filter.collections.insert(Collection::new(collection)?);
+            } else {
+                filter.words.push(word.to_lowercase());
+            }
+        }
+
+        Ok(filter)
+    }
+}
+
+impl fmt::Display for GameFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let tags = self.tags.iter().map(|tag| format!("#{tag}"));
+        let collections = self.collections.iter().map(|collection| {
+            if collection.as_str().contains(char::is_whitespace) {
+                format!("@\"{collection}\"")
+            } else {
+                format!("@{collection}")
+            }
+        });
+        let words: Vec<String> = self
+            .words
+            .iter()
+            .cloned()
+            .chain(tags)
+            .chain(collections)
+            .collect();
+
+        write!(f, "{}", words.join(" "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::domain::game::{
+        Collection, Game, GameCreateRequest, GameFilter, GameId, Name, Platform, Tag,
+    };
+    use pretty_assertions::assert_eq;
+
+    fn game(name: &str, tags: &[&str], collections: &[&str]) -> Game {
+        let req = GameCreateRequest::builder()
+            .name(Name::new(name).unwrap())
+            .platform(Platform::new("Nintendo Switch").unwrap())
+            .tags(tags.iter().map(|tag| Tag::new(tag).unwrap()).collect())
+            .collections(
+                collections
+                    .iter()
+                    .map(|collection| Collection::new(collection).unwrap())
+                    .collect(),
+            )
+            .build();
+        Game::new(GameId::generate(), &req)
+    }
+
+    #[test]
+    fn parses_and_matches() {
+        let filter: GameFilter = r#"kart #Co-op @"Summer backlog""#.parse().unwrap();
+        let games = [
+            game("Mario Kart 8", &["co-op", "racing"], &["Summer backlog"]),
+            game("Mario Kart 8", &["co-op"], &[]),
+            game("Zelda", &["co-op"], &["Summer backlog"]),
+        ];
+
+        let matching: Vec<bool> = games.iter().map(|game| filter.matches(game)).collect();
+
+        assert_eq!(matching, [true, false, false]);
+        assert_eq!(filter.to_string(), r#"kart #co-op @"Summer backlog""#);
+        assert!(GameFilter::default().matches(&games[2]));
+    }
+}
@@ -0,0 +1,294 @@
+use std::{collections::VecDeque, fmt};
+
+use log::error;
+use thiserror::Error;
+
+use crate::domain::game::{
+    Game, GameCreateError, GameCreateRequest, GameDeleteError, GameDeleteRequest, GameId,
+    GameService, GameUpdateError, GameUpdateRequest,
+};
+
+/// How many changes [`History`] remembers before it forgets the oldest.
+pub const HISTORY_LIMIT: usize = 100;
+
+/// A change to the game library. Each change carries the games on both sides, so it can be
+/// reverted without asking the storage what it looked like before.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Change {
+    Create(Game),
+    Update {
+        before: Box<Game>,
+        after: Box<Game>,
+    },
+    Delete(Game),
+    /// Several changes made as one operation, undone and redone together.
+    Batch(Vec<Change>),
+}
+
+impl Change {
+    /// The change that reverts this one.
+    pub fn inverse(&self) -> Self {
+        match self {
+            Self::Create(game) => Self::Delete(game.clone()),
+            Self::Update { before, after } => Self::Update {
+                before: after.clone(),
+                after: before.clone(),
+            },
+            Self::Delete(game) => Self::Create(game.clone()),
+            Self::Batch(changes) => Self::Batch(changes.iter().rev().map(Self::inverse).collect()),
+        }
+    }
+
+    /// Whether this change touches the game `id`.
+    pub fn affects(&self, id: GameId) -> bool {
+        match self {
+            Self::Create(game) | Self::Delete(game) => game.id() == id,
+            Self::Update { after, .. } => after.id() == id,
+            Self::Batch(changes) => changes.iter().any(|change| change.affects(id)),
+        }
+    }
+
+    /// Performs the change. A batch stops at the first change that fails and reverts the ones
+    /// before it, so it is made either as a whole or not at all.
+    fn apply(&self, service: &impl GameService) -> Result<(), HistoryError> {
+        match self {
+            Self::Create(game) => {
+                service.restore_game(game)?;
+            }
+            Self::Update { after, .. } => {
+                let req = GameUpdateRequest::builder()
+                    .id(after.id())
+                    .game(GameCreateRequest::from(&**after))
+                    .build();
+                service.update_game(&req)?;
+            }
+            Self::Delete(game) => service.delete_game(&GameDeleteRequest::from(game.id()))?,
+            Self::Batch(changes) => {
+                for (done, change) in changes.iter().enumerate() {
+                    if let Err(err) = change.apply(service) {
+                        for made in changes[..done].iter().rev() {
+                            if let Err(err) = made.inverse().apply(service) {
+                                error!("Cannot roll back {made}: {err}");
+                            }
+                        }
+                        return Err(err);
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Create(game) => write!(f, "add {}", game.name()),
+            Self::Update { after, .. } => write!(f, "edit {}", after.name()),
+            Self::Delete(game) => write!(f, "delete {}", game.name()),
+            Self::Batch(changes)
+                if changes
+                    .iter()
+                    .all(|change| matches!(change, Self::Delete(_))) =>
+            {
+                write!(f, "delete {} games", changes.len())
+            }
+            Self::Batch(changes) => write!(f, "{} changes", changes.len()),
+        }
+    }
+}
+
+/// Undo and redo stacks of library changes.
+///
+/// Recording a new change clears the redo stack. A change that fails to undo or redo stays where
+/// it was, so the user can try again once the cause is fixed.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct History {
+    undo: VecDeque<Change>,
+    redo: Vec<Change>,
+}
+
+impl History {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Remembers a change that was just made.
+    pub fn record(&mut self, change: Change) {
+        if self.undo.len() == HISTORY_LIMIT {
+            self.undo.pop_front();
+        }
+        self.undo.push_back(change);
+        self.redo.clear();
+    }
+
+    /// The change the next [`History::undo`] reverts.
+    pub fn next_undo(&self) -> Option<&Change> {
+        self.undo.back()
+    }
+
+    /// The change the next [`History::redo`] makes again.
+    pub fn next_redo(&self) -> Option<&Change> {
+        self.redo.last()
+    }
+
+    /// Reverts the last change and returns it, or `None` if there is nothing to undo.
+    pub fn undo(&mut self, service: &impl GameService) -> Result<Option<Change>, HistoryError> {
+        let Some(change) = self.undo.back() else {
+            return Ok(None);
+        };
+        change.inverse().apply(service)?;
+
+        let change = self.undo.pop_back().expect("checked above");
+        self.redo.push(change.clone());
+        Ok(Some(change))
+    }
+
+    /// Makes the last undone change again and returns it, or `None` if there is nothing to redo.
+    pub fn redo(&mut self, service: &impl GameService) -> Result<Option<Change>, HistoryError> {
+        let Some(change) = self.redo.last() else {
+            return Ok(None);
+        };
+        change.apply(service)?;
+
+        let change = self.redo.pop().expect("checked above");
+        self.undo.push_back(change.clone());
+        Ok(Some(change))
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum HistoryError {
+    #[error(transparent)]
+    Create(#[from] GameCreateError),
+    #[error(transparent)]
+    Update(#[from] GameUpdateError),
+    #[error(transparent)]
+    Delete(#[from] GameDeleteError),
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        domain::game::{
+            Change, GameCreateRequest, GameDeleteRequest, GameService, GameUpdateRequest, History,
+            Name, Platform, Service,
+        },
+        outbound::{Fault, InMemoryGameRepository},
+    };
+    use pretty_assertions::assert_eq;
+
+    fn request(name: &str) -> GameCreateRequest {
+        GameCreateRequest::builder()
+            .name(Name::new(name).unwrap())
+            .platform(Platform::new("Nintendo Switch").unwrap())
+            .build()
+    }
+
+    fn names(service: &impl GameService) -> Vec<String> {
+        service
+            .list_games()
+            .unwrap()
+            .iter()
+            .map(|game| game.name().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn undo_and_redo_round_trip() {
+        let service = Service::new(InMemoryGameRepository::new());
+        let mut history = History::new();
+
+        let mario = service.create_game(&request("mario")).unwrap();
+        history.record(Change::Create(mario.clone()));
+        let renamed = service
+            .update_game(
+                &GameUpdateRequest::builder()
+                    .id(mario.id())
+                    .game(request("mario kart"))
+                    .build(),
+            )
+            .unwrap();
+        history.record(Change::Update {
+            before: Box::new(mario.clone()),
+            after: Box::new(renamed.clone()),
+        });
+        service
+            .delete_game(&GameDeleteRequest::from(renamed.id()))
+            .unwrap();
+        history.record(Change::Delete(renamed.clone()));
+
+        assert_eq!(
+            history.next_undo().map(ToString::to_string).as_deref(),
+            Some("delete mario kart")
+        );
+        history.undo(&service).unwrap();
+        assert_eq!(service.get_game(mario.id()).unwrap(), renamed);
+        history.undo(&service).unwrap();
+        assert_eq!(names(&service), ["mario"]);
+        history.undo(&service).unwrap();
+        assert!(names(&service).is_empty());
+        assert!(history.undo(&service).unwrap().is_none());
+
+        history.redo(&service).unwrap();
+        history.redo(&service).unwrap();
+        assert_eq!(names(&service), ["mario kart"]);
+        assert_eq!(
+            history.next_redo().map(ToString::to_string).as_deref(),
+            Some("delete mario kart")
+        );
+
+        history.record(Change::Create(
+            service.create_game(&request("zelda")).unwrap(),
+        ));
+        assert!(history.next_redo().is_none());
+    }
+
+    #[test]
+    fn failed_undo_keeps_the_change() {
+        let service = Service::new(InMemoryGameRepository::new());
+        let mut history = History::new();
+        let mario = service.create_game(&request("mario")).unwrap();
+        history.record(Change::Create(mario));
+
+        service.repo.fail_next(Fault::Io);
+        assert!(history.undo(&service).is_err());
+        assert!(history.next_undo().is_some());
+        assert!(history.undo(&service).unwrap().is_some());
+    }
+
+    #[test]
+    fn batches_are_undone_whole_or_not_at_all() {
+        let service = Service::new(InMemoryGameRepository::new());
+        let mut history = History::new();
+        let mario = service.create_game(&request("mario")).unwrap();
+        let zelda = service.create_game(&request("zelda")).unwrap();
+        for game in [&mario, &zelda] {
+            service
+                .delete_game(&GameDeleteRequest::from(game.id()))
+                .unwrap();
+        }
+        history.record(Change::Batch(vec![
+            Change::Delete(mario.clone()),
+            Change::Delete(zelda),
+        ]));
+
+        // Zelda comes back first, then mario clashes with the new one.
+        let other = service.create_game(&request("mario")).unwrap();
+        assert!(history.undo(&service).is_err());
+        assert_eq!(names(&service), ["mario"]);
+        assert_eq!(service.get_game(other.id()).unwrap(), other);
+        assert!(history.next_undo().is_some());
+
+        service
+            .delete_game(&GameDeleteRequest::from(other.id()))
+            .unwrap();
+        history.undo(&service).unwrap();
+        let mut restored = names(&service);
+        restored.sort();
+        assert_eq!(restored, ["mario", "zelda"]);
+        history.redo(&service).unwrap();
+        assert!(names(&service).is_empty());
+    }
+}
@@ -1,8 +1,56 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+    ops::Add,
+    str::FromStr,
+};
+
 use bon::Builder;
+use chrono::TimeDelta;
 use derive_more::{Display, From};
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
+use uuid::Uuid;
+
+mod filter;
+mod history;
+mod service;
+mod template;
+
+pub use filter::GameFilter;
+pub use history::{Change, History, HistoryError};
+pub use service::Service;
+pub use template::{
+    BUILTIN_PLACEHOLDERS, PRESENCE_TEXT_LIMIT, Placeholders, Template, TemplateError,
+};
+
+/// Persistent identifier of a game, independent of its name and position in the library.
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Display, Serialize, Deserialize,
+)]
+#[serde(transparent)]
+pub struct GameId(Uuid);
+
+impl GameId {
+    pub fn generate() -> Self {
+        Self(Uuid::new_v4())
+    }
+}
+
+impl FromStr for GameId {
+    type Err = GameIdParseError;
+
+    fn from_str(raw: &str) -> Result<Self, Self::Err> {
+        Ok(Self(Uuid::parse_str(raw.trim())?))
+    }
+}
+
+#[derive(Clone, Debug, Error)]
+#[error("invalid game id: {0}")]
+pub struct GameIdParseError(#[from] uuid::Error);
 
-#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Display)]
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Display, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
 pub struct Name(String);
 
 impl Name {
@@ -14,13 +62,32 @@ impl Name {
             Ok(Self(trimmed.to_string()))
         }
     }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl TryFrom<String> for Name {
+    type Error = GameNameEmptyError;
+
+    fn try_from(raw: String) -> Result<Self, Self::Error> {
+        Self::new(&raw)
+    }
+}
+
+impl From<Name> for String {
+    fn from(value: Name) -> Self {
+        value.0
+    }
 }
 
 #[derive(Clone, Debug, Error)]
 #[error("game name cannot be empty")]
 pub struct GameNameEmptyError;
 
-#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Display)]
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Display, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
 pub struct Platform(String);
 
 impl Platform {
@@ -32,94 +99,863 @@ impl Platform {
             Ok(Self(trimmed.to_string()))
         }
     }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl TryFrom<String> for Platform {
+    type Error = GamePlatformEmptyError;
+
+    fn try_from(raw: String) -> Result<Self, Self::Error> {
+        Self::new(&raw)
+    }
+}
+
+/// Placeholder for games whose platform was never filled in.
+impl Default for Platform {
+    fn default() -> Self {
+        Self(String::from("No platform"))
+    }
+}
+
+impl From<Platform> for String {
+    fn from(value: Platform) -> Self {
+        value.0
+    }
 }
 
 #[derive(Clone, Debug, Error)]
 #[error("game platform cannot be empty")]
 pub struct GamePlatformEmptyError;
 
-#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, From, Display)]
+#[derive(Clone, Debug, Error)]
+pub enum GameValidationError {
+    #[error(transparent)]
+    Name(#[from] GameNameEmptyError),
+    #[error(transparent)]
+    Platform(#[from] GamePlatformEmptyError),
+    #[error(transparent)]
+    Tag(#[from] GameTagInvalidError),
+    #[error(transparent)]
+    Collection(#[from] GameCollectionEmptyError),
+    #[error(transparent)]
+    Flag(#[from] GameFlagInvalidError),
+    #[error(transparent)]
+    Party(#[from] GamePartyInvalidError),
+    #[error(transparent)]
+    Field(#[from] GameFieldInvalidError),
+    #[error(transparent)]
+    Template(#[from] TemplateError),
+    #[error(transparent)]
+    Secret(#[from] GameSecretInvalidError),
+}
+
+#[derive(
+    Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, From, Display, Serialize, Deserialize,
+)]
+#[serde(transparent)]
 pub struct Image(String);
 
-#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, From, Display)]
+impl Image {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+#[derive(
+    Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, From, Display, Serialize, Deserialize,
+)]
+#[serde(transparent)]
 pub struct Tooltip(String);
 
-#[expect(dead_code)]
+impl Tooltip {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+/// Free-form label such as `co-op` or `backlog`. Tags are lowercase and a single word.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Display, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Tag(String);
+
+impl Tag {
+    pub fn new(raw: &str) -> Result<Self, GameTagInvalidError> {
+        let trimmed = raw.trim();
+        if trimmed.is_empty() || trimmed.contains(char::is_whitespace) {
+            Err(GameTagInvalidError(raw.to_string()))
+        } else {
+            Ok(Self(trimmed.to_lowercase()))
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl TryFrom<String> for Tag {
+    type Error = GameTagInvalidError;
+
+    fn try_from(raw: String) -> Result<Self, Self::Error> {
+        Self::new(&raw)
+    }
+}
+
+impl From<Tag> for String {
+    fn from(value: Tag) -> Self {
+        value.0
+    }
+}
+
+#[derive(Clone, Debug, Error)]
+#[error("invalid tag {0:?}: tags are a single non-empty word")]
+pub struct GameTagInvalidError(String);
+
+/// Name of a collection the game belongs to, e.g. `Summer backlog`.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Display, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Collection(String);
+
+impl Collection {
+    pub fn new(raw: &str) -> Result<Self, GameCollectionEmptyError> {
+        let trimmed = raw.trim();
+        if trimmed.is_empty() {
+            Err(GameCollectionEmptyError)
+        } else {
+            Ok(Self(trimmed.to_string()))
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl TryFrom<String> for Collection {
+    type Error = GameCollectionEmptyError;
+
+    fn try_from(raw: String) -> Result<Self, Self::Error> {
+        Self::new(&raw)
+    }
+}
+
+impl From<Collection> for String {
+    fn from(value: Collection) -> Self {
+        value.0
+    }
+}
+
+#[derive(Clone, Debug, Error)]
+#[error("collection name cannot be empty")]
+pub struct GameCollectionEmptyError;
+
+#[derive(Clone, Debug, Error)]
+#[error("expected yes or no, got {0:?}")]
+pub struct GameFlagInvalidError(pub String);
+
+/// Name of a custom field such as `status`, usable as a placeholder in templates. Names are a
+/// single word and cannot shadow the [`BUILTIN_PLACEHOLDERS`].
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Display, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct FieldName(String);
+
+impl FieldName {
+    pub fn new(raw: &str) -> Result<Self, GameFieldInvalidError> {
+        let trimmed = raw.trim();
+        let valid = !trimmed.is_empty()
+            && trimmed
+                .chars()
+                .all(|c| c.is_alphanumeric() || c == '_' || c == '-')
+            && !BUILTIN_PLACEHOLDERS.contains(&trimmed);
+        if valid {
+            Ok(Self(trimmed.to_string()))
+        } else {
+            Err(GameFieldInvalidError(raw.to_string()))
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl TryFrom<String> for FieldName {
+    type Error = GameFieldInvalidError;
+
+    fn try_from(raw: String) -> Result<Self, Self::Error> {
+        Self::new(&raw)
+    }
+}
+
+impl From<FieldName> for String {
+    fn from(value: FieldName) -> Self {
+        value.0
+    }
+}
+
+#[derive(Clone, Debug, Error)]
+#[error("invalid field name {0:?}: names are a single word other than a built-in placeholder")]
+pub struct GameFieldInvalidError(pub String);
+
+/// Time spent playing, counted in whole seconds. Shown as e.g. `3h 05m`.
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(transparent)]
+pub struct Playtime(u64);
+
+impl Playtime {
+    pub const fn from_seconds(seconds: u64) -> Self {
+        Self(seconds)
+    }
+
+    pub const fn seconds(self) -> u64 {
+        self.0
+    }
+
+    pub const fn is_zero(&self) -> bool {
+        self.0 == 0
+    }
+}
+
+/// Negative deltas, e.g. after the clock was turned back, count as no time at all.
+impl From<TimeDelta> for Playtime {
+    fn from(delta: TimeDelta) -> Self {
+        Self(u64::try_from(delta.num_seconds()).unwrap_or_default())
+    }
+}
+
+impl Add for Playtime {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self(self.0.saturating_add(other.0))
+    }
+}
+
+impl fmt::Display for Playtime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let minutes = self.0 / 60;
+        match minutes / 60 {
+            0 => write!(f, "{minutes}m"),
+            hours => write!(f, "{hours}h {:02}m", minutes % 60),
+        }
+    }
+}
+
+/// The group playing the game together, shown by Discord as e.g. `(2 of 4)`.
+///
+/// Written as `id size/max`, e.g. `couch 2/4`.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(try_from = "PartyFields")]
+pub struct Party {
+    id: String,
+    size: u32,
+    max: u32,
+}
+
+impl Party {
+    pub fn new(id: &str, size: u32, max: u32) -> Result<Self, GamePartyInvalidError> {
+        let id = id.trim();
+        if id.is_empty() {
+            Err(GamePartyInvalidError::EmptyId)
+        } else if size == 0 || size > max {
+            Err(GamePartyInvalidError::Size { size, max })
+        } else {
+            Ok(Self {
+                id: id.to_string(),
+                size,
+                max,
+            })
+        }
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub const fn size(&self) -> u32 {
+        self.size
+    }
+
+    pub const fn max(&self) -> u32 {
+        self.max
+    }
+
+    /// The same party with `size` members, kept between one and the maximum.
+    pub fn with_size(self, size: u32) -> Self {
+        Self {
+            size: size.clamp(1, self.max),
+            ..self
+        }
+    }
+}
+
+impl FromStr for Party {
+    type Err = GamePartyInvalidError;
+
+    fn from_str(raw: &str) -> Result<Self, Self::Err> {
+        let invalid = || GamePartyInvalidError::Format(raw.to_string());
+        let (id, size) = raw
+            .trim()
+            .rsplit_once(char::is_whitespace)
+            .ok_or_else(invalid)?;
+        let (size, max) = size.split_once('/').ok_or_else(invalid)?;
+
+        Self::new(
+            id,
+            size.parse().map_err(|_| invalid())?,
+            max.parse().map_err(|_| invalid())?,
+        )
+    }
+}
+
+impl fmt::Display for Party {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}/{}", self.id, self.size, self.max)
+    }
+}
+
+/// The `[game.party]` table as written, before it is validated.
+#[derive(Deserialize)]
+struct PartyFields {
+    id: String,
+    size: u32,
+    max: u32,
+}
+
+impl TryFrom<PartyFields> for Party {
+    type Error = GamePartyInvalidError;
+
+    fn try_from(fields: PartyFields) -> Result<Self, Self::Error> {
+        Self::new(&fields.id, fields.size, fields.max)
+    }
+}
+
+#[derive(Clone, Debug, Error)]
+pub enum GamePartyInvalidError {
+    #[error("party id cannot be empty")]
+    EmptyId,
+    #[error("party size {size} is not between 1 and the maximum of {max}")]
+    Size { size: u32, max: u32 },
+    #[error("expected a party as `id size/max`, got {0:?}")]
+    Format(String),
+}
+
+/// Text Discord hands to friends who join or spectate the game from our profile, e.g. a server
+/// address or room code. It cannot contain whitespace or commas.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Secret(String);
+
+impl Secret {
+    /// Most characters Discord accepts in a secret.
+    pub const MAX_LEN: usize = 128;
+
+    pub fn new(raw: &str) -> Result<Self, GameSecretInvalidError> {
+        let raw = raw.trim();
+        if raw.is_empty() {
+            Err(GameSecretInvalidError::Empty)
+        } else if raw.chars().count() > Self::MAX_LEN {
+            Err(GameSecretInvalidError::TooLong(raw.chars().count()))
+        } else if raw.contains(|c: char| c.is_whitespace() || c == ',') {
+            Err(GameSecretInvalidError::Format(raw.to_string()))
+        } else {
+            Ok(Self(raw.to_string()))
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl TryFrom<String> for Secret {
+    type Error = GameSecretInvalidError;
+
+    fn try_from(raw: String) -> Result<Self, Self::Error> {
+        Self::new(&raw)
+    }
+}
+
+impl From<Secret> for String {
+    fn from(value: Secret) -> Self {
+        value.0
+    }
+}
+
+impl fmt::Display for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// The secrets that let friends join or spectate the game through Discord. Discord only offers
+/// to join when the presence also has a party.
+///
+/// Written as `join=…, spectate=…`, either part optional.
+#[derive(Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct Secrets {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    join: Option<Secret>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    spectate: Option<Secret>,
+}
+
+impl Secrets {
+    pub const fn new(join: Option<Secret>, spectate: Option<Secret>) -> Self {
+        Self { join, spectate }
+    }
+
+    pub const fn join(&self) -> Option<&Secret> {
+        self.join.as_ref()
+    }
+
+    pub const fn spectate(&self) -> Option<&Secret> {
+        self.spectate.as_ref()
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.join.is_none() && self.spectate.is_none()
+    }
+}
+
+impl FromStr for Secrets {
+    type Err = GameSecretInvalidError;
+
+    fn from_str(raw: &str) -> Result<Self, Self::Err> {
+        let mut secrets = Self::default();
+        for entry in raw.split(',').filter(|entry| !entry.trim().is_empty()) {
+            let invalid = || GameSecretInvalidError::Format(entry.trim().to_string());
+            let (kind, secret) = entry.split_once('=').ok_or_else(invalid)?;
+            let slot = match kind.trim() {
+                "join" => &mut secrets.join,
+                "spectate" => &mut secrets.spectate,
+                _ => return Err(invalid()),
+            };
+            *slot = Some(Secret::new(secret)?);
+        }
+
+        Ok(secrets)
+    }
+}
+
+impl fmt::Display for Secrets {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let entries = [("join", &self.join), ("spectate", &self.spectate)]
+            .into_iter()
+            .filter_map(|(kind, secret)| Some(format!("{kind}={}", secret.as_ref()?)))
+            .collect::<Vec<_>>();
+        f.write_str(&entries.join(", "))
+    }
+}
+
+#[derive(Clone, Debug, Error)]
+pub enum GameSecretInvalidError {
+    #[error("secret cannot be empty")]
+    Empty,
+    #[error("secret has {0} characters, Discord accepts at most {max}", max = Secret::MAX_LEN)]
+    TooLong(usize),
+    #[error("expected secrets as `join=…, spectate=…` without spaces, got {0:?}")]
+    Format(String),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Builder)]
 pub struct Game {
+    id: GameId,
     name: Name,
     platform: Platform,
     large_image: Option<Image>,
     large_tooltip: Option<Tooltip>,
     small_image: Option<Image>,
     small_tooltip: Option<Tooltip>,
+    #[builder(default)]
+    tags: BTreeSet<Tag>,
+    #[builder(default)]
+    collections: BTreeSet<Collection>,
+    /// Whether the presence shows the time since the game was activated. `None` leaves it to
+    /// the global `--timestamp` flag.
+    show_elapsed: Option<bool>,
+    party: Option<Party>,
+    /// Overrides the global state template.
+    state: Option<Template>,
+    /// Overrides the global details template.
+    details: Option<Template>,
+    /// Values of custom placeholders.
+    #[builder(default)]
+    fields: BTreeMap<FieldName, String>,
+    /// Total time played over all finished sessions.
+    #[builder(default)]
+    playtime: Playtime,
+    #[builder(default)]
+    secrets: Secrets,
+}
+
+impl Game {
+    pub fn new(id: GameId, req: &GameCreateRequest) -> Self {
+        Self {
+            id,
+            name: req.name.clone(),
+            platform: req.platform.clone(),
+            large_image: req.large_image.clone(),
+            large_tooltip: req.large_tooltip.clone(),
+            small_image: req.small_image.clone(),
+            small_tooltip: req.small_tooltip.clone(),
+            tags: req.tags.clone(),
+            collections: req.collections.clone(),
+            show_elapsed: req.show_elapsed,
+            party: req.party.clone(),
+            state: req.state.clone(),
+            details: req.details.clone(),
+            fields: req.fields.clone(),
+            playtime: req.playtime,
+            secrets: req.secrets.clone(),
+        }
+    }
+
+    pub const fn id(&self) -> GameId {
+        self.id
+    }
+
+    pub const fn name(&self) -> &Name {
+        &self.name
+    }
+
+    pub const fn platform(&self) -> &Platform {
+        &self.platform
+    }
+
+    pub const fn large_image(&self) -> Option<&Image> {
+        self.large_image.as_ref()
+    }
+
+    pub const fn large_tooltip(&self) -> Option<&Tooltip> {
+        self.large_tooltip.as_ref()
+    }
+
+    pub const fn small_image(&self) -> Option<&Image> {
+        self.small_image.as_ref()
+    }
+
+    pub const fn small_tooltip(&self) -> Option<&Tooltip> {
+        self.small_tooltip.as_ref()
+    }
+
+    pub const fn tags(&self) -> &BTreeSet<Tag> {
+        &self.tags
+    }
+
+    pub const fn collections(&self) -> &BTreeSet<Collection> {
+        &self.collections
+    }
+
+    pub const fn show_elapsed(&self) -> Option<bool> {
+        self.show_elapsed
+    }
+
+    pub const fn party(&self) -> Option<&Party> {
+        self.party.as_ref()
+    }
+
+    pub const fn state(&self) -> Option<&Template> {
+        self.state.as_ref()
+    }
+
+    pub const fn details(&self) -> Option<&Template> {
+        self.details.as_ref()
+    }
+
+    pub const fn fields(&self) -> &BTreeMap<FieldName, String> {
+        &self.fields
+    }
+
+    pub const fn playtime(&self) -> Playtime {
+        self.playtime
+    }
+
+    pub const fn secrets(&self) -> &Secrets {
+        &self.secrets
+    }
+
+    /// The same game with `playtime` in place of its own.
+    pub fn with_playtime(self, playtime: Playtime) -> Self {
+        Self { playtime, ..self }
+    }
+}
+
+impl From<&Game> for GameCreateRequest {
+    fn from(game: &Game) -> Self {
+        Self {
+            name: game.name.clone(),
+            platform: game.platform.clone(),
+            large_image: game.large_image.clone(),
+            large_tooltip: game.large_tooltip.clone(),
+            small_image: game.small_image.clone(),
+            small_tooltip: game.small_tooltip.clone(),
+            tags: game.tags.clone(),
+            collections: game.collections.clone(),
+            show_elapsed: game.show_elapsed,
+            party: game.party.clone(),
+            state: game.state.clone(),
+            details: game.details.clone(),
+            fields: game.fields.clone(),
+            playtime: game.playtime,
+            secrets: game.secrets.clone(),
+        }
+    }
 }
 
-#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, From, Builder)]
+/// The editable data of a game. Its serialized form is the `[[game]]` table of the game file.
+#[derive(
+    Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, From, Builder, Serialize, Deserialize,
+)]
 pub struct GameCreateRequest {
     name: Name,
+    #[serde(default)]
     platform: Platform,
+    #[serde(rename = "large_image_key")]
     large_image: Option<Image>,
+    #[serde(rename = "large_image_tooltip")]
     large_tooltip: Option<Tooltip>,
+    #[serde(rename = "small_image_key")]
     small_image: Option<Image>,
+    #[serde(rename = "small_image_tooltip")]
     small_tooltip: Option<Tooltip>,
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    #[builder(default)]
+    tags: BTreeSet<Tag>,
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    #[builder(default)]
+    collections: BTreeSet<Collection>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    show_elapsed: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    party: Option<Party>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    state: Option<Template>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    details: Option<Template>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    #[builder(default)]
+    fields: BTreeMap<FieldName, String>,
+    /// Seconds played so far. Updates leave it alone, see [`GameRepository::add_playtime`].
+    #[serde(default, skip_serializing_if = "Playtime::is_zero")]
+    #[builder(default)]
+    playtime: Playtime,
+    #[serde(default, skip_serializing_if = "Secrets::is_empty")]
+    #[builder(default)]
+    secrets: Secrets,
 }
 
 impl GameCreateRequest {
-    pub fn name(&self) -> &Name {
+    pub const fn name(&self) -> &Name {
         &self.name
     }
-    
-    pub fn platform(&self) -> &Platform {
+
+    pub const fn platform(&self) -> &Platform {
         &self.platform
     }
-    
-    pub fn large_image(&self) -> Option<&Image> {
+
+    pub const fn large_image(&self) -> Option<&Image> {
         self.large_image.as_ref()
     }
-    
-    pub fn large_tooltip(&self) -> Option<&Tooltip> {
+
+    pub const fn large_tooltip(&self) -> Option<&Tooltip> {
         self.large_tooltip.as_ref()
     }
-    
-    pub fn small_image(&self) -> Option<&Image> {
+
+    pub const fn small_image(&self) -> Option<&Image> {
         self.small_image.as_ref()
     }
-    
-    pub fn small_tooltip(&self) -> Option<&Tooltip> {
+
+    pub const fn small_tooltip(&self) -> Option<&Tooltip> {
         self.small_tooltip.as_ref()
     }
+
+    pub const fn tags(&self) -> &BTreeSet<Tag> {
+        &self.tags
+    }
+
+    pub const fn collections(&self) -> &BTreeSet<Collection> {
+        &self.collections
+    }
+
+    pub const fn show_elapsed(&self) -> Option<bool> {
+        self.show_elapsed
+    }
+
+    pub const fn party(&self) -> Option<&Party> {
+        self.party.as_ref()
+    }
+
+    pub const fn state(&self) -> Option<&Template> {
+        self.state.as_ref()
+    }
+
+    pub const fn details(&self) -> Option<&Template> {
+        self.details.as_ref()
+    }
+
+    pub const fn fields(&self) -> &BTreeMap<FieldName, String> {
+        &self.fields
+    }
+
+    pub const fn playtime(&self) -> Playtime {
+        self.playtime
+    }
+
+    pub const fn secrets(&self) -> &Secrets {
+        &self.secrets
+    }
+
+    /// The same game data on a different platform.
+    pub fn with_platform(self, platform: Platform) -> Self {
+        Self { platform, ..self }
+    }
+
+    /// The same game data with a different party, e.g. after someone joined.
+    pub fn with_party(self, party: Option<Party>) -> Self {
+        Self { party, ..self }
+    }
+
+    /// The same game data with different tags and collections.
+    pub fn with_labels(self, tags: BTreeSet<Tag>, collections: BTreeSet<Collection>) -> Self {
+        Self {
+            tags,
+            collections,
+            ..self
+        }
+    }
+}
+
+/// `GameUpdateRequest` replaces the data of the game identified by `id`.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Builder)]
+pub struct GameUpdateRequest {
+    id: GameId,
+    game: GameCreateRequest,
+}
+
+impl GameUpdateRequest {
+    pub const fn id(&self) -> GameId {
+        self.id
+    }
+
+    pub const fn game(&self) -> &GameCreateRequest {
+        &self.game
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, From)]
 pub struct GameDeleteRequest {
-    name: Name,
+    id: GameId,
 }
 
 impl GameDeleteRequest {
-    pub fn name(&self) -> &Name {
-        &self.name
+    pub const fn id(&self) -> GameId {
+        self.id
     }
 }
 
 /// `GameRepository` represents a store of `Game` data.
 pub trait GameRepository {
     fn create_game(&self, req: &GameCreateRequest) -> Result<Game, GameCreateError>;
+    /// Stores `game` under its existing id, e.g. to bring back a deleted game.
+    fn restore_game(&self, game: &Game) -> Result<Game, GameCreateError>;
+    /// Replaces the data of a game, keeping its playtime.
+    fn update_game(&self, req: &GameUpdateRequest) -> Result<Game, GameUpdateError>;
+    /// Adds `played` to the playtime of the game `id`.
+    fn add_playtime(&self, id: GameId, played: Playtime) -> Result<Game, GameUpdateError>;
     fn delete_game(&self, req: &GameDeleteRequest) -> Result<(), GameDeleteError>;
+    fn get_game(&self, id: GameId) -> Result<Game, GameGetError>;
+    fn find_game_by_name(&self, name: &Name) -> Result<Option<Game>, GameGetError>;
+    fn list_games(&self) -> Result<Vec<Game>, GameListError>;
+}
+
+impl<R: GameRepository + ?Sized> GameRepository for Box<R> {
+    fn create_game(&self, req: &GameCreateRequest) -> Result<Game, GameCreateError> {
+        (**self).create_game(req)
+    }
+
+    fn restore_game(&self, game: &Game) -> Result<Game, GameCreateError> {
+        (**self).restore_game(game)
+    }
+
+    fn update_game(&self, req: &GameUpdateRequest) -> Result<Game, GameUpdateError> {
+        (**self).update_game(req)
+    }
+
+    fn add_playtime(&self, id: GameId, played: Playtime) -> Result<Game, GameUpdateError> {
+        (**self).add_playtime(id, played)
+    }
+
+    fn delete_game(&self, req: &GameDeleteRequest) -> Result<(), GameDeleteError> {
+        (**self).delete_game(req)
+    }
+
+    fn get_game(&self, id: GameId) -> Result<Game, GameGetError> {
+        (**self).get_game(id)
+    }
+
+    fn find_game_by_name(&self, name: &Name) -> Result<Option<Game>, GameGetError> {
+        (**self).find_game_by_name(name)
+    }
+
+    fn list_games(&self) -> Result<Vec<Game>, GameListError> {
+        (**self).list_games()
+    }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[derive(Debug, Error)]
 pub enum GameCreateError {
     #[error("game with name {game_name} already exists")]
     Duplicate { game_name: Name },
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[derive(Debug, Error)]
+pub enum GameUpdateError {
+    #[error("game with id {id} does not exist")]
+    NotFound { id: GameId },
+    #[error("game with name {game_name} already exists")]
+    Duplicate { game_name: Name },
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[derive(Debug, Error)]
 pub enum GameDeleteError {
-    #[error("game with name {game_name} does not exist")]
-    NotFound { game_name: Name },
+    #[error("game with id {id} does not exist")]
+    NotFound { id: GameId },
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
 }
 
+#[derive(Debug, Error)]
+pub enum GameGetError {
+    #[error("game with id {id} does not exist")]
+    NotFound { id: GameId },
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[derive(Debug, Error)]
+pub enum GameListError {
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+/// `GameService` is the entry point for every change to the game library.
 pub trait GameService {
     fn create_game(&self, req: &GameCreateRequest) -> Result<Game, GameCreateError>;
+    fn restore_game(&self, game: &Game) -> Result<Game, GameCreateError>;
+    fn update_game(&self, req: &GameUpdateRequest) -> Result<Game, GameUpdateError>;
+    fn add_playtime(&self, id: GameId, played: Playtime) -> Result<Game, GameUpdateError>;
     fn delete_game(&self, req: &GameDeleteRequest) -> Result<(), GameDeleteError>;
-}
\ No newline at end of file
+    fn get_game(&self, id: GameId) -> Result<Game, GameGetError>;
+    fn find_game_by_name(&self, name: &Name) -> Result<Option<Game>, GameGetError>;
+    fn list_games(&self) -> Result<Vec<Game>, GameListError>;
+}
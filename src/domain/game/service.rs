@@ -0,0 +1,180 @@
+use crate::domain::game::{
+    Game, GameCreateError, GameCreateRequest, GameDeleteError, GameDeleteRequest, GameGetError,
+    GameId, GameListError, GameRepository, GameService, GameUpdateError, GameUpdateRequest, Name,
+    Playtime,
+};
+
+/// Canonical implementation of [`GameService`], delegating storage to a [`GameRepository`].
+#[derive(Debug, Clone)]
+pub struct Service<R: GameRepository> {
+    pub(in crate::domain) repo: R,
+}
+
+impl<R: GameRepository> Service<R> {
+    pub const fn new(repo: R) -> Self {
+        Self { repo }
+    }
+
+    fn find_by_name(&self, name: &Name) -> Result<Option<Game>, anyhow::Error> {
+        match self.repo.find_game_by_name(name) {
+            Ok(game) => Ok(game),
+            Err(GameGetError::NotFound { .. }) => Ok(None),
+            Err(GameGetError::Unknown(err)) => Err(err),
+        }
+    }
+}
+
+impl<R: GameRepository> GameService for Service<R> {
+    fn create_game(&self, req: &GameCreateRequest) -> Result<Game, GameCreateError> {
+        if self.find_by_name(req.name())?.is_some() {
+            return Err(GameCreateError::Duplicate {
+                game_name: req.name().clone(),
+            });
+        }
+
+        self.repo.create_game(req)
+    }
+
+    fn restore_game(&self, game: &Game) -> Result<Game, GameCreateError> {
+        if self.find_by_name(game.name())?.is_some() {
+            return Err(GameCreateError::Duplicate {
+                game_name: game.name().clone(),
+            });
+        }
+
+        self.repo.restore_game(game)
+    }
+
+    fn update_game(&self, req: &GameUpdateRequest) -> Result<Game, GameUpdateError> {
+        match self.repo.get_game(req.id()) {
+            Ok(_) => {}
+            Err(GameGetError::NotFound { id }) => return Err(GameUpdateError::NotFound { id }),
+            Err(GameGetError::Unknown(err)) => return Err(err.into()),
+        }
+
+        let new_name = req.game().name();
+        if let Some(other) = self.find_by_name(new_name)?
+            && other.id() != req.id()
+        {
+            return Err(GameUpdateError::Duplicate {
+                game_name: new_name.clone(),
+            });
+        }
+
+        self.repo.update_game(req)
+    }
+
+    fn add_playtime(&self, id: GameId, played: Playtime) -> Result<Game, GameUpdateError> {
+        self.repo.add_playtime(id, played)
+    }
+
+    fn delete_game(&self, req: &GameDeleteRequest) -> Result<(), GameDeleteError> {
+        self.repo.delete_game(req)
+    }
+
+    fn get_game(&self, id: GameId) -> Result<Game, GameGetError> {
+        self.repo.get_game(id)
+    }
+
+    fn find_game_by_name(&self, name: &Name) -> Result<Option<Game>, GameGetError> {
+        self.repo.find_game_by_name(name)
+    }
+
+    fn list_games(&self) -> Result<Vec<Game>, GameListError> {
+        self.repo.list_games()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        domain::game::{
+            GameCreateError, GameCreateRequest, GameDeleteError, GameDeleteRequest, GameId,
+            GameService, GameUpdateError, GameUpdateRequest, Name, Platform, Service,
+        },
+        outbound::{Fault, InMemoryGameRepository},
+    };
+    use pretty_assertions::assert_eq;
+
+    fn request(name: &str) -> GameCreateRequest {
+        GameCreateRequest::builder()
+            .name(Name::new(name).unwrap())
+            .platform(Platform::new("Nintendo Switch").unwrap())
+            .build()
+    }
+
+    #[test]
+    fn create_game_rejects_duplicates() {
+        let service = Service::new(InMemoryGameRepository::new());
+        service.create_game(&request("mario")).unwrap();
+
+        let result = service.create_game(&request("mario"));
+
+        assert!(matches!(result, Err(GameCreateError::Duplicate { .. })));
+        assert_eq!(service.list_games().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn update_game_renames_in_place() {
+        let service = Service::new(InMemoryGameRepository::new());
+        let mario = service.create_game(&request("mario")).unwrap();
+        service.create_game(&request("zelda")).unwrap();
+
+        let req = GameUpdateRequest::builder()
+            .id(mario.id())
+            .game(request("luigi"))
+            .build();
+        service.update_game(&req).unwrap();
+
+        let names: Vec<String> = service
+            .list_games()
+            .unwrap()
+            .iter()
+            .map(|game| game.name().to_string())
+            .collect();
+        assert_eq!(names, ["luigi", "zelda"]);
+    }
+
+    #[test]
+    fn update_game_rejects_missing_and_duplicate_names() {
+        let service = Service::new(InMemoryGameRepository::new());
+        let mario = service.create_game(&request("mario")).unwrap();
+        service.create_game(&request("zelda")).unwrap();
+
+        let missing = GameUpdateRequest::builder()
+            .id(GameId::generate())
+            .game(request("luigi"))
+            .build();
+        let duplicate = GameUpdateRequest::builder()
+            .id(mario.id())
+            .game(request("zelda"))
+            .build();
+
+        assert!(matches!(
+            service.update_game(&missing),
+            Err(GameUpdateError::NotFound { .. })
+        ));
+        assert!(matches!(
+            service.update_game(&duplicate),
+            Err(GameUpdateError::Duplicate { .. })
+        ));
+    }
+
+    #[test]
+    fn storage_failures_are_surfaced() {
+        let repo = InMemoryGameRepository::new();
+        repo.fail_next(Fault::Io);
+        repo.fail_next(Fault::NotFound);
+        let service = Service::new(repo);
+
+        assert!(matches!(
+            service.create_game(&request("mario")),
+            Err(GameCreateError::Unknown(_))
+        ));
+        assert!(matches!(
+            service.delete_game(&GameDeleteRequest::from(GameId::generate())),
+            Err(GameDeleteError::NotFound { .. })
+        ));
+        assert!(service.list_games().unwrap().is_empty());
+    }
+}
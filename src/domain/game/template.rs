@@ -0,0 +1,277 @@
+use std::{collections::BTreeMap, fmt, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::domain::game::{FieldName, Game, Playtime, Tag};
+
+/// Most characters Discord shows in the state or details of an activity.
+pub const PRESENCE_TEXT_LIMIT: usize = 128;
+
+/// Placeholders every game can use, next to its custom fields.
+pub const BUILTIN_PLACEHOLDERS: [&str; 6] =
+    ["name", "platform", "tag", "tags", "elapsed", "playtime"];
+
+/// Placeholders whose value changes while the game is being played.
+const TIME_PLACEHOLDERS: [&str; 2] = ["elapsed", "playtime"];
+
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+enum Segment {
+    Text(String),
+    Placeholder(String),
+}
+
+/// Text of an activity with `{placeholder}`s, e.g. `{name} — {status}`. `{{` and `}}` stand
+/// for literal braces.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Template {
+    raw: String,
+    segments: Vec<Segment>,
+}
+
+impl Template {
+    pub fn new(raw: &str) -> Result<Self, TemplateError> {
+        let mut segments = Vec::new();
+        let mut text = String::new();
+        let mut chars = raw.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.next_if_eq(&'{').is_some() => text.push('{'),
+                '}' if chars.next_if_eq(&'}').is_some() => text.push('}'),
+                '{' => {
+                    let mut name = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some('{') | None => return Err(TemplateError::Unclosed(raw.into())),
+                            Some(c) => name.push(c),
+                        }
+                    }
+                    if !text.is_empty() {
+                        segments.push(Segment::Text(std::mem::take(&mut text)));
+                    }
+                    segments.push(Segment::Placeholder(name));
+                }
+                '}' => return Err(TemplateError::Unmatched(raw.into())),
+                c => text.push(c),
+            }
+        }
+        if !text.is_empty() {
+            segments.push(Segment::Text(text));
+        }
+
+        Ok(Self {
+            raw: raw.to_string(),
+            segments,
+        })
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.raw
+    }
+
+    pub fn placeholders(&self) -> impl Iterator<Item = &str> {
+        self.segments.iter().filter_map(|segment| match segment {
+            Segment::Placeholder(name) => Some(name.as_str()),
+            Segment::Text(_) => None,
+        })
+    }
+
+    /// Whether the rendered text goes stale as time passes.
+    pub fn mentions_time(&self) -> bool {
+        self.placeholders()
+            .any(|name| TIME_PLACEHOLDERS.contains(&name))
+    }
+
+    /// Fails on the first placeholder that is neither built in nor one of `fields`.
+    pub fn check(&self, fields: &BTreeMap<FieldName, String>) -> Result<(), TemplateError> {
+        match self.placeholders().find(|&name| {
+            !BUILTIN_PLACEHOLDERS.contains(&name) && !fields.keys().any(|key| key.as_str() == name)
+        }) {
+            Some(unknown) => Err(TemplateError::Unknown(unknown.to_string())),
+            None => Ok(()),
+        }
+    }
+
+    /// The text with every placeholder replaced. Unknown placeholders are left out, and text
+    /// past [`PRESENCE_TEXT_LIMIT`] is cut off with an ellipsis.
+    pub fn render(&self, values: &Placeholders) -> String {
+        let text = self.expand(values);
+        if text.chars().count() > PRESENCE_TEXT_LIMIT {
+            let mut cut: String = text.chars().take(PRESENCE_TEXT_LIMIT - 1).collect();
+            cut.push('…');
+            cut
+        } else {
+            text
+        }
+    }
+
+    /// Like [`Template::check`], and also fails if the text rendered for `values` is too long
+    /// for Discord.
+    pub fn validate(&self, values: &Placeholders) -> Result<(), TemplateError> {
+        self.check(values.game.fields())?;
+
+        let len = self.expand(values).chars().count();
+        if len > PRESENCE_TEXT_LIMIT {
+            return Err(TemplateError::TooLong { len });
+        }
+
+        Ok(())
+    }
+
+    /// Whether [`Template::render`] has to cut the text for `values` short.
+    pub fn overflows(&self, values: &Placeholders) -> bool {
+        self.expand(values).chars().count() > PRESENCE_TEXT_LIMIT
+    }
+
+    fn expand(&self, values: &Placeholders) -> String {
+        self.segments
+            .iter()
+            .map(|segment| match segment {
+                Segment::Text(text) => text.clone(),
+                Segment::Placeholder(name) => values.get(name).unwrap_or_default(),
+            })
+            .collect()
+    }
+}
+
+impl FromStr for Template {
+    type Err = TemplateError;
+
+    fn from_str(raw: &str) -> Result<Self, Self::Err> {
+        Self::new(raw)
+    }
+}
+
+impl TryFrom<String> for Template {
+    type Error = TemplateError;
+
+    fn try_from(raw: String) -> Result<Self, Self::Error> {
+        Self::new(&raw)
+    }
+}
+
+impl From<Template> for String {
+    fn from(value: Template) -> Self {
+        value.raw
+    }
+}
+
+impl fmt::Display for Template {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.raw)
+    }
+}
+
+/// What the placeholders of a [`Template`] stand for while `game` is being played.
+#[derive(Clone, Copy, Debug)]
+pub struct Placeholders<'a> {
+    game: &'a Game,
+    /// Time since the session started.
+    elapsed: Playtime,
+}
+
+impl<'a> Placeholders<'a> {
+    pub const fn new(game: &'a Game, elapsed: Playtime) -> Self {
+        Self { game, elapsed }
+    }
+
+    fn get(&self, name: &str) -> Option<String> {
+        let game = self.game;
+        Some(match name {
+            "name" => game.name().to_string(),
+            "platform" => game.platform().to_string(),
+            "tag" => game.tags().first().map(Tag::to_string).unwrap_or_default(),
+            "tags" => game
+                .tags()
+                .iter()
+                .map(Tag::as_str)
+                .collect::<Vec<_>>()
+                .join(", "),
+            "elapsed" => self.elapsed.to_string(),
+            "playtime" => (game.playtime() + self.elapsed).to_string(),
+            field => game
+                .fields()
+                .iter()
+                .find(|(key, _)| key.as_str() == field)
+                .map(|(_, value)| value.clone())?,
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Error)]
+pub enum TemplateError {
+    #[error("unclosed placeholder in {0:?}, write {{{{ for a literal brace")]
+    Unclosed(String),
+    #[error("unmatched }} in {0:?}, write }}}} for a literal brace")]
+    Unmatched(String),
+    #[error("unknown placeholder {{{0}}}")]
+    Unknown(String),
+    #[error("{len} characters, Discord shows at most {PRESENCE_TEXT_LIMIT}")]
+    TooLong { len: usize },
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use crate::domain::game::{
+        FieldName, Game, GameId, Name, Platform, Playtime, Tag,
+        template::{Placeholders, Template, TemplateError},
+    };
+    use pretty_assertions::assert_eq;
+
+    fn zelda() -> Game {
+        Game::builder()
+            .id(GameId::generate())
+            .name(Name::new("Zelda").unwrap())
+            .platform(Platform::new("Nintendo Switch").unwrap())
+            .tags([Tag::new("co-op").unwrap()].into())
+            .fields(BTreeMap::from([(
+                FieldName::new("status").unwrap(),
+                String::from("Shrine hunting"),
+            )]))
+            .playtime(Playtime::from_seconds(2 * 3600))
+            .build()
+    }
+
+    #[test]
+    fn renders_placeholders() {
+        let game = zelda();
+        let values = Placeholders::new(&game, Playtime::from_seconds(65 * 60));
+        let render = |raw: &str| Template::new(raw).unwrap().render(&values);
+
+        assert_eq!(render("{name} — {status}"), "Zelda — Shrine hunting");
+        assert_eq!(render("{platform} · {tag}"), "Nintendo Switch · co-op");
+        assert_eq!(render("{elapsed} of {playtime}"), "1h 05m of 3h 05m");
+        assert_eq!(render("{{{name}}} {missing}"), "{Zelda} ");
+        assert_eq!(render(&"x".repeat(200)).chars().count(), 128);
+    }
+
+    #[test]
+    fn reports_bad_templates() {
+        let game = zelda();
+        let values = Placeholders::new(&game, Playtime::default());
+
+        assert!(matches!(
+            Template::new("{name"),
+            Err(TemplateError::Unclosed(_))
+        ));
+        assert!(matches!(
+            Template::new("name}"),
+            Err(TemplateError::Unmatched(_))
+        ));
+        assert_eq!(
+            Template::new("{name} {mood}").unwrap().validate(&values),
+            Err(TemplateError::Unknown(String::from("mood")))
+        );
+        assert_eq!(
+            Template::new(&format!("{} {{status}}", "x".repeat(120)))
+                .unwrap()
+                .validate(&values),
+            Err(TemplateError::TooLong { len: 135 })
+        );
+    }
+}
@@ -1 +1,11 @@
-pub mod game;
\ No newline at end of file
+use crate::domain::{game::GameRepository, platform::PlatformRepository};
+
+pub mod asset;
+pub mod game;
+pub mod platform;
+pub mod presence;
+
+/// Storage for the whole library: the games and the platform registry they refer to.
+pub trait Repository: GameRepository + PlatformRepository {}
+
+impl<R: GameRepository + PlatformRepository + ?Sized> Repository for R {}
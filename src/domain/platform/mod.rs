@@ -0,0 +1,209 @@
+use bon::Builder;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::domain::game::{Game, Image, Platform, Tooltip};
+
+mod service;
+
+/// A registered platform: its canonical name, alternative spellings and the console icon that
+/// games on it show unless they override it. Serialized as a `[[platform]]` table.
+#[derive(Clone, Debug, PartialEq, Eq, Builder, Serialize, Deserialize)]
+pub struct PlatformDefinition {
+    name: Platform,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[builder(default)]
+    aliases: Vec<Platform>,
+    #[serde(rename = "small_image_key")]
+    small_image: Option<Image>,
+    #[serde(rename = "small_image_tooltip")]
+    small_tooltip: Option<Tooltip>,
+}
+
+impl PlatformDefinition {
+    pub const fn name(&self) -> &Platform {
+        &self.name
+    }
+
+    pub fn aliases(&self) -> &[Platform] {
+        &self.aliases
+    }
+
+    pub const fn small_image(&self) -> Option<&Image> {
+        self.small_image.as_ref()
+    }
+
+    pub const fn small_tooltip(&self) -> Option<&Tooltip> {
+        self.small_tooltip.as_ref()
+    }
+
+    /// Every spelling that refers to this platform, the canonical name first.
+    pub fn names(&self) -> impl Iterator<Item = &Platform> {
+        std::iter::once(&self.name).chain(&self.aliases)
+    }
+
+    /// Whether `platform` is the name or one of the aliases, ignoring ASCII case.
+    pub fn matches(&self, platform: &Platform) -> bool {
+        self.names()
+            .any(|name| name.as_str().eq_ignore_ascii_case(platform.as_str()))
+    }
+
+    /// Returns `game` on this platform's canonical name, with the platform's small image and
+    /// tooltip filled in where the game does not set its own.
+    pub fn apply_to(&self, game: &Game) -> Game {
+        Game::builder()
+            .id(game.id())
+            .name(game.name().clone())
+            .platform(self.name.clone())
+            .maybe_large_image(game.large_image().cloned())
+            .maybe_large_tooltip(game.large_tooltip().cloned())
+            .maybe_small_image(game.small_image().or(self.small_image()).cloned())
+            .maybe_small_tooltip(game.small_tooltip().or(self.small_tooltip()).cloned())
+            .tags(game.tags().clone())
+            .collections(game.collections().clone())
+            .maybe_show_elapsed(game.show_elapsed())
+            .maybe_party(game.party().cloned())
+            .maybe_state(game.state().cloned())
+            .maybe_details(game.details().cloned())
+            .fields(game.fields().clone())
+            .playtime(game.playtime())
+            .secrets(game.secrets().clone())
+            .build()
+    }
+
+    /// Copy of this definition under a different canonical name.
+    pub fn renamed(&self, name: Platform) -> Self {
+        Self {
+            name,
+            ..self.clone()
+        }
+    }
+}
+
+/// `PlatformUpdateRequest` replaces the definition of the platform called `name`.
+#[derive(Clone, Debug, PartialEq, Eq, Builder)]
+pub struct PlatformUpdateRequest {
+    name: Platform,
+    platform: PlatformDefinition,
+}
+
+impl PlatformUpdateRequest {
+    pub const fn name(&self) -> &Platform {
+        &self.name
+    }
+
+    pub const fn platform(&self) -> &PlatformDefinition {
+        &self.platform
+    }
+}
+
+/// `PlatformRepository` stores the platform registry next to the games.
+pub trait PlatformRepository {
+    fn create_platform(
+        &self,
+        platform: &PlatformDefinition,
+    ) -> Result<PlatformDefinition, PlatformCreateError>;
+    /// Replaces a definition. When the name changes, every game on the old name is moved to the
+    /// new one as part of the same change.
+    fn update_platform(
+        &self,
+        req: &PlatformUpdateRequest,
+    ) -> Result<PlatformDefinition, PlatformUpdateError>;
+    fn delete_platform(&self, name: &Platform) -> Result<(), PlatformDeleteError>;
+    /// Looks a platform up by its name or any of its aliases.
+    fn find_platform(
+        &self,
+        platform: &Platform,
+    ) -> Result<Option<PlatformDefinition>, PlatformGetError>;
+    fn list_platforms(&self) -> Result<Vec<PlatformDefinition>, PlatformListError>;
+}
+
+impl<R: PlatformRepository + ?Sized> PlatformRepository for Box<R> {
+    fn create_platform(
+        &self,
+        platform: &PlatformDefinition,
+    ) -> Result<PlatformDefinition, PlatformCreateError> {
+        (**self).create_platform(platform)
+    }
+
+    fn update_platform(
+        &self,
+        req: &PlatformUpdateRequest,
+    ) -> Result<PlatformDefinition, PlatformUpdateError> {
+        (**self).update_platform(req)
+    }
+
+    fn delete_platform(&self, name: &Platform) -> Result<(), PlatformDeleteError> {
+        (**self).delete_platform(name)
+    }
+
+    fn find_platform(
+        &self,
+        platform: &Platform,
+    ) -> Result<Option<PlatformDefinition>, PlatformGetError> {
+        (**self).find_platform(platform)
+    }
+
+    fn list_platforms(&self) -> Result<Vec<PlatformDefinition>, PlatformListError> {
+        (**self).list_platforms()
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum PlatformCreateError {
+    #[error("platform {platform} already exists")]
+    Duplicate { platform: Platform },
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[derive(Debug, Error)]
+pub enum PlatformUpdateError {
+    #[error("platform {platform} does not exist")]
+    NotFound { platform: Platform },
+    #[error("platform {platform} already exists")]
+    Duplicate { platform: Platform },
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[derive(Debug, Error)]
+pub enum PlatformDeleteError {
+    #[error("platform {platform} does not exist")]
+    NotFound { platform: Platform },
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[derive(Debug, Error)]
+pub enum PlatformGetError {
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[derive(Debug, Error)]
+pub enum PlatformListError {
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+/// `PlatformService` manages the platform registry and applies it to games.
+pub trait PlatformService {
+    fn create_platform(
+        &self,
+        platform: &PlatformDefinition,
+    ) -> Result<PlatformDefinition, PlatformCreateError>;
+    fn update_platform(
+        &self,
+        req: &PlatformUpdateRequest,
+    ) -> Result<PlatformDefinition, PlatformUpdateError>;
+    fn delete_platform(&self, name: &Platform) -> Result<(), PlatformDeleteError>;
+    fn find_platform(
+        &self,
+        platform: &Platform,
+    ) -> Result<Option<PlatformDefinition>, PlatformGetError>;
+    fn list_platforms(&self) -> Result<Vec<PlatformDefinition>, PlatformListError>;
+    /// `game` with the defaults of its registered platform applied, or unchanged if its platform
+    /// is not registered.
+    fn resolve_game(&self, game: &Game) -> Result<Game, PlatformGetError>;
+}
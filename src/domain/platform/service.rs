@@ -0,0 +1,187 @@
+use crate::domain::{
+    Repository,
+    game::{Game, Platform, Service},
+    platform::{
+        PlatformCreateError, PlatformDefinition, PlatformDeleteError, PlatformGetError,
+        PlatformListError, PlatformService, PlatformUpdateError, PlatformUpdateRequest,
+    },
+};
+
+impl<R: Repository> Service<R> {
+    /// First name of `platform` that is already taken by a registered platform other than
+    /// `except`.
+    fn conflicting_name(
+        &self,
+        platform: &PlatformDefinition,
+        except: Option<&Platform>,
+    ) -> Result<Option<Platform>, anyhow::Error> {
+        let registered = self.repo.list_platforms().map_err(|err| match err {
+            PlatformListError::Unknown(err) => err,
+        })?;
+
+        Ok(registered
+            .iter()
+            .filter(|other| Some(other.name()) != except)
+            .find_map(|other| platform.names().find(|name| other.matches(name)))
+            .cloned())
+    }
+}
+
+impl<R: Repository> PlatformService for Service<R> {
+    fn create_platform(
+        &self,
+        platform: &PlatformDefinition,
+    ) -> Result<PlatformDefinition, PlatformCreateError> {
+        if let Some(platform) = self.conflicting_name(platform, None)? {
+            return Err(PlatformCreateError::Duplicate { platform });
+        }
+
+        self.repo.create_platform(platform)
+    }
+
+    fn update_platform(
+        &self,
+        req: &PlatformUpdateRequest,
+    ) -> Result<PlatformDefinition, PlatformUpdateError> {
+        let existing = self
+            .repo
+            .find_platform(req.name())
+            .map_err(|PlatformGetError::Unknown(err)| err)?
+            .filter(|existing| existing.name() == req.name())
+            .ok_or_else(|| PlatformUpdateError::NotFound {
+                platform: req.name().clone(),
+            })?;
+
+        if let Some(platform) = self.conflicting_name(req.platform(), Some(existing.name()))? {
+            return Err(PlatformUpdateError::Duplicate { platform });
+        }
+
+        self.repo.update_platform(req)
+    }
+
+    fn delete_platform(&self, name: &Platform) -> Result<(), PlatformDeleteError> {
+        self.repo.delete_platform(name)
+    }
+
+    fn find_platform(
+        &self,
+        platform: &Platform,
+    ) -> Result<Option<PlatformDefinition>, PlatformGetError> {
+        self.repo.find_platform(platform)
+    }
+
+    fn list_platforms(&self) -> Result<Vec<PlatformDefinition>, PlatformListError> {
+        self.repo.list_platforms()
+    }
+
+    fn resolve_game(&self, game: &Game) -> Result<Game, PlatformGetError> {
+        Ok(match self.repo.find_platform(game.platform())? {
+            Some(platform) => platform.apply_to(game),
+            None => game.clone(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        domain::{
+            game::{GameCreateRequest, GameService, Image, Name, Platform, Service, Tooltip},
+            platform::{
+                PlatformCreateError, PlatformDefinition, PlatformService, PlatformUpdateRequest,
+            },
+        },
+        outbound::InMemoryGameRepository,
+    };
+    use pretty_assertions::assert_eq;
+
+    fn platform(name: &str) -> Platform {
+        Platform::new(name).unwrap()
+    }
+
+    fn switch() -> PlatformDefinition {
+        PlatformDefinition::builder()
+            .name(platform("Nintendo Switch"))
+            .aliases(vec![platform("Switch")])
+            .small_image(Image::from(String::from("switch")))
+            .small_tooltip(Tooltip::from(String::from("Nintendo Switch")))
+            .build()
+    }
+
+    fn game(name: &str, on: &str) -> GameCreateRequest {
+        GameCreateRequest::builder()
+            .name(Name::new(name).unwrap())
+            .platform(platform(on))
+            .build()
+    }
+
+    #[test]
+    fn games_inherit_platform_defaults() {
+        let service = Service::new(InMemoryGameRepository::new());
+        service.create_platform(&switch()).unwrap();
+        let inherited = service.create_game(&game("mario", "switch")).unwrap();
+        let overridden = service
+            .create_game(
+                &GameCreateRequest::builder()
+                    .name(Name::new("zelda").unwrap())
+                    .platform(platform("Switch"))
+                    .small_image(Image::from(String::from("zelda")))
+                    .build(),
+            )
+            .unwrap();
+
+        let inherited = service.resolve_game(&inherited).unwrap();
+        let overridden = service.resolve_game(&overridden).unwrap();
+
+        assert_eq!(inherited.platform(), &platform("Nintendo Switch"));
+        assert_eq!(inherited.small_image().map(Image::as_str), Some("switch"));
+        assert_eq!(overridden.small_image().map(Image::as_str), Some("zelda"));
+        assert_eq!(
+            overridden.small_tooltip().map(Tooltip::as_str),
+            Some("Nintendo Switch")
+        );
+    }
+
+    #[test]
+    fn names_and_aliases_are_unique() {
+        let service = Service::new(InMemoryGameRepository::new());
+        service.create_platform(&switch()).unwrap();
+
+        let clash = PlatformDefinition::builder()
+            .name(platform("Switch 2"))
+            .aliases(vec![platform("SWITCH")])
+            .build();
+
+        assert!(matches!(
+            service.create_platform(&clash),
+            Err(PlatformCreateError::Duplicate { .. })
+        ));
+    }
+
+    #[test]
+    fn renaming_moves_games() {
+        let service = Service::new(InMemoryGameRepository::new());
+        service.create_platform(&switch()).unwrap();
+        service
+            .create_game(&game("mario", "Nintendo Switch"))
+            .unwrap();
+        service
+            .create_game(&game("zelda", "nintendo switch"))
+            .unwrap();
+        service.create_game(&game("tetris", "Game Boy")).unwrap();
+
+        let req = PlatformUpdateRequest::builder()
+            .name(platform("Nintendo Switch"))
+            .platform(switch().renamed(platform("Switch 1")))
+            .build();
+        service.update_platform(&req).unwrap();
+
+        let platforms: Vec<String> = service
+            .list_games()
+            .unwrap()
+            .iter()
+            .map(|game| game.platform().to_string())
+            .collect();
+        assert_eq!(platforms, ["Switch 1", "Switch 1", "Game Boy"]);
+    }
+}
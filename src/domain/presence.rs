@@ -0,0 +1,534 @@
+use bon::Builder;
+use chrono::{DateTime, Utc};
+use derive_more::Display;
+use thiserror::Error;
+
+use crate::domain::{
+    game::{Game, Image, Party, Placeholders, Playtime, Secrets, Template, Tooltip},
+    presence::lobby::{Lobby, LobbyCommand, LobbyId, LobbyMessage},
+};
+
+pub mod connection;
+pub mod lobby;
+pub mod scheduler;
+
+/// What the user is shown to be doing, independent of the service that displays it.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Builder)]
+pub struct Presence {
+    state: String,
+    details: String,
+    large_image: Option<Image>,
+    large_tooltip: Option<Tooltip>,
+    small_image: Option<Image>,
+    small_tooltip: Option<Tooltip>,
+    /// Start of the session, shown as elapsed time.
+    start: Option<DateTime<Utc>>,
+    party: Option<Party>,
+    #[builder(default)]
+    secrets: Secrets,
+}
+
+impl Presence {
+    pub fn state(&self) -> &str {
+        &self.state
+    }
+
+    pub fn details(&self) -> &str {
+        &self.details
+    }
+
+    pub const fn large_image(&self) -> Option<&Image> {
+        self.large_image.as_ref()
+    }
+
+    pub const fn large_tooltip(&self) -> Option<&Tooltip> {
+        self.large_tooltip.as_ref()
+    }
+
+    pub const fn small_image(&self) -> Option<&Image> {
+        self.small_image.as_ref()
+    }
+
+    pub const fn small_tooltip(&self) -> Option<&Tooltip> {
+        self.small_tooltip.as_ref()
+    }
+
+    pub const fn start(&self) -> Option<DateTime<Utc>> {
+        self.start
+    }
+
+    pub const fn party(&self) -> Option<&Party> {
+        self.party.as_ref()
+    }
+
+    pub const fn secrets(&self) -> &Secrets {
+        &self.secrets
+    }
+
+    /// The presence of `game` after `elapsed` of play. The state and details are rendered from
+    /// the game's own templates, or else from `defaults`.
+    pub fn render(game: &Game, defaults: &ActivityTemplates, elapsed: Playtime) -> Self {
+        let values = Placeholders::new(game, elapsed);
+
+        Self::builder()
+            .state(defaults.state_for(game).render(&values))
+            .details(defaults.details_for(game).render(&values))
+            .maybe_large_image(game.large_image().cloned())
+            .maybe_large_tooltip(game.large_tooltip().cloned())
+            .maybe_small_image(game.small_image().cloned())
+            .maybe_small_tooltip(game.small_tooltip().cloned())
+            .maybe_party(game.party().cloned())
+            .secrets(game.secrets().clone())
+            .build()
+    }
+
+    /// The same presence, counting the elapsed time from `start`.
+    pub fn with_start(self, start: Option<DateTime<Utc>>) -> Self {
+        Self { start, ..self }
+    }
+
+    pub fn with_secrets(self, secrets: Secrets) -> Self {
+        Self { secrets, ..self }
+    }
+
+    pub fn with_party(self, party: Option<Party>) -> Self {
+        Self { party, ..self }
+    }
+}
+
+/// The presence of `game` as it is activated, with the default templates.
+impl From<&Game> for Presence {
+    fn from(game: &Game) -> Self {
+        Self::render(game, &ActivityTemplates::default(), Playtime::default())
+    }
+}
+
+/// State and details of games that have no templates of their own.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ActivityTemplates {
+    state: Template,
+    details: Template,
+}
+
+impl ActivityTemplates {
+    pub const fn new(state: Template, details: Template) -> Self {
+        Self { state, details }
+    }
+
+    pub const fn state(&self) -> &Template {
+        &self.state
+    }
+
+    pub const fn details(&self) -> &Template {
+        &self.details
+    }
+
+    /// The template the state of `game` is rendered from.
+    pub fn state_for<'a>(&'a self, game: &'a Game) -> &'a Template {
+        game.state().unwrap_or(&self.state)
+    }
+
+    /// The template the details of `game` are rendered from.
+    pub fn details_for<'a>(&'a self, game: &'a Game) -> &'a Template {
+        game.details().unwrap_or(&self.details)
+    }
+
+    /// Whether the text rendered for `game` goes stale as time passes.
+    pub fn mentions_time(&self, game: &Game) -> bool {
+        self.state_for(game).mentions_time() || self.details_for(game).mentions_time()
+    }
+}
+
+/// The game's name as the state and its platform as the details.
+impl Default for ActivityTemplates {
+    fn default() -> Self {
+        Self {
+            state: Template::new("{name}").expect("valid template"),
+            details: Template::new("{platform}").expect("valid template"),
+        }
+    }
+}
+
+/// Whether a [`PresencePublisher`] can currently reach its service.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Display)]
+pub enum PresenceStatus {
+    #[display("connected")]
+    Connected,
+    /// Not connected yet, or lost the connection, and trying again.
+    #[display("connecting")]
+    Connecting,
+    #[display("offline")]
+    Offline,
+}
+
+/// Identifier of a Discord user.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Display)]
+pub struct UserId(i64);
+
+impl UserId {
+    pub const fn new(id: i64) -> Self {
+        Self(id)
+    }
+
+    pub const fn get(self) -> i64 {
+        self.0
+    }
+}
+
+/// Someone on Discord, e.g. a friend asking to join.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct User {
+    id: UserId,
+    name: String,
+}
+
+impl User {
+    pub const fn new(id: UserId, name: String) -> Self {
+        Self { id, name }
+    }
+
+    pub const fn id(&self) -> UserId {
+        self.id
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// Whether a friend is around, as Discord shows with the dot next to their name.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Display)]
+pub enum OnlineStatus {
+    #[display("online")]
+    Online,
+    #[display("idle")]
+    Idle,
+    #[display("do not disturb")]
+    DoNotDisturb,
+    #[display("offline")]
+    Offline,
+}
+
+/// What a friend is doing, as their Discord activity shows it.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct FriendActivity {
+    /// The application or game, e.g. "Console Player" for someone using this app.
+    name: String,
+    state: String,
+    details: String,
+}
+
+impl FriendActivity {
+    pub const fn new(name: String, state: String, details: String) -> Self {
+        Self {
+            name,
+            state,
+            details,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn state(&self) -> &str {
+        &self.state
+    }
+
+    pub fn details(&self) -> &str {
+        &self.details
+    }
+
+    /// Whether the activity is about `game`, judging by its name appearing as the application,
+    /// the state or the details. Activities from console players name the game in the state.
+    pub fn is_playing(&self, game: &Game) -> bool {
+        let name = game.name().as_str();
+        [&self.name, &self.state, &self.details]
+            .into_iter()
+            .any(|text| text.trim().eq_ignore_ascii_case(name))
+    }
+}
+
+/// Someone on the user's Discord friends list.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Friend {
+    user: User,
+    status: OnlineStatus,
+    activity: Option<FriendActivity>,
+}
+
+impl Friend {
+    pub const fn new(user: User, status: OnlineStatus) -> Self {
+        Self {
+            user,
+            status,
+            activity: None,
+        }
+    }
+
+    pub fn with_activity(self, activity: Option<FriendActivity>) -> Self {
+        Self { activity, ..self }
+    }
+
+    pub const fn user(&self) -> &User {
+        &self.user
+    }
+
+    pub const fn status(&self) -> OnlineStatus {
+        self.status
+    }
+
+    /// What the friend is doing, `None` if nothing is shown.
+    pub const fn activity(&self) -> Option<&FriendActivity> {
+        self.activity.as_ref()
+    }
+}
+
+/// The user's friends, online ones first and then by name, kept current from the
+/// [`PresenceEvent`]s about them.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FriendList {
+    friends: Vec<Friend>,
+}
+
+impl FriendList {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Applies `event` if it is about friends and tells whether it was.
+    pub fn apply(&mut self, event: &PresenceEvent) -> bool {
+        match event {
+            PresenceEvent::Friends(friends) => self.friends.clone_from(friends),
+            PresenceEvent::FriendUpdate(friend) => {
+                self.friends.retain(|known| known.user.id != friend.user.id);
+                self.friends.push(friend.clone());
+            }
+            PresenceEvent::FriendRemoved(id) => self.friends.retain(|known| known.user.id != *id),
+            _ => return false,
+        }
+
+        self.friends.sort_by_cached_key(|friend| {
+            (
+                friend.status,
+                friend.user.name.to_lowercase(),
+                friend.user.id,
+            )
+        });
+        true
+    }
+
+    pub fn get(&self, index: usize) -> Option<&Friend> {
+        self.friends.get(index)
+    }
+
+    pub fn find(&self, id: UserId) -> Option<&Friend> {
+        self.friends.iter().find(|friend| friend.user.id == id)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Friend> {
+        self.friends.iter()
+    }
+
+    pub const fn len(&self) -> usize {
+        self.friends.len()
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.friends.is_empty()
+    }
+}
+
+/// What an invite sent with [`PresencePublisher::send_invite`] lets the friend do.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Display)]
+pub enum InviteKind {
+    #[display("join")]
+    Join,
+    #[display("spectate")]
+    Spectate,
+}
+
+/// Something the service tells us about, collected by [`PresencePublisher::take_events`].
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum PresenceEvent {
+    /// `User` asks to join the game we show, see [`PresencePublisher::reply_to_join`].
+    JoinRequest(User),
+    /// We were let into someone else's game, which the secret identifies.
+    Join(String),
+    /// We are spectating someone else's game, which the secret identifies.
+    Spectate(String),
+    /// The whole friends list, replacing whatever was known before.
+    Friends(Vec<Friend>),
+    /// A friend was added or changed.
+    FriendUpdate(Friend),
+    /// Someone is no longer a friend.
+    FriendRemoved(UserId),
+    /// The invite to the user was delivered.
+    InviteSent(UserId),
+    /// The invite to the user could not be delivered, for the given reason.
+    InviteFailed(UserId, String),
+    /// We opened or joined the lobby. Its members follow as [`PresenceEvent::LobbyMemberJoined`].
+    LobbyJoined(Lobby),
+    LobbyMemberJoined(LobbyId, User),
+    LobbyMemberLeft(LobbyId, UserId),
+    /// A member sent a message to the lobby.
+    LobbyMessage(LobbyId, UserId, LobbyMessage),
+    /// We left the lobby, or it was deleted.
+    LobbyClosed(LobbyId),
+    /// A [`LobbyCommand`] failed, for the given reason.
+    LobbyFailed(String),
+}
+
+/// Answer to a [`PresenceEvent::JoinRequest`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Display)]
+pub enum JoinReply {
+    #[display("accept")]
+    Accept,
+    #[display("decline")]
+    Decline,
+}
+
+/// `PresencePublisher` shows a [`Presence`] on some service, e.g. a Discord profile.
+pub trait PresencePublisher {
+    /// Replaces the shown presence.
+    fn set(&mut self, presence: &Presence) -> Result<(), PresenceError>;
+    /// Removes the shown presence altogether.
+    fn clear(&mut self) -> Result<(), PresenceError>;
+    fn status(&self) -> PresenceStatus;
+    /// Gives the publisher a chance to do pending work. Called once per frame.
+    fn poll(&mut self) -> Result<(), PresenceError>;
+    /// Delivers everything still queued, e.g. right before the app exits.
+    fn flush(&mut self) -> Result<(), PresenceError> {
+        self.poll()
+    }
+    /// Takes the events that arrived since the last call, oldest first.
+    fn take_events(&mut self) -> Vec<PresenceEvent>;
+    /// Answers the join request of `user`.
+    fn reply_to_join(&mut self, user: UserId, reply: JoinReply) -> Result<(), PresenceError>;
+    /// Invites `user` to the shown presence, which needs the matching secret. Whether the invite
+    /// arrives is told later by [`PresenceEvent::InviteSent`] or [`PresenceEvent::InviteFailed`].
+    fn send_invite(&mut self, user: UserId, kind: InviteKind) -> Result<(), PresenceError>;
+    /// Opens, joins, leaves or messages a lobby. How it went is told later by the lobby
+    /// [`PresenceEvent`]s.
+    fn lobby(&mut self, command: LobbyCommand) -> Result<(), PresenceError>;
+}
+
+impl<P: PresencePublisher + ?Sized> PresencePublisher for Box<P> {
+    fn set(&mut self, presence: &Presence) -> Result<(), PresenceError> {
+        (**self).set(presence)
+    }
+
+    fn clear(&mut self) -> Result<(), PresenceError> {
+        (**self).clear()
+    }
+
+    fn status(&self) -> PresenceStatus {
+        (**self).status()
+    }
+
+    fn poll(&mut self) -> Result<(), PresenceError> {
+        (**self).poll()
+    }
+
+    fn flush(&mut self) -> Result<(), PresenceError> {
+        (**self).flush()
+    }
+
+    fn take_events(&mut self) -> Vec<PresenceEvent> {
+        (**self).take_events()
+    }
+
+    fn reply_to_join(&mut self, user: UserId, reply: JoinReply) -> Result<(), PresenceError> {
+        (**self).reply_to_join(user, reply)
+    }
+
+    fn send_invite(&mut self, user: UserId, kind: InviteKind) -> Result<(), PresenceError> {
+        (**self).send_invite(user, kind)
+    }
+
+    fn lobby(&mut self, command: LobbyCommand) -> Result<(), PresenceError> {
+        (**self).lobby(command)
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum PresenceError {
+    #[error("not connected")]
+    NotConnected,
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::domain::{
+        game::{Game, GameId, Image, Name, Platform},
+        presence::{
+            Friend, FriendActivity, FriendList, OnlineStatus, Presence, PresenceEvent, User, UserId,
+        },
+    };
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn presence_from_game() {
+        let game = Game::builder()
+            .id(GameId::generate())
+            .name(Name::new("mario").unwrap())
+            .platform(Platform::new("Nintendo Switch").unwrap())
+            .large_image(Image::from(String::from("mario")))
+            .build();
+
+        let presence = Presence::from(&game);
+
+        assert_eq!(presence.state(), "mario");
+        assert_eq!(presence.details(), "Nintendo Switch");
+        assert_eq!(presence.large_image().map(Image::as_str), Some("mario"));
+        assert_eq!(presence.small_image(), None);
+    }
+
+    #[test]
+    fn activities_name_the_game_anywhere() {
+        let game = Game::builder()
+            .id(GameId::generate())
+            .name(Name::new("Mario Kart 8").unwrap())
+            .platform(Platform::new("Nintendo Switch").unwrap())
+            .build();
+        let activity = |name: &str, state: &str| {
+            FriendActivity::new(name.to_string(), state.to_string(), String::new())
+        };
+
+        assert!(activity("Mario Kart 8", "").is_playing(&game));
+        assert!(activity("Console Player", "mario kart 8").is_playing(&game));
+        assert!(!activity("Console Player", "Mario Kart 8 Deluxe").is_playing(&game));
+    }
+
+    #[test]
+    fn friend_list_follows_the_events() {
+        let friend = |id, name: &str, status| {
+            Friend::new(User::new(UserId::new(id), name.to_string()), status)
+        };
+        let names = |friends: &FriendList| {
+            friends
+                .iter()
+                .map(|friend| friend.user().name().to_string())
+                .collect::<Vec<_>>()
+        };
+        let mut friends = FriendList::new();
+
+        assert!(friends.apply(&PresenceEvent::Friends(vec![
+            friend(1, "zelda", OnlineStatus::Offline),
+            friend(2, "peach", OnlineStatus::Idle),
+            friend(3, "Luigi", OnlineStatus::Idle),
+        ])));
+        assert_eq!(names(&friends), ["Luigi", "peach", "zelda"]);
+
+        friends.apply(&PresenceEvent::FriendUpdate(friend(
+            1,
+            "zelda",
+            OnlineStatus::Online,
+        )));
+        friends.apply(&PresenceEvent::FriendRemoved(UserId::new(2)));
+        assert!(!friends.apply(&PresenceEvent::Join(String::from("room-7"))));
+        assert_eq!(names(&friends), ["zelda", "Luigi"]);
+    }
+}
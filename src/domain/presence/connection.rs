@@ -0,0 +1,241 @@
+use std::time::Instant;
+
+use log::{debug, info, warn};
+
+use crate::domain::presence::{
+    InviteKind, JoinReply, Presence, PresenceError, PresenceEvent, PresencePublisher,
+    PresenceStatus, UserId,
+    lobby::LobbyCommand,
+    scheduler::{Clock, SystemClock, backoff},
+};
+
+/// `PresencePublisher` that keeps a connection to its service, opening it with `connect`.
+///
+/// It starts out disconnected and tries to connect on every poll, waiting longer after each
+/// failed attempt. The presence asked for meanwhile is remembered and applied as soon as the
+/// connection is up, and again whenever it had to be reopened.
+#[derive(Debug)]
+pub struct Reconnecting<F, P, C = SystemClock> {
+    connect: F,
+    clock: C,
+    connection: Option<P>,
+    /// The presence to show, `None` to show nothing.
+    wanted: Option<Presence>,
+    failures: u32,
+    retry_at: Option<Instant>,
+}
+
+impl<F, P> Reconnecting<F, P>
+where
+    F: FnMut() -> Result<P, PresenceError>,
+    P: PresencePublisher,
+{
+    pub const fn new(connect: F) -> Self {
+        Self::with_clock(connect, SystemClock)
+    }
+}
+
+impl<F, P, C> Reconnecting<F, P, C>
+where
+    F: FnMut() -> Result<P, PresenceError>,
+    P: PresencePublisher,
+    C: Clock,
+{
+    pub const fn with_clock(connect: F, clock: C) -> Self {
+        Self {
+            connect,
+            clock,
+            connection: None,
+            wanted: None,
+            failures: 0,
+            retry_at: None,
+        }
+    }
+
+    /// The publisher of the open connection, if any.
+    pub const fn connection(&self) -> Option<&P> {
+        self.connection.as_ref()
+    }
+
+    /// Connects unless connected already or still waiting to retry, then applies the wanted
+    /// presence.
+    fn ensure_connected(&mut self) -> Result<(), PresenceError> {
+        let now = self.clock.now();
+        if self.connection.is_some() || self.retry_at.is_some_and(|at| now < at) {
+            return Ok(());
+        }
+
+        match (self.connect)() {
+            Ok(connection) => {
+                info!("presence connected");
+                self.connection = Some(connection);
+                self.failures = 0;
+                self.retry_at = None;
+                self.apply()
+            }
+            Err(err) => {
+                self.failures += 1;
+                let wait = backoff(self.failures);
+                debug!("cannot connect the presence, retrying in {wait:?}: {err}");
+                self.retry_at = Some(now + wait);
+                Ok(())
+            }
+        }
+    }
+
+    fn apply(&mut self) -> Result<(), PresenceError> {
+        let wanted = self.wanted.clone();
+        self.with_connection(|connection| match &wanted {
+            Some(presence) => connection.set(presence),
+            None => connection.clear(),
+        })
+    }
+
+    /// Runs `action` on the open connection, if any, and drops the connection if `action` lost
+    /// it.
+    fn with_connection(
+        &mut self,
+        action: impl FnOnce(&mut P) -> Result<(), PresenceError>,
+    ) -> Result<(), PresenceError> {
+        let Some(connection) = &mut self.connection else {
+            return Ok(());
+        };
+
+        let result = action(connection);
+        if result.is_err() && connection.status() != PresenceStatus::Connected {
+            warn!("presence disconnected, reconnecting");
+            self.connection = None;
+            self.failures = 1;
+            self.retry_at = Some(self.clock.now() + backoff(self.failures));
+        }
+
+        result
+    }
+}
+
+impl<F, P, C> PresencePublisher for Reconnecting<F, P, C>
+where
+    F: FnMut() -> Result<P, PresenceError>,
+    P: PresencePublisher,
+    C: Clock,
+{
+    fn set(&mut self, presence: &Presence) -> Result<(), PresenceError> {
+        self.wanted = Some(presence.clone());
+        self.with_connection(|connection| connection.set(presence))
+    }
+
+    fn clear(&mut self) -> Result<(), PresenceError> {
+        self.wanted = None;
+        self.with_connection(PresencePublisher::clear)
+    }
+
+    fn status(&self) -> PresenceStatus {
+        self.connection
+            .as_ref()
+            .map_or(PresenceStatus::Connecting, PresencePublisher::status)
+    }
+
+    fn poll(&mut self) -> Result<(), PresenceError> {
+        self.with_connection(PresencePublisher::poll)?;
+        self.ensure_connected()
+    }
+
+    fn flush(&mut self) -> Result<(), PresenceError> {
+        self.with_connection(PresencePublisher::flush)
+    }
+
+    fn take_events(&mut self) -> Vec<PresenceEvent> {
+        self.connection
+            .as_mut()
+            .map(PresencePublisher::take_events)
+            .unwrap_or_default()
+    }
+
+    fn reply_to_join(&mut self, user: UserId, reply: JoinReply) -> Result<(), PresenceError> {
+        if self.connection.is_none() {
+            return Err(PresenceError::NotConnected);
+        }
+        self.with_connection(|connection| connection.reply_to_join(user, reply))
+    }
+
+    fn send_invite(&mut self, user: UserId, kind: InviteKind) -> Result<(), PresenceError> {
+        if self.connection.is_none() {
+            return Err(PresenceError::NotConnected);
+        }
+        self.with_connection(|connection| connection.send_invite(user, kind))
+    }
+
+    fn lobby(&mut self, command: LobbyCommand) -> Result<(), PresenceError> {
+        if self.connection.is_none() {
+            return Err(PresenceError::NotConnected);
+        }
+        self.with_connection(|connection| connection.lobby(command))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, io, rc::Rc};
+
+    use crate::{
+        domain::presence::{
+            Presence, PresenceError, PresencePublisher, PresenceStatus,
+            connection::Reconnecting,
+            scheduler::{backoff, tests::FakeClock},
+        },
+        outbound::InMemoryPresence,
+    };
+    use pretty_assertions::assert_eq;
+
+    fn presence(state: &str) -> Presence {
+        Presence::builder()
+            .state(state.to_string())
+            .details(String::new())
+            .build()
+    }
+
+    #[test]
+    fn connects_with_backoff_and_applies_the_wanted_presence() {
+        let clock = FakeClock::new();
+        let reachable = Rc::new(Cell::new(false));
+        let attempts = Rc::new(Cell::new(0));
+        let connect = {
+            let (reachable, attempts) = (reachable.clone(), attempts.clone());
+            move || -> Result<InMemoryPresence, PresenceError> {
+                attempts.set(attempts.get() + 1);
+                if reachable.get() {
+                    Ok(InMemoryPresence::new())
+                } else {
+                    Err(anyhow::Error::from(io::Error::from(io::ErrorKind::NotFound)).into())
+                }
+            }
+        };
+        let mut publisher = Reconnecting::with_clock(connect, clock.clone());
+        assert_eq!(publisher.status(), PresenceStatus::Connecting);
+
+        publisher.set(&presence("a")).unwrap();
+        publisher.poll().unwrap();
+        publisher.poll().unwrap();
+        assert_eq!(attempts.get(), 1);
+        clock.advance(backoff(1));
+        publisher.poll().unwrap();
+        assert_eq!(attempts.get(), 2);
+
+        reachable.set(true);
+        clock.advance(backoff(2));
+        publisher.poll().unwrap();
+        let connection = publisher.connection().unwrap();
+        assert_eq!(connection.published(), [Some(presence("a"))]);
+
+        // A failure that loses the connection drops it, and the next one gets the presence again.
+        publisher.connection.as_mut().unwrap().fail_next();
+        assert!(publisher.set(&presence("b")).is_err());
+        assert_eq!(publisher.status(), PresenceStatus::Connecting);
+        clock.advance(backoff(1));
+        publisher.poll().unwrap();
+        assert_eq!(
+            publisher.connection().unwrap().current(),
+            Some(&presence("b"))
+        );
+    }
+}
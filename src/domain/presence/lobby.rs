@@ -0,0 +1,255 @@
+use std::{collections::VecDeque, fmt};
+
+use derive_more::Display;
+
+use crate::domain::{
+    game::GameId,
+    presence::{PresenceEvent, User},
+};
+
+/// Members a lobby has room for unless the game's party says otherwise.
+pub const DEFAULT_LOBBY_CAPACITY: u32 = 4;
+
+/// Longest text message sent to a lobby, in characters.
+pub const MAX_MESSAGE_LEN: usize = 256;
+
+/// Messages a [`LobbyRoom`] keeps, dropping the oldest ones first.
+pub const MESSAGE_HISTORY: usize = 100;
+
+/// Identifier of a Discord lobby.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Display)]
+pub struct LobbyId(i64);
+
+impl LobbyId {
+    pub const fn new(id: i64) -> Self {
+        Self(id)
+    }
+
+    pub const fn get(self) -> i64 {
+        self.0
+    }
+}
+
+/// A lobby the user is in.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Lobby {
+    id: LobbyId,
+    /// The secret others join with, see [`is_lobby_secret`].
+    secret: String,
+    capacity: u32,
+}
+
+impl Lobby {
+    pub const fn new(id: LobbyId, secret: String, capacity: u32) -> Self {
+        Self {
+            id,
+            secret,
+            capacity,
+        }
+    }
+
+    pub const fn id(&self) -> LobbyId {
+        self.id
+    }
+
+    pub fn secret(&self) -> &str {
+        &self.secret
+    }
+
+    pub const fn capacity(&self) -> u32 {
+        self.capacity
+    }
+}
+
+/// Whether `secret` is a lobby's activity secret, `<lobby id>:<lobby secret>`, rather than one
+/// of a game's own join secrets.
+pub fn is_lobby_secret(secret: &str) -> bool {
+    secret
+        .split_once(':')
+        .is_some_and(|(id, rest)| id.parse::<i64>().is_ok() && !rest.is_empty())
+}
+
+/// Payload of a lobby message. Other members may send anything, so only valid UTF-8 counts as
+/// text.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum LobbyMessage {
+    /// Text with any control characters replaced, so it cannot mess up the terminal.
+    Text(String),
+    /// Data that is not text, of the given length in bytes.
+    Binary(usize),
+}
+
+impl LobbyMessage {
+    pub fn decode(data: &[u8]) -> Self {
+        match std::str::from_utf8(data) {
+            Ok(text) => Self::Text(
+                text.chars()
+                    .map(|c| if c.is_control() { '\u{fffd}' } else { c })
+                    .collect(),
+            ),
+            Err(_) => Self::Binary(data.len()),
+        }
+    }
+}
+
+impl fmt::Display for LobbyMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Text(text) => f.write_str(text),
+            Self::Binary(len) => write!(f, "<{len} bytes of binary data>"),
+        }
+    }
+}
+
+/// What [`PresencePublisher::lobby`] is asked to do. The outcome arrives as
+/// [`PresenceEvent`]s.
+///
+/// [`PresencePublisher::lobby`]: crate::domain::presence::PresencePublisher::lobby
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum LobbyCommand {
+    /// Opens a private lobby for `game`, named in its metadata.
+    Create {
+        capacity: u32,
+        game: String,
+    },
+    /// Joins the lobby with the given activity secret.
+    Join(String),
+    Leave(LobbyId),
+    /// Sends a text message to everyone in the lobby.
+    Send(LobbyId, String),
+}
+
+/// Who sent a message in a [`LobbyRoom`].
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Display)]
+pub enum Sender {
+    #[display("you")]
+    Me,
+    #[display("{_0}")]
+    Member(String),
+}
+
+/// The lobby the user is in, with its members and recent messages, kept current from the
+/// [`PresenceEvent`]s about it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LobbyRoom {
+    lobby: Lobby,
+    /// The game the lobby was opened or joined for, if any was active.
+    game: Option<GameId>,
+    members: Vec<User>,
+    messages: VecDeque<(Sender, LobbyMessage)>,
+}
+
+impl LobbyRoom {
+    pub const fn new(lobby: Lobby, game: Option<GameId>) -> Self {
+        Self {
+            lobby,
+            game,
+            members: Vec::new(),
+            messages: VecDeque::new(),
+        }
+    }
+
+    pub const fn lobby(&self) -> &Lobby {
+        &self.lobby
+    }
+
+    pub const fn game(&self) -> Option<GameId> {
+        self.game
+    }
+
+    pub fn members(&self) -> &[User] {
+        &self.members
+    }
+
+    /// The recent messages, oldest first.
+    pub fn messages(&self) -> impl DoubleEndedIterator<Item = &(Sender, LobbyMessage)> {
+        self.messages.iter()
+    }
+
+    /// Records a message the user sent.
+    pub fn sent(&mut self, text: String) {
+        self.push(Sender::Me, LobbyMessage::Text(text));
+    }
+
+    /// Applies `event` if it is about this lobby and tells whether it was.
+    pub fn apply(&mut self, event: &PresenceEvent) -> bool {
+        match event {
+            PresenceEvent::LobbyMemberJoined(id, user) if *id == self.lobby.id => {
+                self.members.retain(|member| member.id() != user.id());
+                self.members.push(user.clone());
+            }
+            PresenceEvent::LobbyMemberLeft(id, user) if *id == self.lobby.id => {
+                self.members.retain(|member| member.id() != *user);
+            }
+            PresenceEvent::LobbyMessage(id, user, message) if *id == self.lobby.id => {
+                let sender = self
+                    .members
+                    .iter()
+                    .find(|member| member.id() == *user)
+                    .map_or_else(|| user.to_string(), |member| member.name().to_string());
+                self.push(Sender::Member(sender), message.clone());
+            }
+            _ => return false,
+        }
+
+        true
+    }
+
+    fn push(&mut self, sender: Sender, message: LobbyMessage) {
+        if self.messages.len() == MESSAGE_HISTORY {
+            self.messages.pop_front();
+        }
+        self.messages.push_back((sender, message));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::domain::presence::{
+        PresenceEvent, User, UserId,
+        lobby::{Lobby, LobbyId, LobbyMessage, LobbyRoom, Sender, is_lobby_secret},
+    };
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn messages_are_decoded_safely() {
+        assert_eq!(
+            LobbyMessage::decode(b"ready?\x1b[2J"),
+            LobbyMessage::Text(String::from("ready?\u{fffd}[2J"))
+        );
+        let binary = LobbyMessage::decode(&[0xff, 0xfe, 0x00]);
+        assert_eq!(binary, LobbyMessage::Binary(3));
+        assert_eq!(binary.to_string(), "<3 bytes of binary data>");
+    }
+
+    #[test]
+    fn room_follows_members_and_messages() {
+        let id = LobbyId::new(1);
+        let mut room = LobbyRoom::new(Lobby::new(id, String::from("1:abc"), 4), None);
+        let luigi = User::new(UserId::new(7), String::from("luigi"));
+
+        assert!(room.apply(&PresenceEvent::LobbyMemberJoined(id, luigi.clone())));
+        assert!(!room.apply(&PresenceEvent::LobbyMemberLeft(LobbyId::new(2), luigi.id())));
+        room.apply(&PresenceEvent::LobbyMessage(
+            id,
+            luigi.id(),
+            LobbyMessage::decode(b"hi"),
+        ));
+        room.sent(String::from("hello"));
+        room.apply(&PresenceEvent::LobbyMemberLeft(id, luigi.id()));
+
+        assert_eq!(room.members(), []);
+        assert_eq!(
+            room.messages().cloned().collect::<Vec<_>>(),
+            [
+                (
+                    Sender::Member(String::from("luigi")),
+                    LobbyMessage::Text(String::from("hi"))
+                ),
+                (Sender::Me, LobbyMessage::Text(String::from("hello"))),
+            ]
+        );
+        assert!(is_lobby_secret("1:abc"));
+        assert!(!is_lobby_secret("room-42"));
+    }
+}
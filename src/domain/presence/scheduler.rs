@@ -0,0 +1,302 @@
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+use crate::domain::presence::{
+    InviteKind, JoinReply, Presence, PresenceError, PresenceEvent, PresencePublisher,
+    PresenceStatus, UserId, lobby::LobbyCommand,
+};
+
+/// Discord accepts this many activity updates per [`RATE_WINDOW`].
+pub const UPDATES_PER_WINDOW: usize = 5;
+pub const RATE_WINDOW: Duration = Duration::from_secs(20);
+
+/// Wait after the first failed update, doubled with every further failure up to [`MAX_BACKOFF`].
+pub const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+pub const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// Source of the current time, so tests can move it forward at will.
+pub trait Clock {
+    fn now(&self) -> Instant;
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// `PresencePublisher` that paces the updates of another one.
+///
+/// Only the latest requested presence is kept, so a burst of updates collapses into one once the
+/// rate limit lets it through. A failed update is retried with exponential backoff on later
+/// polls until it succeeds or a newer update replaces it.
+#[derive(Debug)]
+pub struct UpdateScheduler<P, C = SystemClock> {
+    inner: P,
+    clock: C,
+    /// What the inner publisher shows, `None` if unknown, e.g. after a failure.
+    shown: Option<Option<Presence>>,
+    /// The update waiting to be delivered, holding `None` for a clear.
+    pending: Option<Option<Presence>>,
+    /// When the updates of the current window were attempted, oldest first.
+    attempts: VecDeque<Instant>,
+    failures: u32,
+    retry_at: Option<Instant>,
+}
+
+impl<P: PresencePublisher> UpdateScheduler<P> {
+    pub const fn new(inner: P) -> Self {
+        Self::with_clock(inner, SystemClock)
+    }
+}
+
+impl<P: PresencePublisher, C: Clock> UpdateScheduler<P, C> {
+    pub const fn with_clock(inner: P, clock: C) -> Self {
+        Self {
+            inner,
+            clock,
+            shown: None,
+            pending: None,
+            attempts: VecDeque::new(),
+            failures: 0,
+            retry_at: None,
+        }
+    }
+
+    pub const fn inner(&self) -> &P {
+        &self.inner
+    }
+
+    /// Whether an update is still waiting for the rate limit or a retry.
+    pub const fn is_pending(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    /// Replaces whatever update is pending with `update` and sends it if allowed.
+    fn request(&mut self, update: Option<Presence>) -> Result<(), PresenceError> {
+        if self.shown.as_ref() == Some(&update) {
+            self.pending = None;
+            self.failures = 0;
+            self.retry_at = None;
+            return Ok(());
+        }
+
+        self.pending = Some(update);
+        self.pump()
+    }
+
+    /// Sends the pending update unless the rate limit or the backoff say to wait.
+    fn pump(&mut self) -> Result<(), PresenceError> {
+        let now = self.clock.now();
+        if self.pending.is_none() || self.retry_at.is_some_and(|at| now < at) {
+            return Ok(());
+        }
+
+        while self
+            .attempts
+            .front()
+            .is_some_and(|&at| now.duration_since(at) >= RATE_WINDOW)
+        {
+            self.attempts.pop_front();
+        }
+        if self.attempts.len() >= UPDATES_PER_WINDOW {
+            return Ok(());
+        }
+
+        self.send(now)
+    }
+
+    fn send(&mut self, now: Instant) -> Result<(), PresenceError> {
+        let Some(update) = self.pending.take() else {
+            return Ok(());
+        };
+
+        self.attempts.push_back(now);
+        let result = match &update {
+            Some(presence) => self.inner.set(presence),
+            None => self.inner.clear(),
+        };
+        match result {
+            Ok(()) => {
+                self.shown = Some(update);
+                self.failures = 0;
+                self.retry_at = None;
+                Ok(())
+            }
+            Err(err) => {
+                self.shown = None;
+                self.pending = Some(update);
+                self.failures += 1;
+                self.retry_at = Some(now + backoff(self.failures));
+                Err(err)
+            }
+        }
+    }
+}
+
+impl<P: PresencePublisher, C: Clock> PresencePublisher for UpdateScheduler<P, C> {
+    fn set(&mut self, presence: &Presence) -> Result<(), PresenceError> {
+        self.request(Some(presence.clone()))
+    }
+
+    fn clear(&mut self) -> Result<(), PresenceError> {
+        self.request(None)
+    }
+
+    fn status(&self) -> PresenceStatus {
+        self.inner.status()
+    }
+
+    fn poll(&mut self) -> Result<(), PresenceError> {
+        self.inner.poll()?;
+        self.pump()
+    }
+
+    /// Sends the pending update right away, regardless of the rate limit and backoff.
+    fn flush(&mut self) -> Result<(), PresenceError> {
+        let sent = self.send(self.clock.now());
+        self.inner.flush()?;
+        sent
+    }
+
+    fn take_events(&mut self) -> Vec<PresenceEvent> {
+        self.inner.take_events()
+    }
+
+    /// Replies are not activity updates, so they skip the queue.
+    fn reply_to_join(&mut self, user: UserId, reply: JoinReply) -> Result<(), PresenceError> {
+        self.inner.reply_to_join(user, reply)
+    }
+
+    /// Delivers the pending update first, so the invite is for the latest presence.
+    fn send_invite(&mut self, user: UserId, kind: InviteKind) -> Result<(), PresenceError> {
+        self.send(self.clock.now())?;
+        self.inner.send_invite(user, kind)
+    }
+
+    fn lobby(&mut self, command: LobbyCommand) -> Result<(), PresenceError> {
+        self.inner.lobby(command)
+    }
+}
+
+/// How long to wait before retrying after `failures` failures in a row.
+pub(super) fn backoff(failures: u32) -> Duration {
+    let doublings = failures.saturating_sub(1).min(16);
+    INITIAL_BACKOFF
+        .saturating_mul(1 << doublings)
+        .min(MAX_BACKOFF)
+}
+
+#[cfg(test)]
+pub(super) mod tests {
+    use std::{
+        cell::Cell,
+        rc::Rc,
+        time::{Duration, Instant},
+    };
+
+    use crate::{
+        domain::presence::{
+            Presence, PresencePublisher,
+            scheduler::{Clock, MAX_BACKOFF, RATE_WINDOW, UpdateScheduler, backoff},
+        },
+        outbound::InMemoryPresence,
+    };
+    use pretty_assertions::assert_eq;
+
+    #[derive(Clone, Debug)]
+    pub(in crate::domain::presence) struct FakeClock(Rc<Cell<Instant>>);
+
+    impl FakeClock {
+        pub(in crate::domain::presence) fn new() -> Self {
+            Self(Rc::new(Cell::new(Instant::now())))
+        }
+
+        pub(in crate::domain::presence) fn advance(&self, by: Duration) {
+            self.0.set(self.0.get() + by);
+        }
+    }
+
+    impl Clock for FakeClock {
+        fn now(&self) -> Instant {
+            self.0.get()
+        }
+    }
+
+    fn scheduler() -> (UpdateScheduler<InMemoryPresence, FakeClock>, FakeClock) {
+        let clock = FakeClock::new();
+        (
+            UpdateScheduler::with_clock(InMemoryPresence::new(), clock.clone()),
+            clock,
+        )
+    }
+
+    fn presence(state: &str) -> Presence {
+        Presence::builder()
+            .state(state.to_string())
+            .details(String::new())
+            .build()
+    }
+
+    #[test]
+    fn bursts_collapse_into_the_latest_update() {
+        let (mut scheduler, clock) = scheduler();
+
+        for game in ["a", "b", "c", "d", "e", "f", "g"] {
+            scheduler.set(&presence(game)).unwrap();
+        }
+        assert_eq!(scheduler.inner().published().len(), 5);
+        assert!(scheduler.is_pending());
+
+        // Asking for what is already shown cancels the pending update.
+        scheduler.set(&presence("e")).unwrap();
+        assert!(!scheduler.is_pending());
+        clock.advance(RATE_WINDOW);
+        scheduler.poll().unwrap();
+        assert_eq!(scheduler.inner().published().len(), 5);
+
+        scheduler.set(&presence("f")).unwrap();
+        scheduler.clear().unwrap();
+        assert_eq!(scheduler.inner().published().len(), 7);
+        assert_eq!(scheduler.inner().current(), None);
+    }
+
+    #[test]
+    fn failures_are_retried_with_backoff() {
+        let (mut scheduler, clock) = scheduler();
+        scheduler.inner.fail_next();
+        scheduler.inner.fail_next();
+
+        assert!(scheduler.set(&presence("a")).is_err());
+        scheduler.poll().unwrap();
+        clock.advance(backoff(1));
+        assert!(scheduler.poll().is_err());
+        clock.advance(backoff(1));
+        scheduler.poll().unwrap();
+        assert!(scheduler.is_pending());
+        clock.advance(backoff(2) - backoff(1));
+        scheduler.poll().unwrap();
+
+        assert_eq!(scheduler.inner().current(), Some(&presence("a")));
+        assert!(!scheduler.is_pending());
+        assert_eq!(backoff(10), MAX_BACKOFF);
+    }
+
+    #[test]
+    fn flush_ignores_the_rate_limit() {
+        let (mut scheduler, _clock) = scheduler();
+        for game in ["a", "b", "c", "d", "e", "f"] {
+            scheduler.set(&presence(game)).unwrap();
+        }
+        scheduler.clear().unwrap();
+        assert!(scheduler.is_pending());
+
+        scheduler.flush().unwrap();
+        assert_eq!(scheduler.inner().published().last(), Some(&None));
+    }
+}
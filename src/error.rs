@@ -1,4 +1,4 @@
-use std::{env::VarError, num::ParseIntError};
+use std::{env::VarError, num::ParseIntError, path::PathBuf};
 
 use thiserror::Error;
 
@@ -20,4 +20,15 @@ pub enum Error {
     ParseIntError(#[from] ParseIntError),
     #[error(transparent)]
     Discord(#[from] discord_game_sdk::Error),
+    #[error(transparent)]
+    Database(#[from] rusqlite::Error),
+    #[error(transparent)]
+    InvalidGame(#[from] crate::domain::game::GameValidationError),
+    #[error("{} lists {name} twice, by name or id; remove one of them", path.display())]
+    DuplicateGame {
+        path: PathBuf,
+        name: crate::domain::game::Name,
+    },
+    #[error(transparent)]
+    Other(#[from] anyhow::Error),
 }
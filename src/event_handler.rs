@@ -1,7 +1,74 @@
-use log::info;
+use std::{cell::RefCell, rc::Rc};
 
-#[derive(Debug, Clone, Copy, Default)]
-pub struct EventHandler;
+use discord_game_sdk::{Discord, LobbyID, RelationshipKind, Status, UserID};
+use log::{error, info, warn};
+
+use crate::domain::presence::{
+    Friend, FriendActivity, OnlineStatus, PresenceEvent, User, UserId,
+    lobby::{LobbyId, LobbyMessage},
+};
+
+/// Receives what the Game SDK reports while its callbacks run. Events the app acts on are queued
+/// for the app to take, see [`EventHandler::queue`], the rest is logged.
+///
+/// Clones share the queue, as do the callbacks of requests that report events too.
+#[derive(Debug, Clone, Default)]
+pub struct EventHandler {
+    events: Rc<RefCell<Vec<PresenceEvent>>>,
+}
+
+impl EventHandler {
+    /// The queue the events go to, oldest first, for the app to take them from and for callbacks
+    /// to add theirs.
+    pub fn queue(&self) -> Rc<RefCell<Vec<PresenceEvent>>> {
+        Rc::clone(&self.events)
+    }
+
+    fn push(&self, event: PresenceEvent) {
+        self.events.borrow_mut().push(event);
+    }
+}
+
+/// Looks the lobby member `user_id` up and queues them as joined once found.
+pub(crate) fn look_up_member<E>(
+    discord: &Discord<'_, E>,
+    lobby_id: LobbyID,
+    user_id: UserID,
+    queue: Rc<RefCell<Vec<PresenceEvent>>>,
+) {
+    discord.user(user_id, move |_discord, result| match result {
+        Ok(user) => queue.borrow_mut().push(PresenceEvent::LobbyMemberJoined(
+            LobbyId::new(lobby_id),
+            User::new(UserId::new(user.id()), user.username().to_string()),
+        )),
+        Err(err) => warn!("cannot look up lobby member {user_id}: {err}"),
+    });
+}
+
+fn friend(relationship: &discord_game_sdk::Relationship) -> Friend {
+    let user = relationship.user();
+    let presence = relationship.presence();
+    let status = match presence.status() {
+        Status::Online => OnlineStatus::Online,
+        Status::Idle => OnlineStatus::Idle,
+        Status::DoNotDisturb => OnlineStatus::DoNotDisturb,
+        Status::Offline | Status::Undefined(_) => OnlineStatus::Offline,
+    };
+    let activity = presence.activity();
+    let activity = (!activity.name().is_empty()).then(|| {
+        FriendActivity::new(
+            activity.name().to_string(),
+            activity.state().to_string(),
+            activity.details().to_string(),
+        )
+    });
+
+    Friend::new(
+        User::new(UserId::new(user.id()), user.username().to_string()),
+        status,
+    )
+    .with_activity(activity)
+}
 
 impl discord_game_sdk::EventHandler for EventHandler {
     fn on_user_achievement_update(
@@ -16,6 +83,7 @@ impl discord_game_sdk::EventHandler for EventHandler {
     fn on_activity_join(&mut self, discord: &discord_game_sdk::Discord<'_, Self>, secret: &str) {
         let _ = discord;
         info!("activity join: {secret}");
+        self.push(PresenceEvent::Join(secret.to_string()));
     }
 
     fn on_activity_spectate(
@@ -25,6 +93,7 @@ impl discord_game_sdk::EventHandler for EventHandler {
     ) {
         let _ = discord;
         info!("activity spectate: {secret}");
+        self.push(PresenceEvent::Spectate(secret.to_string()));
     }
 
     fn on_activity_join_request(
@@ -34,6 +103,10 @@ impl discord_game_sdk::EventHandler for EventHandler {
     ) {
         let _ = discord;
         info!("activity join request: {user:#?}");
+        self.push(PresenceEvent::JoinRequest(User::new(
+            UserId::new(user.id()),
+            user.username().to_string(),
+        )));
     }
 
     fn on_activity_invite(
@@ -64,6 +137,7 @@ impl discord_game_sdk::EventHandler for EventHandler {
     ) {
         let _ = discord;
         info!("lobby delete: {lobby_id:#?}; reason: {reason}");
+        self.push(PresenceEvent::LobbyClosed(LobbyId::new(lobby_id)));
     }
 
     fn on_member_connect(
@@ -72,8 +146,8 @@ impl discord_game_sdk::EventHandler for EventHandler {
         lobby_id: discord_game_sdk::LobbyID,
         member_id: discord_game_sdk::UserID,
     ) {
-        let _ = discord;
         info!("member connect: {lobby_id:#?}; {member_id:#?}");
+        look_up_member(discord, lobby_id, member_id, self.queue());
     }
 
     fn on_member_update(
@@ -94,6 +168,10 @@ impl discord_game_sdk::EventHandler for EventHandler {
     ) {
         let _ = discord;
         info!("member disconnect: {lobby_id:#?}; {member_id:#?}");
+        self.push(PresenceEvent::LobbyMemberLeft(
+            LobbyId::new(lobby_id),
+            UserId::new(member_id),
+        ));
     }
 
     fn on_lobby_message(
@@ -103,10 +181,14 @@ impl discord_game_sdk::EventHandler for EventHandler {
         member_id: discord_game_sdk::UserID,
         data: &[u8],
     ) {
-        let vec = Vec::from(data);
-        let string = String::from_utf8(vec).unwrap();
         let _ = discord;
-        info!("lobby message: {lobby_id:#?}; {member_id:#?}; message {string}");
+        let message = LobbyMessage::decode(data);
+        info!("lobby message: {lobby_id:#?}; {member_id:#?}; message {message}");
+        self.push(PresenceEvent::LobbyMessage(
+            LobbyId::new(lobby_id),
+            UserId::new(member_id),
+            message,
+        ));
     }
 
     fn on_speaking(
@@ -128,11 +210,10 @@ impl discord_game_sdk::EventHandler for EventHandler {
         channel_id: discord_game_sdk::NetworkChannelID,
         data: &[u8],
     ) {
-        let vec = Vec::from(data);
-        let string = String::from_utf8(vec).unwrap();
         let _ = discord;
+        let message = LobbyMessage::decode(data);
         info!(
-            "lobby network message: {lobby_id:#?}; {member_id:#?}; {channel_id:#?}; message: {string}"
+            "lobby network message: {lobby_id:#?}; {member_id:#?}; {channel_id:#?}; message: {message}"
         );
     }
 
@@ -143,10 +224,9 @@ impl discord_game_sdk::EventHandler for EventHandler {
         channel_id: discord_game_sdk::NetworkChannelID,
         data: &[u8],
     ) {
-        let vec = Vec::from(data);
-        let string = String::from_utf8(vec).unwrap();
         let _ = discord;
-        info!("network message: {peer_id:#?}; {channel_id:#?}; message: {string}");
+        let message = LobbyMessage::decode(data);
+        info!("network message: {peer_id:#?}; {channel_id:#?}; message: {message}");
     }
 
     fn on_network_route_update(
@@ -164,8 +244,20 @@ impl discord_game_sdk::EventHandler for EventHandler {
     }
 
     fn on_relationships_refresh(&mut self, discord: &discord_game_sdk::Discord<'_, Self>) {
-        let _ = discord;
         info!("relationship refresh");
+        discord
+            .filter_relationships(|relationship| relationship.kind() == RelationshipKind::Friend);
+        let friends = match discord.iter_relationships() {
+            Ok(relationships) => relationships
+                .filter_map(|relationship| relationship.ok())
+                .map(|relationship| friend(&relationship))
+                .collect(),
+            Err(err) => {
+                error!("cannot list the relationships: {err}");
+                return;
+            }
+        };
+        self.push(PresenceEvent::Friends(friends));
     }
 
     fn on_relationship_update(
@@ -175,6 +267,11 @@ impl discord_game_sdk::EventHandler for EventHandler {
     ) {
         let _ = discord;
         info!("relationship update: {relationship:#?}");
+        self.push(if relationship.kind() == RelationshipKind::Friend {
+            PresenceEvent::FriendUpdate(friend(relationship))
+        } else {
+            PresenceEvent::FriendRemoved(UserId::new(relationship.user().id()))
+        });
     }
 
     fn on_entitlement_create(
@@ -0,0 +1,171 @@
+use std::collections::HashMap;
+
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Rect},
+    style::{Style, Stylize},
+    symbols::border,
+    text::{Line, Span},
+    widgets::{Block, Clear, HighlightSpacing, List, ListItem, ListState, StatefulWidget, Widget},
+};
+
+use crate::{
+    domain::{
+        game::Game,
+        presence::{Friend, FriendList, InviteKind, OnlineStatus, UserId},
+    },
+    game::notice::popup_area,
+};
+
+/// How far an invite sent from the picker got.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Delivery {
+    Sending(InviteKind),
+    Sent(InviteKind),
+    Failed(String),
+}
+
+/// Popup listing the friends to invite to `game`, with how their last invite went.
+#[derive(Debug)]
+pub(crate) struct FriendPicker<'a> {
+    pub game: &'a str,
+    pub friends: &'a FriendList,
+    pub deliveries: &'a HashMap<UserId, Delivery>,
+}
+
+impl StatefulWidget for FriendPicker<'_> {
+    type State = ListState;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        let area = popup_area(area, Constraint::Percentage(60));
+        let block = Block::bordered()
+            .title(Line::from(format!(" Invite to {} ", self.game).bold()).centered())
+            .title_bottom(
+                Line::from(vec![
+                    " Join ".into(),
+                    "<J>".blue().bold(),
+                    " Spectate ".into(),
+                    "<S>".blue().bold(),
+                    " Close ".into(),
+                    "<ESC> ".blue().bold(),
+                ])
+                .centered(),
+            )
+            .border_set(border::THICK);
+        Clear.render(area, buf);
+
+        if self.friends.is_empty() {
+            Line::from("No friends known yet".dark_gray()).render(block.inner(area), buf);
+            block.render(area, buf);
+            return;
+        }
+
+        let items: Vec<ListItem> = self
+            .friends
+            .iter()
+            .map(|friend| {
+                let mut spans = name_and_status(friend);
+                match self.deliveries.get(&friend.user().id()) {
+                    Some(Delivery::Sending(kind)) => {
+                        spans.push(format!(" sending {kind} invite").dark_gray().italic());
+                    }
+                    Some(Delivery::Sent(kind)) => {
+                        spans.push(format!(" {kind} invite sent").green().italic());
+                    }
+                    Some(Delivery::Failed(reason)) => {
+                        spans.push(format!(" invite failed: {reason}").red().italic());
+                    }
+                    None => {}
+                }
+                ListItem::new(Line::from(spans))
+            })
+            .collect();
+
+        let list = List::new(items)
+            .block(block)
+            .highlight_symbol(">")
+            .highlight_spacing(HighlightSpacing::Always)
+            .highlight_style(Style::new().light_blue().italic());
+
+        StatefulWidget::render(list, area, buf, state);
+    }
+}
+
+/// Panel listing the friends and what they are playing. Friends playing a game from `library`
+/// are highlighted.
+#[derive(Debug)]
+pub(crate) struct FriendsPanel<'a> {
+    pub friends: &'a FriendList,
+    pub library: &'a [Game],
+}
+
+impl StatefulWidget for FriendsPanel<'_> {
+    type State = ListState;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        let block = Block::bordered()
+            .title(Line::from(" Friends ".bold()).centered())
+            .title_bottom(
+                Line::from(vec![
+                    " Jump to game ".into(),
+                    "<ENTER>".blue().bold(),
+                    " Close ".into(),
+                    "<ESC> ".blue().bold(),
+                ])
+                .centered(),
+            )
+            .border_set(border::THICK);
+
+        if self.friends.is_empty() {
+            Line::from("No friends known yet".dark_gray()).render(block.inner(area), buf);
+            block.render(area, buf);
+            return;
+        }
+
+        let items: Vec<ListItem> = self
+            .friends
+            .iter()
+            .map(|friend| {
+                let playing = match friend.activity() {
+                    Some(activity) => {
+                        let mut text = format!("  {}", activity.name());
+                        if !activity.state().is_empty() {
+                            text.push_str(&format!(": {}", activity.state()));
+                        }
+                        if self.library.iter().any(|game| activity.is_playing(game)) {
+                            text.green().bold()
+                        } else {
+                            text.into()
+                        }
+                    }
+                    None => "  not playing".dark_gray(),
+                };
+                ListItem::new(vec![
+                    Line::from(name_and_status(friend)),
+                    Line::from(playing),
+                ])
+            })
+            .collect();
+
+        let list = List::new(items)
+            .block(block)
+            .highlight_symbol(">")
+            .highlight_spacing(HighlightSpacing::Always)
+            .highlight_style(Style::new().light_blue().italic());
+
+        StatefulWidget::render(list, area, buf, state);
+    }
+}
+
+fn name_and_status(friend: &Friend) -> Vec<Span<'_>> {
+    let status = format!(" {}", friend.status());
+    vec![
+        Span::from(friend.user().name()).bold(),
+        match friend.status() {
+            OnlineStatus::Online => status.green(),
+            OnlineStatus::Idle => status.yellow(),
+            OnlineStatus::DoNotDisturb => status.red(),
+            OnlineStatus::Offline => status.dark_gray(),
+        },
+    ]
+}
@@ -2,41 +2,52 @@ use ratatui::{
     style::{Style, Stylize},
     symbols::border,
     text::Line,
-    widgets::{Block, HighlightSpacing, List, ListState, StatefulWidget},
+    widgets::{Block, HighlightSpacing, List, ListItem, ListState, StatefulWidget},
 };
 
-use crate::game::Game;
+use crate::domain::{
+    game::{Game, GameFilter, History, Tag},
+    presence::PresenceStatus,
+};
 
 #[derive(Debug, Default)]
 pub(crate) struct GameList {
     items: Vec<Game>,
+    filter: GameFilter,
+    undo: Option<String>,
+    redo: Option<String>,
+    status: Option<PresenceStatus>,
 }
 
 impl GameList {
-    pub fn push(&mut self, value: Game) {
-        self.items.push(value)
+    /// Shows `filter` in the title. The items are expected to be filtered already.
+    pub fn with_filter(self, filter: GameFilter) -> Self {
+        Self { filter, ..self }
     }
 
-    pub fn get_mut<I>(&mut self, index: I) -> Option<&mut I::Output>
-    where
-        I: std::slice::SliceIndex<[Game]>,
-    {
-        self.items.get_mut(index)
+    /// Shows what undo and redo would do next.
+    pub fn with_history(self, history: &History) -> Self {
+        Self {
+            undo: history.next_undo().map(ToString::to_string),
+            redo: history.next_redo().map(ToString::to_string),
+            ..self
+        }
     }
 
-    pub fn remove(&mut self, index: usize) -> Game {
-        self.items.remove(index)
+    /// Shows whether the presence reaches Discord.
+    pub fn with_status(self, status: PresenceStatus) -> Self {
+        Self {
+            status: Some(status),
+            ..self
+        }
     }
 
-    pub fn get<I>(&self, index: I) -> Option<&I::Output>
-    where
-        I: std::slice::SliceIndex<[Game]>,
-    {
+    pub fn get(&self, index: usize) -> Option<&Game> {
         self.items.get(index)
     }
 
-    pub fn clone_inner(&self) -> Vec<Game> {
-        self.items.clone()
+    pub fn remove(&mut self, index: usize) -> Game {
+        self.items.remove(index)
     }
 }
 
@@ -49,7 +60,11 @@ impl StatefulWidget for &GameList {
         buf: &mut ratatui::prelude::Buffer,
         state: &mut Self::State,
     ) {
-        let title = Line::from(" Games ".bold());
+        let title = if self.filter.is_empty() {
+            Line::from(" Games ".bold())
+        } else {
+            Line::from(format!(" Games: {} ", self.filter).bold())
+        };
         let instructions = Line::from(vec![
             " Activate current selection ".into(),
             "<ENTER>".blue().bold(),
@@ -57,17 +72,60 @@ impl StatefulWidget for &GameList {
             "<Arrowkeys>".blue().bold(),
             " Edit ".into(),
             "<E>".blue().bold(),
+            " Clear presence ".into(),
+            "<C>".blue().bold(),
+            " Invite friends ".into(),
+            "<I>".blue().bold(),
+            " Friends ".into(),
+            "<F>".blue().bold(),
+            " Lobby ".into(),
+            "<L>".blue().bold(),
+            " Party size ".into(),
+            "<+/->".blue().bold(),
+            " Filter ".into(),
+            "</>".blue().bold(),
             " Quit ".into(),
             "<Q>".blue().bold(),
             " Unselect ".into(),
             "<U> ".blue().bold(),
         ]);
+        let mut history = Vec::new();
+        if let Some(undo) = &self.undo {
+            history.extend([format!(" Undo {undo} ").into(), "<Z>".blue().bold()]);
+        }
+        if let Some(redo) = &self.redo {
+            history.extend([format!(" Redo {redo} ").into(), "<Y>".blue().bold()]);
+        }
+        history.push(" ".into());
+        let status = self.status.map(|status| {
+            let label = format!(" Discord {status} ");
+            Line::from(match status {
+                PresenceStatus::Connected => label.green(),
+                PresenceStatus::Connecting => label.yellow(),
+                PresenceStatus::Offline => label.dark_gray(),
+            })
+        });
         let block = Block::bordered()
+            .title(status.unwrap_or_default().left_aligned())
             .title(title.centered())
+            .title(Line::from(history).right_aligned())
             .title_bottom(instructions.centered())
             .border_set(border::THICK);
 
-        let items: Vec<Game> = self.items.to_vec();
+        let items: Vec<ListItem> = self
+            .items
+            .iter()
+            .map(|game| {
+                let mut details = game.platform().to_string();
+                if let Some(party) = game.party() {
+                    details.push_str(&format!(" ({} of {})", party.size(), party.max()));
+                }
+                for tag in game.tags().iter().map(Tag::as_str) {
+                    details.push_str(&format!(" #{tag}"));
+                }
+                format!("{}\n{details}", game.name()).into()
+            })
+            .collect();
 
         let list = List::new(items)
             .block(block)
@@ -83,6 +141,12 @@ impl FromIterator<Game> for GameList {
     fn from_iter<T: IntoIterator<Item = Game>>(iter: T) -> Self {
         let items = iter.into_iter().collect();
 
-        Self { items }
+        Self {
+            items,
+            filter: GameFilter::default(),
+            undo: None,
+            redo: None,
+            status: None,
+        }
     }
 }
@@ -0,0 +1,156 @@
+use ratatui::{
+    Frame,
+    crossterm::event::Event,
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Stylize},
+    symbols::border,
+    text::Line,
+    widgets::{Block, Paragraph, Wrap},
+};
+use tui_input::{Input as TuiInput, backend::crossterm::EventHandler};
+
+use crate::domain::presence::{
+    User,
+    lobby::{LobbyMessage, LobbyRoom, MAX_MESSAGE_LEN, Sender},
+};
+
+/// Pane with the lobby the user is in, or a way to get into one, and a line to type into: a
+/// message while in a lobby, a lobby secret to join one otherwise.
+#[derive(Debug, Default)]
+pub(crate) struct LobbyPane {
+    input: TuiInput,
+}
+
+impl LobbyPane {
+    pub fn value(&self) -> &str {
+        self.input.value().trim()
+    }
+
+    pub fn reset(&mut self) {
+        self.input.reset();
+    }
+
+    /// Renders the pane for `room`, or for getting into a lobby for the active `game`.
+    pub fn render(&self, area: Rect, frame: &mut Frame, room: Option<&LobbyRoom>, game: &str) {
+        let instructions = if room.is_some() {
+            vec![
+                " Send ".into(),
+                "<ENTER>".blue().bold(),
+                " Leave ".into(),
+                "<CTRL+L>".blue().bold(),
+            ]
+        } else {
+            vec![
+                " Join ".into(),
+                "<ENTER>".blue().bold(),
+                " Open ".into(),
+                "<CTRL+N>".blue().bold(),
+            ]
+        };
+        let block = Block::bordered()
+            .title(Line::from(" Lobby ".bold()).centered())
+            .title_bottom(
+                Line::from([instructions, vec![" Close ".into(), "<ESC> ".blue().bold()]].concat())
+                    .centered(),
+            )
+            .border_set(border::THICK);
+        let [content_area, input_area] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(3)]).areas(block.inner(area));
+        frame.render_widget(block, area);
+
+        let (lines, title) = match room {
+            Some(room) => (room_lines(room, content_area.height), "Message"),
+            None if game.is_empty() => (
+                vec![
+                    Line::from("Not in a lobby.".dark_gray()),
+                    Line::from("Activate a game to open a lobby for it, or paste a lobby secret.")
+                        .dark_gray(),
+                ],
+                "Lobby secret",
+            ),
+            None => (
+                vec![
+                    Line::from("Not in a lobby.".dark_gray()),
+                    Line::from(format!("Open one for {game}, or paste a lobby secret."))
+                        .dark_gray(),
+                ],
+                "Lobby secret",
+            ),
+        };
+        frame.render_widget(
+            Paragraph::new(lines).wrap(Wrap { trim: false }),
+            content_area,
+        );
+        self.render_input(input_area, frame, room.is_some(), title);
+    }
+
+    fn render_input(&self, area: Rect, frame: &mut Frame, messaging: bool, title: &str) {
+        let len = self.value().chars().count();
+        let (style, title) = if messaging && len > MAX_MESSAGE_LEN {
+            (
+                Color::Red,
+                format!("{title}: too long ({len} of {MAX_MESSAGE_LEN})"),
+            )
+        } else {
+            (Color::Yellow, title.to_string())
+        };
+
+        // keep 2 for borders and 1 for cursor
+        let width = area.width.max(3) - 3;
+        let scroll = self.input.visual_scroll(width as usize);
+        let input = Paragraph::new(self.input.value())
+            .style(style)
+            .scroll((0, scroll as u16))
+            .block(Block::bordered().title(title));
+        frame.render_widget(input, area);
+
+        let x = self.input.visual_cursor().max(scroll) - scroll + 1;
+        frame.set_cursor_position((area.x + x as u16, area.y + 1))
+    }
+}
+
+impl EventHandler for LobbyPane {
+    fn handle_event(&mut self, evt: &Event) -> Option<tui_input::StateChanged> {
+        self.input.handle_event(evt)
+    }
+}
+
+/// The members, then as many of the latest messages as fit in `height` lines.
+fn room_lines(room: &LobbyRoom, height: u16) -> Vec<Line<'_>> {
+    let lobby = room.lobby();
+    let members = room
+        .members()
+        .iter()
+        .map(User::name)
+        .collect::<Vec<_>>()
+        .join(", ");
+    let mut lines = vec![
+        Line::from(vec![
+            format!("{} of {} ", room.members().len(), lobby.capacity()).bold(),
+            members.into(),
+        ]),
+        Line::from(format!("Secret {}", lobby.secret()).dark_gray()),
+        Line::default(),
+    ];
+
+    let room_for = usize::from(height).saturating_sub(lines.len());
+    let mut messages: Vec<Line> = room
+        .messages()
+        .rev()
+        .take(room_for)
+        .map(|(sender, message)| {
+            let sender = match sender {
+                Sender::Me => sender.to_string().dark_gray(),
+                Sender::Member(_) => sender.to_string().bold(),
+            };
+            let message = match message {
+                LobbyMessage::Text(_) => message.to_string().into(),
+                LobbyMessage::Binary(_) => message.to_string().dark_gray().italic(),
+            };
+            Line::from(vec![sender, ": ".into(), message])
+        })
+        .collect();
+    messages.reverse();
+    lines.extend(messages);
+    lines
+}
@@ -0,0 +1,138 @@
+use chrono::{DateTime, Local, TimeDelta, Utc};
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Flex, Layout, Rect},
+    style::Stylize,
+    symbols::border,
+    text::Line,
+    widgets::{Block, Clear, Paragraph, Widget, Wrap},
+};
+
+use crate::domain::presence::User;
+
+/// How long a notice stays in the status bar.
+pub const NOTICE_DURATION: TimeDelta = TimeDelta::seconds(5);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub(crate) enum Level {
+    Info,
+    Error,
+}
+
+/// Short message about something that just happened, shown in the status bar for a while.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Notice {
+    level: Level,
+    message: String,
+    at: DateTime<Utc>,
+}
+
+impl Notice {
+    pub fn info(message: impl Into<String>) -> Self {
+        Self {
+            level: Level::Info,
+            message: message.into(),
+            at: Utc::now(),
+        }
+    }
+
+    pub fn error(message: impl Into<String>) -> Self {
+        Self {
+            level: Level::Error,
+            message: message.into(),
+            at: Utc::now(),
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Whether the notice has been shown long enough at `now`.
+    pub fn has_expired(&self, now: DateTime<Utc>) -> bool {
+        now - self.at >= NOTICE_DURATION
+    }
+}
+
+/// The notice as a single line, meant for the status bar.
+impl Widget for &Notice {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let line = match self.level {
+            Level::Info => Line::from(vec![" ".into(), self.message.as_str().green()]),
+            Level::Error => Line::from(vec![
+                " ".into(),
+                self.message.as_str().red(),
+                " Details ".into(),
+                "<!>".blue().bold(),
+            ]),
+        };
+
+        line.render(area, buf);
+    }
+}
+
+/// Popup with the full text of the last error and when it happened.
+#[derive(Debug)]
+pub(crate) struct ErrorDetails<'a>(pub &'a Notice);
+
+impl Widget for ErrorDetails<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let area = popup_area(area, Constraint::Percentage(40));
+        let block = Block::bordered()
+            .title(Line::from(" Last error ".bold()).centered())
+            .title_bottom(Line::from(vec![" Close ".into(), "<ESC> ".blue().bold()]).centered())
+            .border_set(border::THICK);
+        let at = self.0.at.with_timezone(&Local).format("%H:%M:%S");
+
+        Clear.render(area, buf);
+        Paragraph::new(vec![
+            Line::from(format!("at {at}").dark_gray()),
+            Line::from(self.0.message()),
+        ])
+        .block(block)
+        .wrap(Wrap { trim: false })
+        .render(area, buf);
+    }
+}
+
+/// Popup asking whether to let a Discord user join the active game.
+#[derive(Debug)]
+pub(crate) struct JoinPrompt<'a>(pub &'a User);
+
+impl Widget for JoinPrompt<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let area = popup_area(area, Constraint::Length(4));
+        let block = Block::bordered()
+            .title(Line::from(" Join request ".bold()).centered())
+            .title_bottom(
+                Line::from(vec![
+                    " Accept ".into(),
+                    "<Y>".blue().bold(),
+                    " Decline ".into(),
+                    "<N>".blue().bold(),
+                    " Ignore ".into(),
+                    "<ESC> ".blue().bold(),
+                ])
+                .centered(),
+            )
+            .border_set(border::THICK);
+
+        Clear.render(area, buf);
+        Paragraph::new(vec![
+            Line::from(vec![self.0.name().bold(), " asks to join your game".into()]),
+            Line::from(format!("Discord user {}", self.0.id()).dark_gray()),
+        ])
+        .block(block)
+        .wrap(Wrap { trim: false })
+        .render(area, buf);
+    }
+}
+
+/// Centered area for a popup `height` high and most of `area` wide.
+pub(crate) fn popup_area(area: Rect, height: Constraint) -> Rect {
+    let [area] = Layout::horizontal([Constraint::Percentage(60)])
+        .flex(Flex::Center)
+        .areas(area);
+    let [area] = Layout::vertical([height]).flex(Flex::Center).areas(area);
+    area
+}
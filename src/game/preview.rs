@@ -0,0 +1,179 @@
+use chrono::Local;
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::Stylize,
+    symbols::border,
+    text::{Line, Span},
+    widgets::{Block, Paragraph, Widget, Wrap},
+};
+
+use crate::domain::{
+    game::{Image, PRESENCE_TEXT_LIMIT, Tooltip},
+    presence::Presence,
+};
+
+/// What Discord would show for the selected game, field by field. Fields that are left empty or
+/// that Discord cuts off are highlighted.
+#[derive(Debug, Default)]
+pub(crate) struct PresencePreview {
+    presence: Option<Presence>,
+    state_cut: bool,
+    details_cut: bool,
+}
+
+impl PresencePreview {
+    pub const fn new(presence: Presence) -> Self {
+        Self {
+            presence: Some(presence),
+            state_cut: false,
+            details_cut: false,
+        }
+    }
+
+    /// Marks the state and the details as cut off to fit.
+    pub fn with_cut(self, state: bool, details: bool) -> Self {
+        Self {
+            state_cut: state,
+            details_cut: details,
+            ..self
+        }
+    }
+}
+
+impl Widget for &PresencePreview {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let block = Block::bordered()
+            .title(Line::from(" Preview ".bold()).centered())
+            .border_set(border::THICK);
+
+        let Some(presence) = &self.presence else {
+            Paragraph::new("Select a game to see its presence".dark_gray())
+                .block(block)
+                .render(area, buf);
+            return;
+        };
+
+        let timer = match presence.start() {
+            Some(start) => vec![
+                format!(
+                    "elapsed since {}",
+                    start.with_timezone(&Local).format("%H:%M")
+                )
+                .into(),
+            ],
+            None => vec!["none".dark_gray()],
+        };
+        let party = match presence.party() {
+            Some(party) => vec![format!("{} of {}", party.size(), party.max()).into()],
+            None => vec!["none".dark_gray()],
+        };
+        let lines = vec![
+            field("Details", text(presence.details(), self.details_cut)),
+            field("State", text(presence.state(), self.state_cut)),
+            field(
+                "Large",
+                image(presence.large_image(), presence.large_tooltip()),
+            ),
+            field(
+                "Small",
+                image(presence.small_image(), presence.small_tooltip()),
+            ),
+            field("Timer", timer),
+            field("Party", party),
+        ];
+
+        Paragraph::new(lines)
+            .block(block)
+            .wrap(Wrap { trim: false })
+            .render(area, buf);
+    }
+}
+
+fn field<'a>(label: &'a str, value: Vec<Span<'a>>) -> Line<'a> {
+    let mut spans = vec![format!("{label:<8}").bold()];
+    spans.extend(value);
+    Line::from(spans)
+}
+
+fn text(value: &str, cut: bool) -> Vec<Span<'_>> {
+    if value.is_empty() {
+        return vec!["empty".yellow().italic()];
+    }
+
+    let mut spans = vec![Span::from(value)];
+    if cut {
+        spans.push(" cut off".red().italic());
+    }
+    spans
+}
+
+fn image<'a>(key: Option<&'a Image>, tooltip: Option<&'a Tooltip>) -> Vec<Span<'a>> {
+    let mut spans = vec![match key {
+        Some(key) => Span::from(key.as_str()),
+        None => "no image".yellow().italic(),
+    }];
+    match tooltip {
+        Some(tooltip) => {
+            spans.push(format!(" “{}”", tooltip.as_str()).into());
+            if tooltip.as_str().chars().count() > PRESENCE_TEXT_LIMIT {
+                spans.push(" cut off".red().italic());
+            }
+        }
+        None if key.is_some() => spans.push(" no tooltip".yellow().italic()),
+        None => {}
+    }
+    spans
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::{buffer::Buffer, layout::Rect, widgets::Widget};
+
+    use crate::{
+        domain::{
+            game::{Image, Party},
+            presence::Presence,
+        },
+        game::preview::PresencePreview,
+    };
+    use pretty_assertions::assert_eq;
+
+    fn rendered(preview: &PresencePreview) -> Vec<String> {
+        let area = Rect::new(0, 0, 40, 8);
+        let mut buf = Buffer::empty(area);
+        preview.render(area, &mut buf);
+
+        (1..area.height - 1)
+            .map(|y| {
+                (1..area.width - 1)
+                    .map(|x| buf[(x, y)].symbol())
+                    .collect::<String>()
+                    .trim_end()
+                    .to_string()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn highlights_empty_and_cut_off_fields() {
+        let presence = Presence::builder()
+            .state(String::from("Zelda"))
+            .details(String::new())
+            .large_image(Image::from(String::from("zelda")))
+            .party(Party::new("couch", 2, 4).unwrap())
+            .build();
+
+        assert_eq!(
+            rendered(&PresencePreview::new(presence).with_cut(true, false)),
+            [
+                "Details empty",
+                "State   Zelda cut off",
+                "Large   zelda no tooltip",
+                "Small   no image",
+                "Timer   none",
+                "Party   2 of 4",
+            ]
+        );
+    }
+}
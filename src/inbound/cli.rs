@@ -0,0 +1,254 @@
+use std::collections::BTreeSet;
+
+use clap::Subcommand;
+
+use crate::{
+    Error,
+    domain::{
+        asset::AssetManifest,
+        game::{
+            Collection, GameCreateRequest, GameFilter, GameId, GameService, GameUpdateRequest,
+            GameValidationError, Image, Platform, Tag, Tooltip,
+        },
+        platform::{PlatformDefinition, PlatformService, PlatformUpdateRequest},
+    },
+};
+
+/// Commands that run instead of the TUI.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Subcommand)]
+pub enum Command {
+    /// List games or change their tags and collections
+    #[command(subcommand)]
+    Games(GameCommand),
+    /// Manage the platform registry
+    #[command(subcommand)]
+    Platform(PlatformCommand),
+    /// Report image keys that would not show up on Discord
+    Lint,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Subcommand)]
+pub enum GameCommand {
+    /// List the games carrying every given tag and collection
+    List {
+        /// Only games with this tag, may be repeated
+        #[arg(long = "tag", value_name = "TAG")]
+        tags: Vec<String>,
+        /// Only games in this collection, may be repeated
+        #[arg(long = "collection", value_name = "COLLECTION")]
+        collections: Vec<String>,
+    },
+    /// Add or remove tags and collections of a game
+    Label {
+        id: GameId,
+        /// Tag to add, may be repeated
+        #[arg(long = "tag", value_name = "TAG")]
+        tags: Vec<String>,
+        /// Tag to remove, may be repeated
+        #[arg(long = "untag", value_name = "TAG")]
+        untags: Vec<String>,
+        /// Collection to add the game to, may be repeated
+        #[arg(long = "collection", value_name = "COLLECTION")]
+        collections: Vec<String>,
+        /// Collection to take the game out of, may be repeated
+        #[arg(long = "uncollect", value_name = "COLLECTION")]
+        uncollects: Vec<String>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Subcommand)]
+pub enum PlatformCommand {
+    /// List the registered platforms
+    List,
+    /// Register a platform
+    Add {
+        name: String,
+        /// Other spelling of the platform, may be repeated
+        #[arg(long = "alias", value_name = "ALIAS")]
+        aliases: Vec<String>,
+        /// Small image shown for games on this platform
+        #[arg(long, value_name = "KEY")]
+        small_image: Option<String>,
+        /// Tooltip of the small image
+        #[arg(long, value_name = "TEXT")]
+        small_tooltip: Option<String>,
+    },
+    /// Rename a platform and every game that uses it
+    Rename { from: String, to: String },
+    /// Remove a platform from the registry, leaving its games untouched
+    Remove { name: String },
+}
+
+fn platform(raw: &str) -> Result<Platform, GameValidationError> {
+    Ok(Platform::new(raw)?)
+}
+
+fn tags(raw: &[String]) -> Result<BTreeSet<Tag>, GameValidationError> {
+    Ok(raw
+        .iter()
+        .map(|tag| Tag::new(tag))
+        .collect::<Result<_, _>>()?)
+}
+
+fn collections(raw: &[String]) -> Result<BTreeSet<Collection>, GameValidationError> {
+    Ok(raw
+        .iter()
+        .map(|collection| Collection::new(collection))
+        .collect::<Result<_, _>>()?)
+}
+
+impl Command {
+    pub fn run(
+        &self,
+        service: &(impl GameService + PlatformService),
+        assets: &AssetManifest,
+    ) -> Result<(), Error> {
+        match self {
+            Self::Games(command) => command.run(service),
+            Self::Platform(command) => command.run(service),
+            Self::Lint => lint(service, assets),
+        }
+    }
+}
+
+/// Checks the keys every game ends up sending, platform defaults included.
+fn lint(
+    service: &(impl GameService + PlatformService),
+    assets: &AssetManifest,
+) -> Result<(), Error> {
+    let games = service
+        .list_games()
+        .map_err(anyhow::Error::from)?
+        .iter()
+        .map(|game| service.resolve_game(game))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(anyhow::Error::from)?;
+
+    let lints = assets.lint(&games);
+    for lint in &lints {
+        println!("{lint}");
+    }
+
+    if lints.is_empty() {
+        Ok(())
+    } else {
+        Err(anyhow::anyhow!("invalid image keys: {}", lints.len()).into())
+    }
+}
+
+impl GameCommand {
+    pub fn run(&self, service: &impl GameService) -> Result<(), Error> {
+        match self {
+            Self::List {
+                tags: tag_names,
+                collections: collection_names,
+            } => {
+                let filter = GameFilter::new(tags(tag_names)?, collections(collection_names)?);
+                for game in service.list_games().map_err(anyhow::Error::from)? {
+                    if !filter.matches(&game) {
+                        continue;
+                    }
+                    let labels: Vec<String> = game
+                        .tags()
+                        .iter()
+                        .map(|tag| format!("#{tag}"))
+                        .chain(game.collections().iter().map(|c| format!("@{c}")))
+                        .collect();
+                    println!(
+                        "{}\t{}\t{}\t{}",
+                        game.id(),
+                        game.name(),
+                        game.platform(),
+                        labels.join(" ")
+                    );
+                }
+            }
+            Self::Label {
+                id,
+                tags: added_tags,
+                untags,
+                collections: added_collections,
+                uncollects,
+            } => {
+                let game = service.get_game(*id).map_err(anyhow::Error::from)?;
+                let untags = tags(untags)?;
+                let uncollects = collections(uncollects)?;
+                let new_tags = game
+                    .tags()
+                    .iter()
+                    .cloned()
+                    .chain(tags(added_tags)?)
+                    .filter(|tag| !untags.contains(tag))
+                    .collect();
+                let new_collections = game
+                    .collections()
+                    .iter()
+                    .cloned()
+                    .chain(collections(added_collections)?)
+                    .filter(|collection| !uncollects.contains(collection))
+                    .collect();
+                let req = GameUpdateRequest::builder()
+                    .id(*id)
+                    .game(GameCreateRequest::from(&game).with_labels(new_tags, new_collections))
+                    .build();
+                service.update_game(&req).map_err(anyhow::Error::from)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl PlatformCommand {
+    pub fn run(&self, service: &impl PlatformService) -> Result<(), Error> {
+        match self {
+            Self::List => {
+                for platform in service.list_platforms().map_err(anyhow::Error::from)? {
+                    let aliases: Vec<&str> =
+                        platform.aliases().iter().map(Platform::as_str).collect();
+                    println!("{}\t{}", platform.name(), aliases.join(", "));
+                }
+            }
+            Self::Add {
+                name,
+                aliases,
+                small_image,
+                small_tooltip,
+            } => {
+                let definition = PlatformDefinition::builder()
+                    .name(platform(name)?)
+                    .aliases(
+                        aliases
+                            .iter()
+                            .map(|alias| platform(alias))
+                            .collect::<Result<_, _>>()?,
+                    )
+                    .maybe_small_image(small_image.clone().map(Image::from))
+                    .maybe_small_tooltip(small_tooltip.clone().map(Tooltip::from))
+                    .build();
+                service
+                    .create_platform(&definition)
+                    .map_err(anyhow::Error::from)?;
+            }
+            Self::Rename { from, to } => {
+                let from = platform(from)?;
+                let existing = service
+                    .find_platform(&from)
+                    .map_err(anyhow::Error::from)?
+                    .ok_or_else(|| anyhow::anyhow!("platform {from} does not exist"))?;
+                let req = PlatformUpdateRequest::builder()
+                    .name(existing.name().clone())
+                    .platform(existing.renamed(platform(to)?))
+                    .build();
+                service.update_platform(&req).map_err(anyhow::Error::from)?;
+            }
+            Self::Remove { name } => {
+                service
+                    .delete_platform(&platform(name)?)
+                    .map_err(anyhow::Error::from)?;
+            }
+        }
+
+        Ok(())
+    }
+}
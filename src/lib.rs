@@ -1,9 +1,25 @@
 #![warn(clippy::missing_const_for_fn)]
-#![feature(if_let_guard)]
 
-use dotenv::dotenv;
+use dotenv::{dotenv, var};
 
-use crate::app::App;
+use log::{info, warn};
+
+use crate::{
+    app::App,
+    domain::{
+        Repository,
+        game::{GameId, Service, Template},
+        presence::{PresencePublisher, connection::Reconnecting, scheduler::UpdateScheduler},
+    },
+    inbound::Command,
+    outbound::{
+        DiscordPresence, InMemoryPresence, SqliteGameRepository, TomlGameRepository,
+        load_asset_manifest,
+    },
+};
+
+#[cfg(unix)]
+use crate::outbound::DiscordIpcPresence;
 
 pub use crate::error::Error;
 
@@ -18,13 +34,106 @@ pub mod domain;
 pub mod inbound;
 pub mod outbound;
 
-#[derive(Debug, Parser, Hash, PartialEq, Eq, Clone, Copy)]
+#[derive(Debug, Parser, Hash, PartialEq, Eq, Clone)]
 struct Config {
+    #[command(subcommand)]
+    command: Option<Command>,
+    /// Show the time since the game was activated, unless the game says otherwise
     #[arg(short, long)]
     timestamp: bool,
+    /// Where the game library is stored
+    #[arg(long, value_enum, default_value_t)]
+    storage: Storage,
+    /// How the presence is published
+    #[arg(long, value_enum, default_value_t)]
+    presence: PresenceBackend,
+    /// Id of the game to show as the current activity on startup
+    #[arg(short, long, value_name = "ID")]
+    game: Option<GameId>,
+    /// State of games without their own, with placeholders such as {name}, {platform}, {tag},
+    /// {tags}, {elapsed}, {playtime} or a custom field
+    #[arg(long, value_name = "TEMPLATE", default_value = "{name}")]
+    state_template: Template,
+    /// Details of games without their own, with the same placeholders as --state-template
+    #[arg(long, value_name = "TEMPLATE", default_value = "{platform}")]
+    details_template: Template,
+    /// Shell command run when someone we let in joins through Discord, or when we join or
+    /// spectate someone else. It gets CONSOLE_PLAYER_EVENT (accept, join or spectate),
+    /// CONSOLE_PLAYER_SECRET and, for accept, CONSOLE_PLAYER_USER, CONSOLE_PLAYER_USER_ID and
+    /// CONSOLE_PLAYER_GAME
+    #[arg(long, value_name = "COMMAND")]
+    join_hook: Option<String>,
+}
+
+#[derive(Debug, Default, ValueEnum, Hash, PartialEq, Eq, Clone, Copy)]
+enum Storage {
+    /// SQLite database at `CONSOLE_PLAYER_DATABASE_URL`
+    #[default]
+    Sqlite,
+    /// `~/.config/consoleplayergames.toml`
+    Toml,
 }
 
-use clap::Parser;
+#[derive(Debug, Default, ValueEnum, Hash, PartialEq, Eq, Clone, Copy)]
+enum PresenceBackend {
+    /// Discord's IPC socket, also served by arRPC
+    #[default]
+    Ipc,
+    /// The native Discord Game SDK library
+    Sdk,
+    /// Keep the presence to ourselves
+    Offline,
+}
+
+use clap::{Parser, ValueEnum};
+
+fn open_repository(
+    storage: Storage,
+    config: &config::Config,
+) -> Result<Box<dyn Repository>, Error> {
+    match storage {
+        Storage::Toml => Ok(Box::new(TomlGameRepository::new()?)),
+        Storage::Sqlite => {
+            let database = SqliteGameRepository::open(config.database_url())?;
+
+            let toml_path = TomlGameRepository::default_path()?;
+            if database.is_new() && toml_path.try_exists()? {
+                let imported = database.import_from(&TomlGameRepository::open(&toml_path)?)?;
+                info!("imported {imported} games from {}", toml_path.display());
+            }
+
+            Ok(Box::new(database))
+        }
+    }
+}
+
+/// A publisher for the chosen backend. It connects in the background and reconnects whenever
+/// the connection is lost, and its updates are paced to stay within Discord's rate limit.
+fn open_presence(backend: PresenceBackend) -> Box<dyn PresencePublisher> {
+    let client_id = || -> Result<i64, Error> { Ok(var("CLIENT_ID")?.parse()?) };
+    let offline = |reason: &dyn std::fmt::Display| -> Box<dyn PresencePublisher> {
+        warn!("running without Discord: {reason}");
+        Box::new(InMemoryPresence::new())
+    };
+
+    let publisher: Box<dyn PresencePublisher> = match (backend, client_id()) {
+        (PresenceBackend::Offline, _) => Box::new(InMemoryPresence::new()),
+        (_, Err(err)) => offline(&err),
+        #[cfg(unix)]
+        (PresenceBackend::Ipc, Ok(client_id)) => Box::new(Reconnecting::new(move || {
+            DiscordIpcPresence::connect(client_id).map_err(|err| anyhow::Error::from(err).into())
+        })),
+        #[cfg(not(unix))]
+        (PresenceBackend::Ipc, Ok(_)) => {
+            offline(&"the IPC backend needs Unix sockets, use --presence sdk")
+        }
+        (PresenceBackend::Sdk, Ok(client_id)) => Box::new(Reconnecting::new(move || {
+            DiscordPresence::connect(client_id).map_err(|err| anyhow::Error::from(err).into())
+        })),
+    };
+
+    Box::new(UpdateScheduler::new(publisher))
+}
 
 pub fn run() -> color_eyre::Result<()> {
     color_eyre::install()?;
@@ -34,9 +143,18 @@ pub fn run() -> color_eyre::Result<()> {
     tui_logger::set_default_level(log::LevelFilter::Trace);
     dotenv().ok();
     let config = Config::parse();
+    let settings = config::Config::from_env()?;
+    let service = Service::new(open_repository(config.storage, &settings)?);
+    let assets = load_asset_manifest(settings.asset_manifest())?;
+
+    if let Some(command) = &config.command {
+        command.run(&service, &assets)?;
+        return Ok(());
+    }
 
     let mut terminal = ratatui::init();
-    let result = App::new()?.run(&mut terminal, config);
+    let result =
+        App::new(service, open_presence(config.presence), assets).run(&mut terminal, config);
     ratatui::try_restore()?;
     result?;
 
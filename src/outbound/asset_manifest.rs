@@ -0,0 +1,18 @@
+use std::{fs, path::Path};
+
+use log::debug;
+
+use crate::{domain::asset::AssetManifest, error::GameError};
+
+/// Reads the asset manifest at `path`. A missing file is an empty manifest.
+pub fn load_asset_manifest(path: impl AsRef<Path>) -> Result<AssetManifest, GameError> {
+    let path = path.as_ref();
+    debug!("asset manifest path: {path:#?}");
+
+    if !path.try_exists()? {
+        debug!("no asset manifest, only checking key rules");
+        return Ok(AssetManifest::default());
+    }
+
+    Ok(toml::from_str(&fs::read_to_string(path)?)?)
+}
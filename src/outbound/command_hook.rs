@@ -0,0 +1,57 @@
+use std::{
+    io,
+    process::{Command, Stdio},
+    thread,
+};
+
+use log::{info, warn};
+
+/// Shell command the user configured to run when something happens, e.g. `--join-hook`. What
+/// happened is passed in `CONSOLE_PLAYER_*` environment variables.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct CommandHook {
+    command: String,
+}
+
+impl CommandHook {
+    pub fn new(command: impl Into<String>) -> Self {
+        Self {
+            command: command.into(),
+        }
+    }
+
+    pub fn command(&self) -> &str {
+        &self.command
+    }
+
+    /// Starts the command with `vars` set and returns without waiting for it. Its output is
+    /// discarded so it cannot draw over the terminal UI, and a failure exit is only logged.
+    pub fn run<'a>(&self, vars: impl IntoIterator<Item = (&'a str, &'a str)>) -> io::Result<()> {
+        #[cfg(unix)]
+        let mut command = Command::new("sh");
+        #[cfg(unix)]
+        command.arg("-c");
+        #[cfg(windows)]
+        let mut command = Command::new("cmd");
+        #[cfg(windows)]
+        command.arg("/C");
+
+        let mut child = command
+            .arg(&self.command)
+            .envs(vars)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()?;
+        info!("started hook {:?}", self.command);
+
+        let hook = self.command.clone();
+        thread::spawn(move || match child.wait() {
+            Ok(status) if status.success() => {}
+            Ok(status) => warn!("hook {hook:?} failed: {status}"),
+            Err(err) => warn!("cannot wait for hook {hook:?}: {err}"),
+        });
+
+        Ok(())
+    }
+}
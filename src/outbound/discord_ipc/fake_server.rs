@@ -0,0 +1,356 @@
+//! Stand-in for the Discord client that tests can connect a [`DiscordIpcPresence`] to.
+//!
+//! [`DiscordIpcPresence`]: crate::outbound::DiscordIpcPresence
+
+use std::{
+    collections::VecDeque,
+    fs,
+    io::Write,
+    net::Shutdown,
+    os::unix::net::{UnixListener, UnixStream},
+    path::{Path, PathBuf},
+    sync::{
+        Arc, Condvar, Mutex, MutexGuard,
+        atomic::{AtomicUsize, Ordering},
+    },
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+use serde_json::{Value, json};
+
+use crate::outbound::discord_ipc::{Frame, IpcError, Opcode};
+
+/// How long [`FakeDiscordServer::wait_for_activities`] waits before giving up.
+const WAIT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Id and secret of the lobby `CREATE_LOBBY` opens.
+pub const FAKE_LOBBY_ID: &str = "1";
+pub const FAKE_LOBBY_SECRET: &str = "s3cret";
+
+/// Misbehaviour the server shows instead of handling the next command normally.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ServerFault {
+    /// Answer the handshake with a close frame.
+    RejectHandshake { code: i64, message: String },
+    /// Answer the next command with an `ERROR` event and ignore it.
+    Reject { code: i64, message: String },
+    /// Answer the next command with a close frame and hang up.
+    Close { code: i64, message: String },
+    /// Hang up without a word when the next command arrives.
+    Disconnect,
+}
+
+#[derive(Debug, Default)]
+struct ServerState {
+    handshakes: Vec<Value>,
+    /// Every command received, subscriptions included.
+    commands: Vec<Value>,
+    activities: Vec<Option<Value>>,
+    /// What `GET_RELATIONSHIPS` answers with.
+    relationships: Vec<Value>,
+    faults: VecDeque<ServerFault>,
+    /// The client currently connected, kept to hang up on it on request.
+    connection: Option<UnixStream>,
+    stopped: bool,
+}
+
+#[derive(Debug, Default)]
+struct Shared {
+    state: Mutex<ServerState>,
+    changed: Condvar,
+}
+
+impl Shared {
+    fn lock(&self) -> MutexGuard<'_, ServerState> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Fake Discord IPC server on a Unix socket in a fresh temporary directory.
+///
+/// It accepts one client at a time, answers the handshake with `READY`, acknowledges every
+/// command and records the activity each `SET_ACTIVITY` carried. Lobbies opened or joined are
+/// answered as if they were empty. Faults queued with [`FakeDiscordServer::fail_next`] are
+/// consumed one per handshake or command, except for the `SUBSCRIBE`s and `GET_RELATIONSHIPS`
+/// sent while connecting, which always succeed.
+#[derive(Debug)]
+pub struct FakeDiscordServer {
+    dir: PathBuf,
+    path: PathBuf,
+    shared: Arc<Shared>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl FakeDiscordServer {
+    pub fn start() -> Result<Self, IpcError> {
+        static SERVERS: AtomicUsize = AtomicUsize::new(0);
+
+        let mut dir = std::env::temp_dir();
+        dir.push(format!(
+            "console-player-ipc-{}-{}",
+            std::process::id(),
+            SERVERS.fetch_add(1, Ordering::Relaxed)
+        ));
+        fs::create_dir_all(&dir)?;
+        let path = dir.join("discord-ipc-0");
+        let _ = fs::remove_file(&path);
+
+        let listener = UnixListener::bind(&path)?;
+        let shared = Arc::new(Shared::default());
+        let thread = thread::spawn({
+            let shared = Arc::clone(&shared);
+            move || serve(&listener, &shared)
+        });
+
+        Ok(Self {
+            dir,
+            path,
+            shared,
+            thread: Some(thread),
+        })
+    }
+
+    /// Directory holding the socket, suitable as `XDG_RUNTIME_DIR`.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Queues `fault` for the next handshake or command.
+    pub fn fail_next(&self, fault: ServerFault) {
+        self.shared.lock().faults.push_back(fault);
+    }
+
+    /// Makes `GET_RELATIONSHIPS` answer with `relationships`, as Discord's JSON objects.
+    pub fn set_relationships(&self, relationships: Vec<Value>) {
+        self.shared.lock().relationships = relationships;
+    }
+
+    /// Hangs up on the connected client right away.
+    pub fn disconnect(&self) {
+        if let Some(connection) = self.shared.lock().connection.take() {
+            let _ = connection.shutdown(Shutdown::Both);
+        }
+    }
+
+    /// Sends the event `evt` with `data` to the connected client, as Discord does for
+    /// subscribed events.
+    ///
+    /// # Panics
+    ///
+    /// If no client is connected.
+    pub fn dispatch(&self, evt: &str, data: Value) {
+        let state = self.shared.lock();
+        let mut connection = state.connection.as_ref().expect("a connected client");
+        let event = json!({ "cmd": "DISPATCH", "evt": evt, "data": data, "nonce": null });
+        Frame::new(Opcode::Frame, event)
+            .write_to(&mut connection)
+            .expect("the client to be reachable");
+    }
+
+    /// Sends `bytes` to the connected client as they are, e.g. to send a broken frame.
+    ///
+    /// # Panics
+    ///
+    /// If no client is connected.
+    pub fn send_raw(&self, bytes: &[u8]) {
+        let state = self.shared.lock();
+        let mut connection = state.connection.as_ref().expect("a connected client");
+        connection
+            .write_all(bytes)
+            .expect("the client to be reachable");
+    }
+
+    /// Waits until at least `count` `cmd` commands arrived and returns their arguments.
+    ///
+    /// # Panics
+    ///
+    /// If they do not arrive within a few seconds.
+    pub fn wait_for_commands(&self, cmd: &str, count: usize) -> Vec<Value> {
+        let matching = |state: &ServerState| {
+            state
+                .commands
+                .iter()
+                .filter(|command| command["cmd"] == cmd)
+                .map(|command| command["args"].clone())
+                .collect::<Vec<_>>()
+        };
+        let state = self.shared.lock();
+        let (state, timeout) = self
+            .shared
+            .changed
+            .wait_timeout_while(state, WAIT_TIMEOUT, |state| matching(state).len() < count)
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        assert!(
+            !timeout.timed_out(),
+            "expected {count} {cmd} commands, got {:?}",
+            state.commands
+        );
+
+        matching(&state)
+    }
+
+    /// Every handshake payload received so far.
+    pub fn handshakes(&self) -> Vec<Value> {
+        self.shared.lock().handshakes.clone()
+    }
+
+    /// Every activity set so far, in order, with `None` for each clear.
+    pub fn activities(&self) -> Vec<Option<Value>> {
+        self.shared.lock().activities.clone()
+    }
+
+    /// Waits until at least `count` activities were recorded and returns all of them.
+    ///
+    /// # Panics
+    ///
+    /// If they do not arrive within a few seconds.
+    pub fn wait_for_activities(&self, count: usize) -> Vec<Option<Value>> {
+        let state = self.shared.lock();
+        let (state, timeout) = self
+            .shared
+            .changed
+            .wait_timeout_while(state, WAIT_TIMEOUT, |state| state.activities.len() < count)
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        assert!(
+            !timeout.timed_out(),
+            "expected {count} activities, got {:?}",
+            state.activities
+        );
+
+        state.activities.clone()
+    }
+}
+
+impl Drop for FakeDiscordServer {
+    fn drop(&mut self) {
+        self.shared.lock().stopped = true;
+        self.disconnect();
+        // Wakes the accept loop up so it notices it was stopped.
+        let _ = UnixStream::connect(&self.path);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
+
+fn serve(listener: &UnixListener, shared: &Shared) {
+    for stream in listener.incoming() {
+        if shared.lock().stopped {
+            return;
+        }
+        let Ok(stream) = stream else { continue };
+        // A client that misbehaves or hangs up only ends its own connection.
+        let _ = serve_client(stream, shared);
+    }
+}
+
+fn serve_client(mut stream: UnixStream, shared: &Shared) -> Result<(), IpcError> {
+    let handshake = Frame::read_from(&mut stream)?;
+    if handshake.opcode != Opcode::Handshake {
+        return Err(IpcError::UnexpectedFrame(handshake.opcode));
+    }
+    shared.lock().handshakes.push(handshake.payload);
+
+    let fault = take_fault(shared, |fault| {
+        matches!(fault, ServerFault::RejectHandshake { .. })
+    });
+    if let Some(ServerFault::RejectHandshake { code, message }) = fault {
+        return close(&mut stream, code, &message);
+    }
+
+    let ready = json!({ "cmd": "DISPATCH", "evt": "READY", "data": { "v": 1 } });
+    Frame::new(Opcode::Frame, ready).write_to(&mut stream)?;
+    shared.lock().connection = Some(stream.try_clone()?);
+
+    loop {
+        let frame = Frame::read_from(&mut stream)?;
+        match frame.opcode {
+            Opcode::Ping => Frame::new(Opcode::Pong, frame.payload).write_to(&mut stream)?,
+            Opcode::Frame => {
+                let nonce = frame.payload["nonce"].clone();
+                let cmd = frame.payload["cmd"].clone();
+                let connecting = cmd == "SUBSCRIBE" || cmd == "GET_RELATIONSHIPS";
+                match take_fault(shared, |fault| {
+                    !connecting && !matches!(fault, ServerFault::RejectHandshake { .. })
+                }) {
+                    Some(ServerFault::Reject { code, message }) => {
+                        let error = json!({
+                            "cmd": frame.payload["cmd"],
+                            "evt": "ERROR",
+                            "data": { "code": code, "message": message },
+                            "nonce": nonce,
+                        });
+                        Frame::new(Opcode::Frame, error).write_to(&mut stream)?;
+                    }
+                    Some(ServerFault::Close { code, message }) => {
+                        return close(&mut stream, code, &message);
+                    }
+                    Some(ServerFault::Disconnect) => return Ok(()),
+                    Some(ServerFault::RejectHandshake { .. }) => unreachable!(),
+                    None => {
+                        record(shared, &frame.payload);
+                        let args = &frame.payload["args"];
+                        let data = match cmd.as_str() {
+                            Some("GET_RELATIONSHIPS") => {
+                                json!({ "relationships": shared.lock().relationships })
+                            }
+                            Some("CREATE_LOBBY") => json!({
+                                "id": FAKE_LOBBY_ID,
+                                "secret": FAKE_LOBBY_SECRET,
+                                "capacity": args["capacity"],
+                                "members": [],
+                            }),
+                            Some("CONNECT_TO_LOBBY") => json!({
+                                "id": args["id"],
+                                "secret": args["secret"],
+                                "capacity": 4,
+                                "members": [],
+                            }),
+                            _ => args["activity"].clone(),
+                        };
+                        let ack = json!({ "cmd": cmd, "evt": null, "data": data, "nonce": nonce });
+                        Frame::new(Opcode::Frame, ack).write_to(&mut stream)?;
+                    }
+                }
+            }
+            Opcode::Close => return Ok(()),
+            opcode => return Err(IpcError::UnexpectedFrame(opcode)),
+        }
+    }
+}
+
+/// Takes the next queued fault if `applies` to it.
+fn take_fault(shared: &Shared, applies: impl Fn(&ServerFault) -> bool) -> Option<ServerFault> {
+    let mut state = shared.lock();
+    if state.faults.front().is_some_and(applies) {
+        state.faults.pop_front()
+    } else {
+        None
+    }
+}
+
+fn record(shared: &Shared, command: &Value) {
+    let mut state = shared.lock();
+    state.commands.push(command.clone());
+    if command["cmd"] == "SET_ACTIVITY" {
+        let activity = &command["args"]["activity"];
+        state
+            .activities
+            .push((!activity.is_null()).then(|| activity.clone()));
+    }
+    shared.changed.notify_all();
+}
+
+fn close(stream: &mut UnixStream, code: i64, message: &str) -> Result<(), IpcError> {
+    let payload = json!({ "code": code, "message": message });
+    Frame::new(Opcode::Close, payload).write_to(stream)?;
+    Ok(())
+}
@@ -0,0 +1,169 @@
+use std::io::{self, Read, Write};
+
+use serde_json::Value;
+
+use crate::outbound::discord_ipc::IpcError;
+
+/// Size of the header in front of every payload: opcode and payload length, both little-endian
+/// `u32`.
+const HEADER_LEN: usize = 8;
+
+/// Largest payload accepted from the other end, as Discord allows. Anything longer means the
+/// stream is corrupt or the peer misbehaves, and is not buffered.
+pub const MAX_PAYLOAD_LEN: usize = 64 * 1024;
+
+/// Kind of an IPC frame.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Opcode {
+    Handshake = 0,
+    Frame = 1,
+    Close = 2,
+    Ping = 3,
+    Pong = 4,
+}
+
+impl TryFrom<u32> for Opcode {
+    type Error = IpcError;
+
+    fn try_from(value: u32) -> Result<Self, Self::Error> {
+        Ok(match value {
+            0 => Self::Handshake,
+            1 => Self::Frame,
+            2 => Self::Close,
+            3 => Self::Ping,
+            4 => Self::Pong,
+            other => return Err(IpcError::InvalidOpcode(other)),
+        })
+    }
+}
+
+/// One message of Discord's IPC protocol: a header followed by a JSON payload.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Frame {
+    pub opcode: Opcode,
+    pub payload: Value,
+}
+
+impl Frame {
+    pub const fn new(opcode: Opcode, payload: Value) -> Self {
+        Self { opcode, payload }
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let payload = self.payload.to_string();
+        let len = u32::try_from(payload.len()).expect("IPC payloads are far below 4 GiB");
+
+        let mut bytes = Vec::with_capacity(HEADER_LEN + payload.len());
+        bytes.extend_from_slice(&(self.opcode as u32).to_le_bytes());
+        bytes.extend_from_slice(&len.to_le_bytes());
+        bytes.extend_from_slice(payload.as_bytes());
+        bytes
+    }
+
+    /// Takes the first complete frame off the front of `buffer`, leaving the rest in place.
+    /// Returns `None` while the frame is still incomplete. A complete frame that turns out to be
+    /// invalid is taken off too, so it is not decoded again.
+    pub fn decode(buffer: &mut Vec<u8>) -> Result<Option<Self>, IpcError> {
+        let Some(header) = buffer.get(..HEADER_LEN) else {
+            return Ok(None);
+        };
+        let opcode = u32::from_le_bytes(header[..4].try_into().expect("4 bytes"));
+        let len = payload_len(header)?;
+        if buffer.len() < HEADER_LEN + len {
+            return Ok(None);
+        }
+
+        let frame: Vec<u8> = buffer.drain(..HEADER_LEN + len).collect();
+        let opcode = Opcode::try_from(opcode)?;
+        let payload = serde_json::from_slice(&frame[HEADER_LEN..])?;
+
+        Ok(Some(Self { opcode, payload }))
+    }
+
+    /// Reads exactly one frame, blocking until it is complete.
+    pub fn read_from(reader: &mut impl Read) -> Result<Self, IpcError> {
+        let mut buffer = vec![0; HEADER_LEN];
+        reader.read_exact(&mut buffer)?;
+        let len = payload_len(&buffer)?;
+        buffer.resize(HEADER_LEN + len, 0);
+        reader.read_exact(&mut buffer[HEADER_LEN..])?;
+
+        Self::decode(&mut buffer).map(|frame| frame.expect("the whole frame was read"))
+    }
+
+    pub fn write_to(&self, writer: &mut impl Write) -> io::Result<()> {
+        writer.write_all(&self.encode())?;
+        writer.flush()
+    }
+}
+
+/// The payload length announced in `header`, if it is acceptable.
+fn payload_len(header: &[u8]) -> Result<usize, IpcError> {
+    let len = u32::from_le_bytes(header[4..HEADER_LEN].try_into().expect("4 bytes")) as usize;
+    if len > MAX_PAYLOAD_LEN {
+        return Err(IpcError::FrameTooLarge(len));
+    }
+
+    Ok(len)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::outbound::discord_ipc::{
+        IpcError,
+        frame::{Frame, MAX_PAYLOAD_LEN, Opcode},
+    };
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn frames_round_trip() {
+        let handshake = Frame::new(Opcode::Handshake, json!({ "v": 1, "client_id": "42" }));
+        let ping = Frame::new(Opcode::Ping, json!({}));
+        let mut bytes = handshake.encode();
+        bytes.extend(ping.encode());
+
+        assert_eq!(&bytes[..4], &[0, 0, 0, 0]);
+        assert_eq!(Frame::read_from(&mut bytes.as_slice()).unwrap(), handshake);
+
+        let mut partial = bytes[..bytes.len() - 1].to_vec();
+        assert_eq!(Frame::decode(&mut partial).unwrap(), Some(handshake));
+        assert_eq!(Frame::decode(&mut partial).unwrap(), None);
+        partial.push(*bytes.last().unwrap());
+        assert_eq!(Frame::decode(&mut partial).unwrap(), Some(ping));
+        assert!(partial.is_empty());
+    }
+
+    #[test]
+    fn rejects_unknown_opcodes() {
+        let mut bytes = Frame::new(Opcode::Pong, json!({})).encode();
+        bytes[0] = 9;
+
+        assert!(matches!(
+            Frame::decode(&mut bytes),
+            Err(IpcError::InvalidOpcode(9))
+        ));
+    }
+
+    #[test]
+    fn invalid_frames_are_consumed_and_huge_ones_refused() {
+        let ping = Frame::new(Opcode::Ping, json!({}));
+        let mut bytes = vec![1, 0, 0, 0, 3, 0, 0, 0, b'{', b'{', b'{'];
+        bytes.extend(ping.encode());
+
+        assert!(matches!(Frame::decode(&mut bytes), Err(IpcError::Json(_))));
+        assert_eq!(Frame::decode(&mut bytes).unwrap(), Some(ping));
+
+        let huge = u32::try_from(MAX_PAYLOAD_LEN + 1).unwrap();
+        let mut bytes = [1u32.to_le_bytes(), huge.to_le_bytes()].concat();
+        assert!(matches!(
+            Frame::read_from(&mut bytes.as_slice()),
+            Err(IpcError::FrameTooLarge(len)) if len == MAX_PAYLOAD_LEN + 1
+        ));
+        assert!(matches!(
+            Frame::decode(&mut bytes),
+            Err(IpcError::FrameTooLarge(_))
+        ));
+    }
+}
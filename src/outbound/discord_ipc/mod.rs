@@ -0,0 +1,994 @@
+//! Pure-Rust client for Discord's local RPC protocol, as spoken by the Discord desktop client and
+//! by stand-ins such as arRPC.
+//!
+//! The client connects to the `discord-ipc-N` Unix socket, identifies itself with a handshake and
+//! then sends `SET_ACTIVITY` commands. It also subscribes to join, spectate and relationship
+//! events, and to the events of the lobby it is in, which it hands out as [`PresenceEvent`]s.
+//! Every message is a [`Frame`].
+
+use std::{
+    collections::HashMap,
+    env,
+    io::{self, Read},
+    os::unix::net::UnixStream,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use log::{debug, info, warn};
+use serde::Serialize;
+use serde_json::{Value, json};
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::domain::{
+    game::{Image, Secret, Tooltip},
+    presence::{
+        Friend, FriendActivity, InviteKind, JoinReply, OnlineStatus, Presence, PresenceError,
+        PresenceEvent, PresencePublisher, PresenceStatus, User, UserId,
+        lobby::{Lobby, LobbyCommand, LobbyId, LobbyMessage},
+    },
+};
+
+pub use frame::{Frame, Opcode};
+
+#[cfg(any(test, feature = "test-support"))]
+pub mod fake_server;
+mod frame;
+
+/// How long to wait for Discord to answer the handshake.
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Events subscribed to right after the handshake.
+const SUBSCRIPTIONS: [&str; 4] = [
+    "ACTIVITY_JOIN",
+    "ACTIVITY_SPECTATE",
+    "ACTIVITY_JOIN_REQUEST",
+    "RELATIONSHIP_UPDATE",
+];
+
+/// Events of a lobby, subscribed to once in it.
+const LOBBY_SUBSCRIPTIONS: [&str; 4] = [
+    "LOBBY_DELETE",
+    "LOBBY_MEMBER_CONNECT",
+    "LOBBY_MEMBER_DISCONNECT",
+    "LOBBY_MESSAGE",
+];
+
+/// The `type` of private lobbies.
+const PRIVATE_LOBBY: i64 = 1;
+
+/// Commands sent while connecting that Discord may refuse, e.g. to applications without the
+/// scope, without making the connection useless.
+const OPTIONAL_COMMANDS: [&str; 2] = ["SUBSCRIBE", "GET_RELATIONSHIPS"];
+
+/// The `type` Discord gives friends in relationships.
+const FRIEND: i64 = 1;
+
+/// Discord tries `discord-ipc-0` up to `discord-ipc-9`.
+const SOCKET_COUNT: usize = 10;
+
+/// Places the socket may be in, relative to the runtime or temp directory. Flatpak and Snap
+/// installs of Discord put it in their own subdirectory.
+const SOCKET_SUBDIRS: [&str; 3] = ["", "app/com.discordapp.Discord", "snap.discord"];
+
+/// Every socket path Discord may listen on, most likely first.
+pub fn socket_candidates() -> Vec<PathBuf> {
+    let dirs = ["XDG_RUNTIME_DIR", "TMPDIR", "TMP", "TEMP"]
+        .into_iter()
+        .filter_map(|var| env::var_os(var).map(PathBuf::from))
+        .chain([PathBuf::from("/tmp")]);
+
+    dirs.flat_map(|dir| SOCKET_SUBDIRS.map(|subdir| dir.join(subdir)))
+        .flat_map(|dir| (0..SOCKET_COUNT).map(move |n| dir.join(format!("discord-ipc-{n}"))))
+        .collect()
+}
+
+/// `PresencePublisher` that talks to Discord over its IPC socket.
+#[derive(Debug)]
+pub struct DiscordIpcPresence {
+    stream: UnixStream,
+    /// Bytes received but not yet decoded into a frame.
+    buffer: Vec<u8>,
+    status: PresenceStatus,
+    /// Events received but not yet taken.
+    events: Vec<PresenceEvent>,
+    /// Commands whose answer becomes an event, by nonce.
+    pending: HashMap<String, Pending>,
+}
+
+/// A command waiting for its answer.
+#[derive(Clone, Copy, Debug)]
+enum Pending {
+    Invite(UserId),
+    /// Opening or joining a lobby.
+    Lobby,
+    LeaveLobby(LobbyId),
+    LobbyMessage,
+}
+
+impl DiscordIpcPresence {
+    /// Connects to the first Discord socket that accepts the handshake.
+    pub fn connect(client_id: i64) -> Result<Self, IpcError> {
+        let mut result = Err(IpcError::NoSocket);
+
+        for path in socket_candidates().iter().filter(|path| path.exists()) {
+            result = Self::connect_to(path, client_id);
+            match &result {
+                Ok(_) => break,
+                Err(err) => debug!("cannot use {}: {err}", path.display()),
+            }
+        }
+
+        result
+    }
+
+    /// Connects to the socket at `path` and performs the handshake as application `client_id`.
+    pub fn connect_to(path: &Path, client_id: i64) -> Result<Self, IpcError> {
+        let mut stream = UnixStream::connect(path)?;
+        stream.set_read_timeout(Some(HANDSHAKE_TIMEOUT))?;
+
+        let handshake = json!({ "v": 1, "client_id": client_id.to_string() });
+        Frame::new(Opcode::Handshake, handshake).write_to(&mut stream)?;
+
+        loop {
+            let frame = Frame::read_from(&mut stream)?;
+            match frame.opcode {
+                Opcode::Frame if frame.payload["evt"] == "READY" => break,
+                Opcode::Ping => Frame::new(Opcode::Pong, frame.payload).write_to(&mut stream)?,
+                Opcode::Close => return Err(IpcError::closed(&frame.payload)),
+                opcode => return Err(IpcError::UnexpectedFrame(opcode)),
+            }
+        }
+        stream.set_read_timeout(None)?;
+        info!("connected to Discord at {}", path.display());
+
+        let mut client = Self {
+            stream,
+            buffer: Vec::new(),
+            status: PresenceStatus::Connected,
+            events: Vec::new(),
+            pending: HashMap::new(),
+        };
+        for event in SUBSCRIPTIONS {
+            client.command("SUBSCRIBE", Some(event), json!({}))?;
+        }
+        client.command("GET_RELATIONSHIPS", None, json!({}))?;
+
+        Ok(client)
+    }
+
+    fn send(&mut self, frame: &Frame) -> Result<(), IpcError> {
+        if self.status != PresenceStatus::Connected {
+            return Err(IpcError::Disconnected);
+        }
+
+        frame.write_to(&mut self.stream).inspect_err(|_| {
+            self.status = PresenceStatus::Offline;
+        })?;
+
+        Ok(())
+    }
+
+    /// Sends the command `cmd`, about the event `evt` if given, and returns its nonce, which
+    /// Discord's answer carries too.
+    fn command(&mut self, cmd: &str, evt: Option<&str>, args: Value) -> Result<String, IpcError> {
+        let nonce = Uuid::new_v4().to_string();
+        let mut command = json!({ "cmd": cmd, "args": args, "nonce": nonce });
+        if let Some(evt) = evt {
+            command["evt"] = evt.into();
+        }
+
+        self.send(&Frame::new(Opcode::Frame, command))?;
+        Ok(nonce)
+    }
+
+    /// Sends `SET_ACTIVITY`; `None` clears the activity.
+    fn set_activity(&mut self, activity: Option<Activity>) -> Result<(), IpcError> {
+        let args = json!({ "pid": std::process::id(), "activity": activity });
+        self.command("SET_ACTIVITY", None, args)?;
+        Ok(())
+    }
+
+    /// Reads whatever has arrived without blocking.
+    fn receive(&mut self) -> Result<(), IpcError> {
+        self.stream.set_nonblocking(true)?;
+        let mut chunk = [0; 4096];
+        let result = loop {
+            match self.stream.read(&mut chunk) {
+                Ok(0) => break Err(IpcError::Disconnected),
+                Ok(read) => self.buffer.extend_from_slice(&chunk[..read]),
+                Err(err) if err.kind() == io::ErrorKind::WouldBlock => break Ok(()),
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(err) => break Err(err.into()),
+            }
+        };
+        self.stream.set_nonblocking(false)?;
+
+        result.inspect_err(|_| self.status = PresenceStatus::Offline)
+    }
+
+    fn handle(&mut self, frame: Frame) -> Result<(), IpcError> {
+        match frame.opcode {
+            Opcode::Ping => self.send(&Frame::new(Opcode::Pong, frame.payload)),
+            Opcode::Close => {
+                self.status = PresenceStatus::Offline;
+                Err(IpcError::closed(&frame.payload))
+            }
+            Opcode::Frame
+                if let Some(pending) = frame.payload["nonce"]
+                    .as_str()
+                    .and_then(|nonce| self.pending.remove(nonce)) =>
+            {
+                self.answered(pending, &frame.payload)
+            }
+            // Older clients and arRPC may not know every event, which costs only those events.
+            Opcode::Frame
+                if frame.payload["evt"] == "ERROR"
+                    && OPTIONAL_COMMANDS
+                        .iter()
+                        .any(|&cmd| frame.payload["cmd"] == cmd) =>
+            {
+                warn!(
+                    "discord: {} refused: {}",
+                    frame.payload["cmd"], frame.payload["data"]
+                );
+                Ok(())
+            }
+            Opcode::Frame if frame.payload["evt"] == "ERROR" => {
+                let (code, message) = error_details(&frame.payload["data"]);
+                Err(IpcError::Rejected { code, message })
+            }
+            Opcode::Frame if frame.payload["cmd"] == "GET_RELATIONSHIPS" => {
+                let friends = frame.payload["data"]["relationships"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter(|relationship| relationship["type"] == FRIEND)
+                    .filter_map(friend)
+                    .collect();
+                self.events.push(PresenceEvent::Friends(friends));
+                Ok(())
+            }
+            Opcode::Frame if frame.payload["cmd"] == "DISPATCH" => {
+                match dispatched_event(&frame.payload) {
+                    Some(event) => self.events.push(event),
+                    None => debug!("discord: {}", frame.payload),
+                }
+                Ok(())
+            }
+            Opcode::Frame | Opcode::Pong => {
+                debug!("discord: {}", frame.payload);
+                Ok(())
+            }
+            Opcode::Handshake => Err(IpcError::UnexpectedFrame(frame.opcode)),
+        }
+    }
+
+    /// Turns the answer to a pending command into events.
+    fn answered(&mut self, pending: Pending, payload: &Value) -> Result<(), IpcError> {
+        let data = &payload["data"];
+        let error = (payload["evt"] == "ERROR").then(|| {
+            let (code, message) = error_details(data);
+            format!("{message} ({code})")
+        });
+
+        match (pending, error) {
+            (Pending::Invite(user), None) => self.events.push(PresenceEvent::InviteSent(user)),
+            (Pending::Invite(user), Some(error)) => {
+                self.events.push(PresenceEvent::InviteFailed(user, error));
+            }
+            (Pending::Lobby, None) => {
+                let Some(lobby) = lobby(data) else {
+                    warn!("discord: unexpected lobby {data}");
+                    return Ok(());
+                };
+                let id = lobby.id();
+                self.events.push(PresenceEvent::LobbyJoined(lobby));
+                for member in data["members"].as_array().into_iter().flatten() {
+                    if let Some(user) = user(&member["user"]) {
+                        self.events.push(PresenceEvent::LobbyMemberJoined(id, user));
+                    }
+                }
+                for event in LOBBY_SUBSCRIPTIONS {
+                    let args = json!({ "lobby_id": id.to_string() });
+                    self.command("SUBSCRIBE", Some(event), args)?;
+                }
+            }
+            (Pending::LeaveLobby(id), None) => self.events.push(PresenceEvent::LobbyClosed(id)),
+            (Pending::LobbyMessage, None) => {}
+            (Pending::LobbyMessage, Some(error)) => self.events.push(PresenceEvent::LobbyFailed(
+                format!("cannot send the message: {error}"),
+            )),
+            (Pending::Lobby | Pending::LeaveLobby(_), Some(error)) => {
+                self.events.push(PresenceEvent::LobbyFailed(error));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn poll_frames(&mut self) -> Result<(), IpcError> {
+        if self.status != PresenceStatus::Connected {
+            return Ok(());
+        }
+
+        self.receive()?;
+        // A frame that cannot be decoded leaves the stream out of step, so the connection is
+        // given up to be reopened.
+        while let Some(frame) = Frame::decode(&mut self.buffer)
+            .inspect_err(|_| self.status = PresenceStatus::Offline)?
+        {
+            self.handle(frame)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl PresencePublisher for DiscordIpcPresence {
+    fn set(&mut self, presence: &Presence) -> Result<(), PresenceError> {
+        self.set_activity(Some(Activity::from(presence)))
+            .map_err(anyhow::Error::from)?;
+
+        Ok(())
+    }
+
+    fn clear(&mut self) -> Result<(), PresenceError> {
+        self.set_activity(None).map_err(anyhow::Error::from)?;
+
+        Ok(())
+    }
+
+    fn status(&self) -> PresenceStatus {
+        self.status
+    }
+
+    fn poll(&mut self) -> Result<(), PresenceError> {
+        self.poll_frames().map_err(anyhow::Error::from)?;
+
+        Ok(())
+    }
+
+    fn take_events(&mut self) -> Vec<PresenceEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    fn reply_to_join(&mut self, user: UserId, reply: JoinReply) -> Result<(), PresenceError> {
+        let cmd = match reply {
+            JoinReply::Accept => "SEND_ACTIVITY_JOIN_INVITE",
+            JoinReply::Decline => "CLOSE_ACTIVITY_REQUEST",
+        };
+        self.command(cmd, None, json!({ "user_id": user.to_string() }))
+            .map_err(anyhow::Error::from)?;
+
+        Ok(())
+    }
+
+    fn send_invite(&mut self, user: UserId, kind: InviteKind) -> Result<(), PresenceError> {
+        let kind = match kind {
+            InviteKind::Join => 1,
+            InviteKind::Spectate => 2,
+        };
+        let args = json!({
+            "user_id": user.to_string(),
+            "type": kind,
+            "content": "",
+            "pid": std::process::id(),
+        });
+        let nonce = self
+            .command("ACTIVITY_INVITE_USER", None, args)
+            .map_err(anyhow::Error::from)?;
+        self.pending.insert(nonce, Pending::Invite(user));
+
+        Ok(())
+    }
+
+    fn lobby(&mut self, command: LobbyCommand) -> Result<(), PresenceError> {
+        let (cmd, args, pending) = match command {
+            LobbyCommand::Create { capacity, game } => {
+                let args = json!({
+                    "type": PRIVATE_LOBBY,
+                    "capacity": capacity,
+                    "metadata": { "game": game },
+                });
+                ("CREATE_LOBBY", args, Pending::Lobby)
+            }
+            LobbyCommand::Join(secret) => {
+                let Some((id, secret)) = secret.split_once(':') else {
+                    return Err(anyhow::anyhow!("{secret:?} is not a lobby secret").into());
+                };
+                let args = json!({ "id": id, "secret": secret });
+                ("CONNECT_TO_LOBBY", args, Pending::Lobby)
+            }
+            LobbyCommand::Leave(id) => {
+                let args = json!({ "id": id.to_string() });
+                ("DISCONNECT_FROM_LOBBY", args, Pending::LeaveLobby(id))
+            }
+            LobbyCommand::Send(id, text) => {
+                let args = json!({ "lobby_id": id.to_string(), "data": text });
+                ("SEND_TO_LOBBY", args, Pending::LobbyMessage)
+            }
+        };
+        let nonce = self.command(cmd, None, args).map_err(anyhow::Error::from)?;
+        self.pending.insert(nonce, pending);
+
+        Ok(())
+    }
+}
+
+/// The event a `DISPATCH` frame carries, if it is one we subscribed to.
+fn dispatched_event(payload: &Value) -> Option<PresenceEvent> {
+    let data = &payload["data"];
+    let secret = || data["secret"].as_str().map(ToString::to_string);
+
+    match payload["evt"].as_str()? {
+        "ACTIVITY_JOIN" => secret().map(PresenceEvent::Join),
+        "ACTIVITY_SPECTATE" => secret().map(PresenceEvent::Spectate),
+        "ACTIVITY_JOIN_REQUEST" => user(&data["user"]).map(PresenceEvent::JoinRequest),
+        "RELATIONSHIP_UPDATE" if data["type"] == FRIEND => {
+            friend(data).map(PresenceEvent::FriendUpdate)
+        }
+        "RELATIONSHIP_UPDATE" => {
+            user(&data["user"]).map(|user| PresenceEvent::FriendRemoved(user.id()))
+        }
+        "LOBBY_DELETE" => Some(PresenceEvent::LobbyClosed(LobbyId::new(snowflake(
+            &data["id"],
+        )?))),
+        "LOBBY_MEMBER_CONNECT" => Some(PresenceEvent::LobbyMemberJoined(
+            LobbyId::new(snowflake(&data["lobby_id"])?),
+            user(&data["member"]["user"])?,
+        )),
+        "LOBBY_MEMBER_DISCONNECT" => Some(PresenceEvent::LobbyMemberLeft(
+            LobbyId::new(snowflake(&data["lobby_id"])?),
+            UserId::new(snowflake(&data["member"]["user"]["id"])?),
+        )),
+        "LOBBY_MESSAGE" => Some(PresenceEvent::LobbyMessage(
+            LobbyId::new(snowflake(&data["lobby_id"])?),
+            UserId::new(snowflake(&data["sender_id"])?),
+            LobbyMessage::decode(&payload_bytes(&data["data"])),
+        )),
+        _ => None,
+    }
+}
+
+/// An id, which Discord sends as a string since it may not fit a JSON number.
+fn snowflake(id: &Value) -> Option<i64> {
+    id.as_str()?.parse().ok()
+}
+
+/// The data of a lobby message, sent as a string or, for binary data, an array of bytes. Other
+/// members may send anything, so whatever is not a byte is dropped.
+fn payload_bytes(data: &Value) -> Vec<u8> {
+    match data {
+        Value::String(text) => text.clone().into_bytes(),
+        Value::Array(bytes) => bytes
+            .iter()
+            .filter_map(|byte| u8::try_from(byte.as_u64()?).ok())
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// The lobby in the answer to `CREATE_LOBBY` or `CONNECT_TO_LOBBY`.
+fn lobby(data: &Value) -> Option<Lobby> {
+    let id = snowflake(&data["id"])?;
+    let secret = data["secret"].as_str()?;
+    let capacity = data["capacity"]
+        .as_u64()
+        .and_then(|capacity| u32::try_from(capacity).ok())
+        .unwrap_or_default();
+    Some(Lobby::new(
+        LobbyId::new(id),
+        format!("{id}:{secret}"),
+        capacity,
+    ))
+}
+
+/// The user as found in events and relationships.
+fn user(user: &Value) -> Option<User> {
+    let id = snowflake(&user["id"])?;
+    let name = user["username"].as_str()?.to_string();
+    Some(User::new(UserId::new(id), name))
+}
+
+/// The friend a relationship is about, assuming it is a friendship.
+fn friend(relationship: &Value) -> Option<Friend> {
+    let presence = &relationship["presence"];
+    let status = match presence["status"].as_str() {
+        Some("online") => OnlineStatus::Online,
+        Some("idle") => OnlineStatus::Idle,
+        Some("dnd") => OnlineStatus::DoNotDisturb,
+        _ => OnlineStatus::Offline,
+    };
+    let activity = &presence["activity"];
+    let text = |field: &str| activity[field].as_str().unwrap_or_default().to_string();
+    let activity = activity["name"]
+        .as_str()
+        .map(|name| FriendActivity::new(name.to_string(), text("state"), text("details")));
+
+    Some(Friend::new(user(&relationship["user"])?, status).with_activity(activity))
+}
+
+/// The `activity` argument of `SET_ACTIVITY`.
+#[derive(Debug, Serialize)]
+struct Activity<'a> {
+    #[serde(skip_serializing_if = "str::is_empty")]
+    state: &'a str,
+    #[serde(skip_serializing_if = "str::is_empty")]
+    details: &'a str,
+    #[serde(skip_serializing_if = "Assets::is_empty")]
+    assets: Assets<'a>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    timestamps: Option<Timestamps>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    party: Option<ActivityParty<'a>>,
+    #[serde(skip_serializing_if = "Secrets::is_empty")]
+    secrets: Secrets<'a>,
+}
+
+/// Unix timestamps in seconds.
+#[derive(Debug, Serialize)]
+struct Timestamps {
+    start: i64,
+}
+
+/// `size` is the current and the maximum number of members.
+#[derive(Debug, Serialize)]
+struct ActivityParty<'a> {
+    id: &'a str,
+    size: [u32; 2],
+}
+
+#[derive(Debug, Serialize)]
+struct Assets<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    large_image: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    large_text: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    small_image: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    small_text: Option<&'a str>,
+}
+
+#[derive(Debug, Serialize)]
+struct Secrets<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    join: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    spectate: Option<&'a str>,
+}
+
+impl Secrets<'_> {
+    const fn is_empty(&self) -> bool {
+        self.join.is_none() && self.spectate.is_none()
+    }
+}
+
+impl Assets<'_> {
+    const fn is_empty(&self) -> bool {
+        self.large_image.is_none()
+            && self.large_text.is_none()
+            && self.small_image.is_none()
+            && self.small_text.is_none()
+    }
+}
+
+impl<'a> From<&'a Presence> for Activity<'a> {
+    fn from(presence: &'a Presence) -> Self {
+        Self {
+            state: presence.state(),
+            details: presence.details(),
+            assets: Assets {
+                large_image: presence.large_image().map(Image::as_str),
+                large_text: presence.large_tooltip().map(Tooltip::as_str),
+                small_image: presence.small_image().map(Image::as_str),
+                small_text: presence.small_tooltip().map(Tooltip::as_str),
+            },
+            timestamps: presence.start().map(|start| Timestamps {
+                start: start.timestamp(),
+            }),
+            party: presence.party().map(|party| ActivityParty {
+                id: party.id(),
+                size: [party.size(), party.max()],
+            }),
+            secrets: Secrets {
+                join: presence.secrets().join().map(Secret::as_str),
+                spectate: presence.secrets().spectate().map(Secret::as_str),
+            },
+        }
+    }
+}
+
+/// The `code` and `message` of a close frame or error event.
+fn error_details(payload: &Value) -> (i64, String) {
+    let code = payload["code"].as_i64().unwrap_or_default();
+    let message = payload["message"].as_str().unwrap_or_default().to_string();
+    (code, message)
+}
+
+#[derive(Debug, Error)]
+pub enum IpcError {
+    #[error("no Discord IPC socket accepted the connection")]
+    NoSocket,
+    #[error("not connected to Discord")]
+    Disconnected,
+    #[error("Discord closed the connection: {message} ({code})")]
+    Closed { code: i64, message: String },
+    #[error("Discord rejected the command: {message} ({code})")]
+    Rejected { code: i64, message: String },
+    #[error("unexpected {0:?} frame from Discord")]
+    UnexpectedFrame(Opcode),
+    #[error("invalid IPC opcode {0}")]
+    InvalidOpcode(u32),
+    #[error("IPC frame of {0} bytes is too large")]
+    FrameTooLarge(usize),
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+}
+
+impl IpcError {
+    fn closed(payload: &Value) -> Self {
+        let (code, message) = error_details(payload);
+        Self::Closed { code, message }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        thread,
+        time::{Duration, Instant},
+    };
+
+    use chrono::DateTime;
+    use serde_json::json;
+
+    use crate::{
+        domain::{
+            game::{Image, Party, Tooltip},
+            presence::{
+                Friend, FriendActivity, InviteKind, JoinReply, OnlineStatus, Presence,
+                PresenceEvent, PresencePublisher, PresenceStatus, User, UserId,
+                connection::Reconnecting,
+                lobby::{Lobby, LobbyCommand, LobbyId, LobbyMessage},
+            },
+        },
+        outbound::{
+            FakeDiscordServer, ServerFault,
+            discord_ipc::{
+                Activity, DiscordIpcPresence, IpcError,
+                fake_server::{FAKE_LOBBY_ID, FAKE_LOBBY_SECRET},
+            },
+        },
+    };
+    use pretty_assertions::assert_eq;
+
+    fn presence(state: &str) -> Presence {
+        Presence::builder()
+            .state(state.to_string())
+            .details(String::from("Nintendo Switch"))
+            .build()
+    }
+
+    /// Polls until `count` events arrived and returns them.
+    fn next_events(client: &mut DiscordIpcPresence, count: usize) -> Vec<PresenceEvent> {
+        let mut events = Vec::new();
+        for _ in 0..500 {
+            client.poll().unwrap();
+            events.extend(client.take_events());
+            if events.len() >= count {
+                return events;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        panic!("expected {count} events, got {events:?}");
+    }
+
+    /// Polls until the client reports an error.
+    fn next_error(client: &mut DiscordIpcPresence) -> IpcError {
+        for _ in 0..500 {
+            if let Err(err) = client.poll_frames() {
+                return err;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        panic!("the client never reported an error");
+    }
+
+    #[test]
+    fn sets_and_clears_the_activity() {
+        let server = FakeDiscordServer::start().unwrap();
+        let mut client = DiscordIpcPresence::connect_to(server.path(), 42).unwrap();
+
+        client.set(&presence("Mario Kart 8")).unwrap();
+        client.clear().unwrap();
+
+        assert_eq!(server.handshakes(), [json!({ "v": 1, "client_id": "42" })]);
+        assert_eq!(
+            server.wait_for_activities(2),
+            [
+                Some(json!({ "state": "Mario Kart 8", "details": "Nintendo Switch" })),
+                None
+            ]
+        );
+        assert_eq!(client.status(), PresenceStatus::Connected);
+    }
+
+    #[test]
+    fn reports_rejected_commands_and_hang_ups() {
+        let server = FakeDiscordServer::start().unwrap();
+        server.fail_next(ServerFault::RejectHandshake {
+            code: 4000,
+            message: String::from("invalid client id"),
+        });
+        assert!(matches!(
+            DiscordIpcPresence::connect_to(server.path(), 42),
+            Err(IpcError::Closed { code: 4000, .. })
+        ));
+
+        let mut client = DiscordIpcPresence::connect_to(server.path(), 42).unwrap();
+        server.fail_next(ServerFault::Reject {
+            code: 4002,
+            message: String::from("bad activity"),
+        });
+        client.set(&presence("x")).unwrap();
+        assert!(matches!(
+            next_error(&mut client),
+            IpcError::Rejected { code: 4002, .. }
+        ));
+        client.set(&presence("Mario Kart 8")).unwrap();
+        assert_eq!(server.wait_for_activities(1).len(), 1);
+
+        server.disconnect();
+        assert!(matches!(next_error(&mut client), IpcError::Disconnected));
+        assert_eq!(client.status(), PresenceStatus::Offline);
+        assert!(client.set(&presence("Zelda")).is_err());
+    }
+
+    #[test]
+    fn a_garbled_frame_gives_up_the_connection() {
+        let server = FakeDiscordServer::start().unwrap();
+        let mut client = DiscordIpcPresence::connect_to(server.path(), 42).unwrap();
+        assert_eq!(next_events(&mut client, 1).len(), 1);
+
+        server.send_raw(&[1, 0, 0, 0, 3, 0, 0, 0, b'{', b'{', b'{']);
+        assert!(matches!(next_error(&mut client), IpcError::Json(_)));
+        assert_eq!(client.status(), PresenceStatus::Offline);
+        assert!(client.buffer.is_empty());
+        drop(client);
+
+        // Reconnecting drops it for a new one.
+        let mut publisher = Reconnecting::new(|| {
+            DiscordIpcPresence::connect_to(server.path(), 42)
+                .map_err(|err| anyhow::Error::from(err).into())
+        });
+        publisher.poll().unwrap();
+        assert_eq!(publisher.status(), PresenceStatus::Connected);
+        server.send_raw(&[1, 0, 0, 0, 3, 0, 0, 0, b'{', b'{', b'{']);
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while publisher.status() == PresenceStatus::Connected && Instant::now() < deadline {
+            let _ = publisher.poll();
+            thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(publisher.status(), PresenceStatus::Connecting);
+    }
+
+    #[test]
+    fn join_requests_arrive_as_events_and_get_replies() {
+        let server = FakeDiscordServer::start().unwrap();
+        let mut client = DiscordIpcPresence::connect_to(server.path(), 42).unwrap();
+        assert_eq!(server.wait_for_commands("SUBSCRIBE", 4).len(), 4);
+        // The empty friends list.
+        assert_eq!(next_events(&mut client, 1).len(), 1);
+
+        server.dispatch(
+            "ACTIVITY_JOIN_REQUEST",
+            json!({ "user": { "id": "80351110224678912", "username": "luigi" } }),
+        );
+        server.dispatch("ACTIVITY_JOIN", json!({ "secret": "room-7" }));
+        let luigi = UserId::new(80_351_110_224_678_912);
+        assert_eq!(
+            next_events(&mut client, 2),
+            [
+                PresenceEvent::JoinRequest(User::new(luigi, String::from("luigi"))),
+                PresenceEvent::Join(String::from("room-7")),
+            ]
+        );
+
+        client.reply_to_join(luigi, JoinReply::Accept).unwrap();
+        client.reply_to_join(luigi, JoinReply::Decline).unwrap();
+        assert_eq!(
+            server.wait_for_commands("SEND_ACTIVITY_JOIN_INVITE", 1),
+            [json!({ "user_id": "80351110224678912" })]
+        );
+        assert_eq!(
+            server.wait_for_commands("CLOSE_ACTIVITY_REQUEST", 1).len(),
+            1
+        );
+    }
+
+    #[test]
+    fn friends_are_listed_and_invites_report_delivery() {
+        let server = FakeDiscordServer::start().unwrap();
+        let relationship = |id: &str, name: &str, kind: i64, status: &str| {
+            json!({
+                "type": kind,
+                "user": { "id": id, "username": name },
+                "presence": { "status": status },
+            })
+        };
+        server.set_relationships(vec![
+            relationship("1", "luigi", 1, "online"),
+            relationship("2", "bowser", 2, "online"),
+            relationship("3", "peach", 1, "dnd"),
+        ]);
+        let mut client = DiscordIpcPresence::connect_to(server.path(), 42).unwrap();
+        let friend = |id, name: &str, status| {
+            Friend::new(User::new(UserId::new(id), name.to_string()), status)
+        };
+
+        assert_eq!(
+            next_events(&mut client, 1),
+            [PresenceEvent::Friends(vec![
+                friend(1, "luigi", OnlineStatus::Online),
+                friend(3, "peach", OnlineStatus::DoNotDisturb),
+            ])]
+        );
+        let mut playing = relationship("3", "peach", 1, "idle");
+        playing["presence"]["activity"] = json!({ "name": "Console Player", "state": "Zelda" });
+        server.dispatch("RELATIONSHIP_UPDATE", playing);
+        server.dispatch(
+            "RELATIONSHIP_UPDATE",
+            relationship("1", "luigi", 2, "online"),
+        );
+        assert_eq!(
+            next_events(&mut client, 2),
+            [
+                PresenceEvent::FriendUpdate(friend(3, "peach", OnlineStatus::Idle).with_activity(
+                    Some(FriendActivity::new(
+                        String::from("Console Player"),
+                        String::from("Zelda"),
+                        String::new()
+                    ))
+                )),
+                PresenceEvent::FriendRemoved(UserId::new(1)),
+            ]
+        );
+
+        client
+            .send_invite(UserId::new(3), InviteKind::Spectate)
+            .unwrap();
+        assert_eq!(
+            next_events(&mut client, 1),
+            [PresenceEvent::InviteSent(UserId::new(3))]
+        );
+        server.fail_next(ServerFault::Reject {
+            code: 5000,
+            message: String::from("no secrets"),
+        });
+        client
+            .send_invite(UserId::new(3), InviteKind::Join)
+            .unwrap();
+        assert_eq!(
+            next_events(&mut client, 1),
+            [PresenceEvent::InviteFailed(
+                UserId::new(3),
+                String::from("no secrets (5000)")
+            )]
+        );
+        // Rejected commands are not recorded.
+        assert_eq!(
+            server.wait_for_commands("ACTIVITY_INVITE_USER", 1),
+            [json!({ "user_id": "3", "type": 2, "content": "", "pid": std::process::id() })]
+        );
+        assert_eq!(client.status(), PresenceStatus::Connected);
+    }
+
+    #[test]
+    fn lobbies_are_opened_chatted_in_and_left() {
+        let server = FakeDiscordServer::start().unwrap();
+        let mut client = DiscordIpcPresence::connect_to(server.path(), 42).unwrap();
+        next_events(&mut client, 1);
+
+        client
+            .lobby(LobbyCommand::Create {
+                capacity: 2,
+                game: String::from("Mario Kart 8"),
+            })
+            .unwrap();
+        let id = LobbyId::new(FAKE_LOBBY_ID.parse().unwrap());
+        assert_eq!(
+            next_events(&mut client, 1),
+            [PresenceEvent::LobbyJoined(Lobby::new(
+                id,
+                format!("{FAKE_LOBBY_ID}:{FAKE_LOBBY_SECRET}"),
+                2
+            ))]
+        );
+        assert_eq!(
+            server.wait_for_commands("CREATE_LOBBY", 1),
+            [json!({ "type": 1, "capacity": 2, "metadata": { "game": "Mario Kart 8" } })]
+        );
+        // The lobby events are subscribed to on top of the ones while connecting.
+        assert_eq!(
+            server.wait_for_commands("SUBSCRIBE", 8)[4..],
+            vec![json!({ "lobby_id": FAKE_LOBBY_ID }); 4]
+        );
+
+        server.dispatch(
+            "LOBBY_MEMBER_CONNECT",
+            json!({ "lobby_id": "1", "member": { "user": { "id": "7", "username": "luigi" } } }),
+        );
+        server.dispatch(
+            "LOBBY_MESSAGE",
+            json!({ "lobby_id": "1", "sender_id": "7", "data": "go!" }),
+        );
+        server.dispatch(
+            "LOBBY_MESSAGE",
+            json!({ "lobby_id": "1", "sender_id": "7", "data": [0xff, 0xfe, 300] }),
+        );
+        assert_eq!(
+            next_events(&mut client, 3),
+            [
+                PresenceEvent::LobbyMemberJoined(
+                    id,
+                    User::new(UserId::new(7), String::from("luigi"))
+                ),
+                PresenceEvent::LobbyMessage(
+                    id,
+                    UserId::new(7),
+                    LobbyMessage::Text(String::from("go!"))
+                ),
+                PresenceEvent::LobbyMessage(id, UserId::new(7), LobbyMessage::Binary(2)),
+            ]
+        );
+
+        client
+            .lobby(LobbyCommand::Send(id, String::from("wait")))
+            .unwrap();
+        client.lobby(LobbyCommand::Leave(id)).unwrap();
+        assert_eq!(
+            next_events(&mut client, 1),
+            [PresenceEvent::LobbyClosed(id)]
+        );
+        assert_eq!(
+            server.wait_for_commands("SEND_TO_LOBBY", 1),
+            [json!({ "lobby_id": "1", "data": "wait" })]
+        );
+        assert!(
+            client
+                .lobby(LobbyCommand::Join(String::from("no-lobby")))
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn activity_payload_skips_missing_fields() {
+        let presence = Presence::builder()
+            .state(String::from("Mario Kart 8"))
+            .details(String::new())
+            .small_image(Image::from(String::from("switch")))
+            .small_tooltip(Tooltip::from(String::from("Nintendo Switch")))
+            .start(DateTime::from_timestamp(1_700_000_000, 0).unwrap())
+            .party(Party::new("couch", 2, 4).unwrap())
+            .secrets("join=room-42".parse().unwrap())
+            .build();
+
+        let payload = serde_json::to_value(Activity::from(&presence)).unwrap();
+
+        assert_eq!(
+            payload,
+            json!({
+                "state": "Mario Kart 8",
+                "assets": { "small_image": "switch", "small_text": "Nintendo Switch" },
+                "timestamps": { "start": 1_700_000_000 },
+                "party": { "id": "couch", "size": [2, 4] },
+                "secrets": { "join": "room-42" },
+            })
+        );
+    }
+}
@@ -0,0 +1,361 @@
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
+};
+
+use discord_game_sdk::{Action, Activity, Discord, LobbyKind, LobbyTransaction, RequestReply};
+use log::{error, info};
+
+use crate::{
+    domain::{
+        game::{Image, Secret, Tooltip},
+        presence::{
+            InviteKind, JoinReply, Presence, PresenceError, PresenceEvent, PresencePublisher,
+            PresenceStatus, UserId,
+            lobby::{Lobby, LobbyCommand, LobbyId},
+        },
+    },
+    event_handler::{EventHandler, look_up_member},
+};
+
+/// `PresencePublisher` backed by the Discord Game SDK, which talks to the local Discord client.
+#[derive(Debug)]
+pub struct DiscordPresence {
+    discord: Discord<'static, EventHandler>,
+    health: Rc<Health>,
+    /// The event handler's queue, which the callbacks of invites and lobby requests add to.
+    events: Queue,
+}
+
+type Queue = Rc<RefCell<Vec<PresenceEvent>>>;
+
+/// How the SDK fared since the last poll, kept up to date by the request callbacks.
+#[derive(Debug)]
+struct Health {
+    status: Cell<PresenceStatus>,
+    /// Errors the SDK reported for requests, oldest first.
+    failures: RefCell<Vec<discord_game_sdk::Error>>,
+}
+
+impl Health {
+    const fn new() -> Self {
+        Self {
+            status: Cell::new(PresenceStatus::Connected),
+            failures: RefCell::new(Vec::new()),
+        }
+    }
+
+    /// Keeps the error of a request for the next poll. An error saying that Discord is gone
+    /// takes the connection offline, so that it is reopened.
+    fn failed(&self, err: discord_game_sdk::Error) {
+        if lost_discord(&err) {
+            self.status.set(PresenceStatus::Offline);
+        }
+        self.failures.borrow_mut().push(err);
+    }
+
+    /// The outcome of a poll that ran the callbacks with `result`.
+    fn polled(&self, result: discord_game_sdk::Result<()>) -> Result<(), PresenceError> {
+        if let Err(err) = result {
+            self.status.set(PresenceStatus::Offline);
+            return Err(anyhow::Error::from(err)
+                .context("failed to run Discord callbacks")
+                .into());
+        }
+
+        // Only the latest failure is returned, the ones before it are logged.
+        let mut failures = self.failures.take();
+        let Some(latest) = failures.pop() else {
+            return Ok(());
+        };
+        for err in failures {
+            error!("Discord rejected a request: {err}");
+        }
+
+        Err(anyhow::Error::from(latest)
+            .context("Discord rejected a request")
+            .into())
+    }
+}
+
+/// Whether `err` means the Discord client went away rather than that it refused a request.
+const fn lost_discord(err: &discord_game_sdk::Error) -> bool {
+    matches!(
+        err,
+        discord_game_sdk::Error::ServiceUnavailable
+            | discord_game_sdk::Error::NotRunning
+            | discord_game_sdk::Error::Internal
+    )
+}
+
+impl DiscordPresence {
+    /// Connects as the Discord application `client_id`. Fails if Discord is not running.
+    pub fn connect(client_id: i64) -> Result<Self, crate::Error> {
+        info!("init discord");
+        let mut discord = Discord::new(client_id)?;
+        let handler = EventHandler::default();
+        let events = handler.queue();
+        *discord.event_handler_mut() = Some(handler);
+
+        Ok(Self {
+            discord,
+            health: Rc::new(Health::new()),
+            events,
+        })
+    }
+
+    /// Callback that keeps the error of a finished request for the next poll.
+    fn on_done(
+        &self,
+    ) -> impl FnOnce(&Discord<'static, EventHandler>, discord_game_sdk::Result<()>) + 'static {
+        let health = Rc::clone(&self.health);
+        move |_discord, result| {
+            if let Err(err) = result {
+                health.failed(err);
+            }
+        }
+    }
+}
+
+impl PresencePublisher for DiscordPresence {
+    fn set(&mut self, presence: &Presence) -> Result<(), PresenceError> {
+        self.discord
+            .update_activity(&Activity::from(presence), self.on_done());
+
+        Ok(())
+    }
+
+    fn clear(&mut self) -> Result<(), PresenceError> {
+        self.discord.clear_activity(self.on_done());
+
+        Ok(())
+    }
+
+    fn status(&self) -> PresenceStatus {
+        self.health.status.get()
+    }
+
+    fn poll(&mut self) -> Result<(), PresenceError> {
+        let result = self.discord.run_callbacks();
+        self.health.polled(result)
+    }
+
+    fn take_events(&mut self) -> Vec<PresenceEvent> {
+        self.events.take()
+    }
+
+    fn reply_to_join(&mut self, user: UserId, reply: JoinReply) -> Result<(), PresenceError> {
+        let reply = match reply {
+            JoinReply::Accept => RequestReply::Yes,
+            JoinReply::Decline => RequestReply::No,
+        };
+        self.discord
+            .send_request_reply(user.get(), reply, self.on_done());
+
+        Ok(())
+    }
+
+    fn send_invite(&mut self, user: UserId, kind: InviteKind) -> Result<(), PresenceError> {
+        let action = match kind {
+            InviteKind::Join => Action::Join,
+            InviteKind::Spectate => Action::Spectate,
+        };
+        let events = Rc::clone(&self.events);
+        self.discord
+            .send_invite(user.get(), action, "", move |_discord, result| {
+                events.borrow_mut().push(match result {
+                    Ok(()) => PresenceEvent::InviteSent(user),
+                    Err(err) => PresenceEvent::InviteFailed(user, err.to_string()),
+                });
+            });
+
+        Ok(())
+    }
+
+    fn lobby(&mut self, command: LobbyCommand) -> Result<(), PresenceError> {
+        let events = Rc::clone(&self.events);
+        let on_lobby =
+            move |discord: &Discord<'_, EventHandler>,
+                  result: discord_game_sdk::Result<&discord_game_sdk::Lobby>| {
+                match result {
+                    Ok(lobby) => joined(discord, lobby, &events),
+                    Err(err) => events
+                        .borrow_mut()
+                        .push(PresenceEvent::LobbyFailed(err.to_string())),
+                }
+            };
+
+        match command {
+            LobbyCommand::Create { capacity, game } => {
+                let mut transaction = LobbyTransaction::new();
+                transaction
+                    .kind(LobbyKind::Private)
+                    .capacity(capacity)
+                    .add_metadata(String::from("game"), game);
+                self.discord.create_lobby(&transaction, on_lobby);
+            }
+            LobbyCommand::Join(secret) => {
+                self.discord
+                    .connect_lobby_with_activity_secret(secret, on_lobby);
+            }
+            LobbyCommand::Leave(id) => {
+                let events = Rc::clone(&self.events);
+                self.discord
+                    .disconnect_lobby(id.get(), move |_discord, result| {
+                        events.borrow_mut().push(match result {
+                            Ok(()) => PresenceEvent::LobbyClosed(id),
+                            Err(err) => PresenceEvent::LobbyFailed(err.to_string()),
+                        });
+                    });
+            }
+            LobbyCommand::Send(id, text) => {
+                let events = Rc::clone(&self.events);
+                self.discord
+                    .send_lobby_message(id.get(), text, move |_discord, result| {
+                        if let Err(err) = result {
+                            events.borrow_mut().push(PresenceEvent::LobbyFailed(format!(
+                                "cannot send the message: {err}"
+                            )));
+                        }
+                    });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Queues `lobby` as joined and looks its members up.
+fn joined(discord: &Discord<'_, EventHandler>, lobby: &discord_game_sdk::Lobby, events: &Queue) {
+    let id = lobby.id();
+    let secret = discord
+        .lobby_activity_secret(id)
+        .unwrap_or_else(|_| format!("{id}:{}", lobby.secret()));
+    events
+        .borrow_mut()
+        .push(PresenceEvent::LobbyJoined(Lobby::new(
+            LobbyId::new(id),
+            secret,
+            lobby.capacity(),
+        )));
+
+    match discord.iter_lobby_member_ids(id) {
+        Ok(members) => {
+            for member in members.flatten() {
+                look_up_member(discord, id, member, Rc::clone(events));
+            }
+        }
+        Err(err) => error!("cannot list the members of lobby {id}: {err}"),
+    }
+}
+
+impl From<&Presence> for Activity {
+    fn from(presence: &Presence) -> Self {
+        let mut activity = Activity::empty();
+        activity
+            .with_state(presence.state())
+            .with_details(presence.details());
+
+        if let Some(key) = presence.large_image().map(Image::as_str) {
+            activity.with_large_image_key(key);
+        }
+        if let Some(tooltip) = presence.large_tooltip().map(Tooltip::as_str) {
+            activity.with_large_image_tooltip(tooltip);
+        }
+        if let Some(key) = presence.small_image().map(Image::as_str) {
+            activity.with_small_image_key(key);
+        }
+        if let Some(tooltip) = presence.small_tooltip().map(Tooltip::as_str) {
+            activity.with_small_image_tooltip(tooltip);
+        }
+        if let Some(start) = presence.start() {
+            activity.with_start_time(start.timestamp());
+        }
+        if let Some(party) = presence.party() {
+            activity
+                .with_party_id(party.id())
+                .with_party_amount(party.size())
+                .with_party_capacity(party.max());
+        }
+        if let Some(secret) = presence.secrets().join().map(Secret::as_str) {
+            activity.with_join_secret(secret);
+        }
+        if let Some(secret) = presence.secrets().spectate().map(Secret::as_str) {
+            activity.with_spectate_secret(secret);
+        }
+
+        activity
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use discord_game_sdk::{Activity, Error};
+
+    use crate::{
+        domain::{
+            game::{Game, GameId, Image, Name, Platform, Tooltip},
+            presence::{Presence, PresenceStatus},
+        },
+        outbound::discord_presence::Health,
+    };
+    use pretty_assertions::{assert_eq, assert_str_eq};
+
+    #[test]
+    fn failures_that_lose_discord_take_it_offline() {
+        let health = Health::new();
+
+        health.failed(Error::InvalidPayload);
+        health.failed(Error::RateLimited);
+        assert!(health.polled(Ok(())).is_err());
+        assert!(health.polled(Ok(())).is_ok());
+        assert_eq!(health.status.get(), PresenceStatus::Connected);
+
+        health.failed(Error::NotRunning);
+        assert!(health.polled(Ok(())).is_err());
+        assert_eq!(health.status.get(), PresenceStatus::Offline);
+
+        let health = Health::new();
+        assert!(health.polled(Err(Error::ServiceUnavailable)).is_err());
+        assert_eq!(health.status.get(), PresenceStatus::Offline);
+    }
+
+    #[test]
+    fn activity_from_game() {
+        let name = "mario";
+        let small_key = "small_key";
+        let small_tooltip = "small_tooltip";
+        let large_key = "large_key";
+        let large_tooltip = "large_tooltip";
+        let platform = "Nintendo Switch";
+
+        let small_image = Game::builder()
+            .id(GameId::generate())
+            .name(Name::new(name).unwrap())
+            .small_image(Image::from(small_key.to_string()))
+            .small_tooltip(Tooltip::from(small_tooltip.to_string()))
+            .platform(Platform::new(platform).unwrap())
+            .build();
+        let small_image = Activity::from(&Presence::from(&small_image));
+
+        let large_image = Game::builder()
+            .id(GameId::generate())
+            .name(Name::new(name).unwrap())
+            .large_image(Image::from(large_key.to_string()))
+            .large_tooltip(Tooltip::from(large_tooltip.to_string()))
+            .platform(Platform::new(platform).unwrap())
+            .build();
+        let large_image = Activity::from(&Presence::from(&large_image));
+
+        assert_str_eq!(name, small_image.state());
+        assert_str_eq!(platform, small_image.details());
+        assert_str_eq!(small_key, small_image.small_image_key());
+        assert_str_eq!(small_tooltip, small_image.small_image_tooltip());
+        assert_str_eq!("", small_image.large_image_key());
+
+        assert_str_eq!(name, large_image.state());
+        assert_str_eq!(large_key, large_image.large_image_key());
+        assert_str_eq!(large_tooltip, large_image.large_image_tooltip());
+        assert_str_eq!("", large_image.small_image_key());
+    }
+}
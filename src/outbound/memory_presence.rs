@@ -0,0 +1,156 @@
+use std::{collections::VecDeque, io};
+
+use crate::domain::presence::{
+    InviteKind, JoinReply, Presence, PresenceError, PresenceEvent, PresencePublisher,
+    PresenceStatus, UserId,
+    lobby::{Lobby, LobbyCommand, LobbyId},
+};
+
+/// `PresencePublisher` that only remembers what it was asked to show.
+///
+/// Stands in for Discord when it is not available, so the app keeps working offline, and lets
+/// tests inspect what would have been published. Anything that needs other users, such as
+/// invites and lobbies, fails with [`PresenceError::NotConnected`] unless it is
+/// [`InMemoryPresence::scripted`].
+#[derive(Debug, Default)]
+pub struct InMemoryPresence {
+    current: Option<Presence>,
+    published: Vec<Option<Presence>>,
+    failures: usize,
+    events: VecDeque<PresenceEvent>,
+    replies: Vec<(UserId, JoinReply)>,
+    invites: Vec<(UserId, InviteKind)>,
+    lobby_commands: Vec<LobbyCommand>,
+    /// Whether it plays a connected service for tests.
+    scripted: bool,
+}
+
+impl InMemoryPresence {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Test double that acts as if other users were reachable: invites are delivered and lobbies
+    /// opened, joined and left right away.
+    #[cfg(any(test, feature = "test-support"))]
+    pub fn scripted() -> Self {
+        Self {
+            scripted: true,
+            ..Self::default()
+        }
+    }
+
+    /// Makes the next `set` or `clear` fail without changing anything.
+    pub const fn fail_next(&mut self) {
+        self.failures += 1;
+    }
+
+    /// The presence currently shown, `None` after a clear.
+    pub const fn current(&self) -> Option<&Presence> {
+        self.current.as_ref()
+    }
+
+    /// Every presence published so far, in order, with `None` for each clear.
+    pub fn published(&self) -> &[Option<Presence>] {
+        &self.published
+    }
+
+    /// Queues `event` as if the service had sent it.
+    pub fn push_event(&mut self, event: PresenceEvent) {
+        self.events.push_back(event);
+    }
+
+    /// Every reply to a join request so far, in order.
+    pub fn replies(&self) -> &[(UserId, JoinReply)] {
+        &self.replies
+    }
+
+    /// Every lobby command so far, in order.
+    pub fn lobby_commands(&self) -> &[LobbyCommand] {
+        &self.lobby_commands
+    }
+
+    /// Every invite sent so far, in order.
+    pub fn invites(&self) -> &[(UserId, InviteKind)] {
+        &self.invites
+    }
+
+    fn publish(&mut self, presence: Option<Presence>) -> Result<(), PresenceError> {
+        if self.failures > 0 {
+            self.failures -= 1;
+            return Err(anyhow::Error::from(io::Error::other("injected presence failure")).into());
+        }
+
+        self.current.clone_from(&presence);
+        self.published.push(presence);
+
+        Ok(())
+    }
+}
+
+impl PresencePublisher for InMemoryPresence {
+    fn set(&mut self, presence: &Presence) -> Result<(), PresenceError> {
+        self.publish(Some(presence.clone()))
+    }
+
+    fn clear(&mut self) -> Result<(), PresenceError> {
+        self.publish(None)
+    }
+
+    fn status(&self) -> PresenceStatus {
+        PresenceStatus::Offline
+    }
+
+    fn poll(&mut self) -> Result<(), PresenceError> {
+        Ok(())
+    }
+
+    fn take_events(&mut self) -> Vec<PresenceEvent> {
+        self.events.drain(..).collect()
+    }
+
+    fn reply_to_join(&mut self, user: UserId, reply: JoinReply) -> Result<(), PresenceError> {
+        self.replies.push((user, reply));
+        Ok(())
+    }
+
+    /// Invites are delivered right away when scripted, there is no one to send them to
+    /// otherwise.
+    fn send_invite(&mut self, user: UserId, kind: InviteKind) -> Result<(), PresenceError> {
+        if !self.scripted {
+            return Err(PresenceError::NotConnected);
+        }
+        self.invites.push((user, kind));
+        self.events.push_back(PresenceEvent::InviteSent(user));
+        Ok(())
+    }
+
+    /// Lobbies are opened, joined and left right away when scripted, and cannot be reached
+    /// otherwise. Lobbies that are opened get the next free id and no members, joined ones the id
+    /// their secret names.
+    fn lobby(&mut self, command: LobbyCommand) -> Result<(), PresenceError> {
+        if !self.scripted {
+            return Err(PresenceError::NotConnected);
+        }
+        let event = match &command {
+            LobbyCommand::Create { capacity, .. } => {
+                let id = self.lobby_commands.len() as i64 + 1;
+                let lobby = Lobby::new(LobbyId::new(id), format!("{id}:secret"), *capacity);
+                Some(PresenceEvent::LobbyJoined(lobby))
+            }
+            LobbyCommand::Join(secret) => {
+                let id = secret
+                    .split_once(':')
+                    .and_then(|(id, _)| id.parse().ok())
+                    .unwrap_or_default();
+                let lobby = Lobby::new(LobbyId::new(id), secret.clone(), 0);
+                Some(PresenceEvent::LobbyJoined(lobby))
+            }
+            LobbyCommand::Leave(id) => Some(PresenceEvent::LobbyClosed(*id)),
+            LobbyCommand::Send(..) => None,
+        };
+        self.events.extend(event);
+        self.lobby_commands.push(command);
+        Ok(())
+    }
+}
@@ -0,0 +1,318 @@
+use std::{cell::RefCell, collections::VecDeque, io, mem};
+
+use crate::domain::{
+    game::{
+        Game, GameCreateError, GameCreateRequest, GameDeleteError, GameDeleteRequest, GameGetError,
+        GameId, GameListError, GameRepository, GameUpdateError, GameUpdateRequest, Name, Platform,
+        Playtime,
+    },
+    platform::{
+        PlatformCreateError, PlatformDefinition, PlatformDeleteError, PlatformGetError,
+        PlatformListError, PlatformRepository, PlatformUpdateError, PlatformUpdateRequest,
+    },
+};
+
+/// Failure that [`InMemoryGameRepository`] reports instead of performing an operation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Fault {
+    /// The game already exists. Operations without such an error fail with [`Fault::Io`].
+    Duplicate,
+    /// The game does not exist. Operations without such an error fail with [`Fault::Io`].
+    NotFound,
+    /// The storage could not be read or written.
+    Io,
+}
+
+/// `GameRepository` that keeps everything in memory and never touches the filesystem.
+///
+/// Faults queued with [`InMemoryGameRepository::fail_next`] are consumed one per operation, in
+/// order, which lets tests simulate storage errors at exactly the step they care about.
+#[derive(Debug, Default)]
+pub struct InMemoryGameRepository {
+    games: RefCell<Vec<Game>>,
+    platforms: RefCell<Vec<PlatformDefinition>>,
+    faults: RefCell<VecDeque<Fault>>,
+}
+
+impl InMemoryGameRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Makes the next operation fail with `fault`.
+    pub fn fail_next(&self, fault: Fault) {
+        self.faults.borrow_mut().push_back(fault);
+    }
+
+    fn take_fault(&self) -> Option<Fault> {
+        self.faults.borrow_mut().pop_front()
+    }
+
+    fn io_error() -> anyhow::Error {
+        anyhow::Error::from(io::Error::other("injected I/O failure"))
+    }
+
+    fn position(&self, id: GameId) -> Option<usize> {
+        self.games.borrow().iter().position(|game| game.id() == id)
+    }
+
+    fn find(&self, name: &Name) -> Option<Game> {
+        self.games
+            .borrow()
+            .iter()
+            .find(|game| game.name() == name)
+            .cloned()
+    }
+
+    fn platform_position(&self, name: &Platform) -> Option<usize> {
+        self.platforms
+            .borrow()
+            .iter()
+            .position(|platform| platform.name() == name)
+    }
+}
+
+impl FromIterator<Game> for InMemoryGameRepository {
+    fn from_iter<T: IntoIterator<Item = Game>>(iter: T) -> Self {
+        Self {
+            games: RefCell::new(iter.into_iter().collect()),
+            platforms: RefCell::default(),
+            faults: RefCell::default(),
+        }
+    }
+}
+
+impl GameRepository for InMemoryGameRepository {
+    fn create_game(&self, req: &GameCreateRequest) -> Result<Game, GameCreateError> {
+        let duplicate = GameCreateError::Duplicate {
+            game_name: req.name().clone(),
+        };
+
+        match self.take_fault() {
+            Some(Fault::Duplicate) => return Err(duplicate),
+            Some(Fault::NotFound | Fault::Io) => return Err(Self::io_error().into()),
+            None => {}
+        }
+
+        if self.find(req.name()).is_some() {
+            return Err(duplicate);
+        }
+
+        let game = Game::new(GameId::generate(), req);
+        self.games.borrow_mut().push(game.clone());
+
+        Ok(game)
+    }
+
+    fn restore_game(&self, game: &Game) -> Result<Game, GameCreateError> {
+        let duplicate = GameCreateError::Duplicate {
+            game_name: game.name().clone(),
+        };
+
+        match self.take_fault() {
+            Some(Fault::Duplicate) => return Err(duplicate),
+            Some(Fault::NotFound | Fault::Io) => return Err(Self::io_error().into()),
+            None => {}
+        }
+
+        if self.find(game.name()).is_some() || self.position(game.id()).is_some() {
+            return Err(duplicate);
+        }
+
+        self.games.borrow_mut().push(game.clone());
+
+        Ok(game.clone())
+    }
+
+    fn update_game(&self, req: &GameUpdateRequest) -> Result<Game, GameUpdateError> {
+        let not_found = || GameUpdateError::NotFound { id: req.id() };
+
+        match self.take_fault() {
+            Some(Fault::Duplicate) => {
+                return Err(GameUpdateError::Duplicate {
+                    game_name: req.game().name().clone(),
+                });
+            }
+            Some(Fault::NotFound) => return Err(not_found()),
+            Some(Fault::Io) => return Err(Self::io_error().into()),
+            None => {}
+        }
+
+        let index = self.position(req.id()).ok_or_else(not_found)?;
+
+        let mut games = self.games.borrow_mut();
+        let game = Game::new(req.id(), req.game()).with_playtime(games[index].playtime());
+        games[index] = game.clone();
+
+        Ok(game)
+    }
+
+    fn add_playtime(&self, id: GameId, played: Playtime) -> Result<Game, GameUpdateError> {
+        match self.take_fault() {
+            Some(Fault::NotFound) => return Err(GameUpdateError::NotFound { id }),
+            Some(Fault::Duplicate | Fault::Io) => return Err(Self::io_error().into()),
+            None => {}
+        }
+
+        let index = self.position(id).ok_or(GameUpdateError::NotFound { id })?;
+
+        let mut games = self.games.borrow_mut();
+        let playtime = games[index].playtime() + played;
+        games[index] = games[index].clone().with_playtime(playtime);
+
+        Ok(games[index].clone())
+    }
+
+    fn delete_game(&self, req: &GameDeleteRequest) -> Result<(), GameDeleteError> {
+        let not_found = || GameDeleteError::NotFound { id: req.id() };
+
+        match self.take_fault() {
+            Some(Fault::NotFound) => return Err(not_found()),
+            Some(Fault::Duplicate | Fault::Io) => return Err(Self::io_error().into()),
+            None => {}
+        }
+
+        let index = self.position(req.id()).ok_or_else(not_found)?;
+        self.games.borrow_mut().remove(index);
+
+        Ok(())
+    }
+
+    fn get_game(&self, id: GameId) -> Result<Game, GameGetError> {
+        let not_found = || GameGetError::NotFound { id };
+
+        match self.take_fault() {
+            Some(Fault::NotFound) => return Err(not_found()),
+            Some(Fault::Duplicate | Fault::Io) => return Err(Self::io_error().into()),
+            None => {}
+        }
+
+        let index = self.position(id).ok_or_else(not_found)?;
+
+        Ok(self.games.borrow()[index].clone())
+    }
+
+    fn find_game_by_name(&self, name: &Name) -> Result<Option<Game>, GameGetError> {
+        match self.take_fault() {
+            Some(Fault::NotFound) => return Ok(None),
+            Some(Fault::Duplicate | Fault::Io) => return Err(Self::io_error().into()),
+            None => {}
+        }
+
+        Ok(self.find(name))
+    }
+
+    fn list_games(&self) -> Result<Vec<Game>, GameListError> {
+        if self.take_fault().is_some() {
+            return Err(Self::io_error().into());
+        }
+
+        Ok(self.games.borrow().clone())
+    }
+}
+
+impl PlatformRepository for InMemoryGameRepository {
+    fn create_platform(
+        &self,
+        platform: &PlatformDefinition,
+    ) -> Result<PlatformDefinition, PlatformCreateError> {
+        let duplicate = PlatformCreateError::Duplicate {
+            platform: platform.name().clone(),
+        };
+
+        match self.take_fault() {
+            Some(Fault::Duplicate) => return Err(duplicate),
+            Some(Fault::NotFound | Fault::Io) => return Err(Self::io_error().into()),
+            None => {}
+        }
+
+        if self.platform_position(platform.name()).is_some() {
+            return Err(duplicate);
+        }
+
+        self.platforms.borrow_mut().push(platform.clone());
+
+        Ok(platform.clone())
+    }
+
+    fn update_platform(
+        &self,
+        req: &PlatformUpdateRequest,
+    ) -> Result<PlatformDefinition, PlatformUpdateError> {
+        let not_found = || PlatformUpdateError::NotFound {
+            platform: req.name().clone(),
+        };
+
+        match self.take_fault() {
+            Some(Fault::Duplicate) => {
+                return Err(PlatformUpdateError::Duplicate {
+                    platform: req.platform().name().clone(),
+                });
+            }
+            Some(Fault::NotFound) => return Err(not_found()),
+            Some(Fault::Io) => return Err(Self::io_error().into()),
+            None => {}
+        }
+
+        let index = self.platform_position(req.name()).ok_or_else(not_found)?;
+        let old = mem::replace(
+            &mut self.platforms.borrow_mut()[index],
+            req.platform().clone(),
+        );
+
+        let renamed = req.platform().name();
+        for game in self.games.borrow_mut().iter_mut() {
+            if old.matches(game.platform()) {
+                *game = Game::new(
+                    game.id(),
+                    &GameCreateRequest::from(&*game).with_platform(renamed.clone()),
+                );
+            }
+        }
+
+        Ok(req.platform().clone())
+    }
+
+    fn delete_platform(&self, name: &Platform) -> Result<(), PlatformDeleteError> {
+        let not_found = || PlatformDeleteError::NotFound {
+            platform: name.clone(),
+        };
+
+        match self.take_fault() {
+            Some(Fault::NotFound) => return Err(not_found()),
+            Some(Fault::Duplicate | Fault::Io) => return Err(Self::io_error().into()),
+            None => {}
+        }
+
+        let index = self.platform_position(name).ok_or_else(not_found)?;
+        self.platforms.borrow_mut().remove(index);
+
+        Ok(())
+    }
+
+    fn find_platform(
+        &self,
+        platform: &Platform,
+    ) -> Result<Option<PlatformDefinition>, PlatformGetError> {
+        match self.take_fault() {
+            Some(Fault::NotFound) => return Ok(None),
+            Some(Fault::Duplicate | Fault::Io) => return Err(Self::io_error().into()),
+            None => {}
+        }
+
+        Ok(self
+            .platforms
+            .borrow()
+            .iter()
+            .find(|definition| definition.matches(platform))
+            .cloned())
+    }
+
+    fn list_platforms(&self) -> Result<Vec<PlatformDefinition>, PlatformListError> {
+        if self.take_fault().is_some() {
+            return Err(Self::io_error().into());
+        }
+
+        Ok(self.platforms.borrow().clone())
+    }
+}
@@ -0,0 +1,21 @@
+mod asset_manifest;
+mod command_hook;
+#[cfg(unix)]
+mod discord_ipc;
+mod discord_presence;
+mod memory_presence;
+mod memory_repository;
+mod sqlite_repository;
+mod toml_repository;
+
+pub use asset_manifest::load_asset_manifest;
+pub use command_hook::CommandHook;
+#[cfg(all(unix, any(test, feature = "test-support")))]
+pub use discord_ipc::fake_server::{FakeDiscordServer, ServerFault};
+#[cfg(unix)]
+pub use discord_ipc::{DiscordIpcPresence, IpcError};
+pub use discord_presence::DiscordPresence;
+pub use memory_presence::InMemoryPresence;
+pub use memory_repository::{Fault, InMemoryGameRepository};
+pub use sqlite_repository::SqliteGameRepository;
+pub use toml_repository::TomlGameRepository;
@@ -0,0 +1,961 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    path::Path,
+};
+
+use anyhow::Context;
+use log::{debug, info};
+use rusqlite::{Connection, OptionalExtension, Row, params};
+
+use crate::{
+    domain::{
+        Repository,
+        game::{
+            Collection, FieldName, Game, GameCreateError, GameCreateRequest, GameDeleteError,
+            GameDeleteRequest, GameGetError, GameId, GameListError, GameRepository,
+            GameUpdateError, GameUpdateRequest, Image, Name, Party, Platform, Playtime, Secret,
+            Secrets, Tag, Template, Tooltip,
+        },
+        platform::{
+            PlatformCreateError, PlatformDefinition, PlatformDeleteError, PlatformGetError,
+            PlatformListError, PlatformRepository, PlatformUpdateError, PlatformUpdateRequest,
+        },
+    },
+    error::GameError,
+};
+
+/// Schema migrations, applied in order. The index of the last applied migration plus one is
+/// stored in `PRAGMA user_version`, so new migrations must only ever be appended.
+const MIGRATIONS: &[&str] = &[
+    r#"
+CREATE TABLE games (
+    id            INTEGER PRIMARY KEY,
+    name          TEXT NOT NULL UNIQUE,
+    platform      TEXT NOT NULL,
+    large_image   TEXT,
+    large_tooltip TEXT,
+    small_image   TEXT,
+    small_tooltip TEXT
+);
+
+CREATE INDEX games_platform_idx ON games (platform);
+"#,
+    // Stable identifiers. Existing rows get a random version 4 UUID.
+    r#"
+ALTER TABLE games ADD COLUMN game_id TEXT;
+
+UPDATE games SET game_id =
+    lower(hex(randomblob(4))) || '-' ||
+    lower(hex(randomblob(2))) || '-4' ||
+    substr(lower(hex(randomblob(2))), 2) || '-' ||
+    substr('89ab', abs(random()) % 4 + 1, 1) || substr(lower(hex(randomblob(2))), 2) || '-' ||
+    lower(hex(randomblob(6)));
+
+CREATE UNIQUE INDEX games_game_id_idx ON games (game_id);
+"#,
+    // Platform registry. Names and aliases are compared case-insensitively.
+    r#"
+CREATE TABLE platforms (
+    id            INTEGER PRIMARY KEY,
+    name          TEXT NOT NULL UNIQUE COLLATE NOCASE,
+    small_image   TEXT,
+    small_tooltip TEXT
+);
+
+CREATE TABLE platform_aliases (
+    alias       TEXT NOT NULL UNIQUE COLLATE NOCASE,
+    platform_id INTEGER NOT NULL REFERENCES platforms (id)
+);
+"#,
+    // Tags and collections.
+    r#"
+CREATE TABLE game_tags (
+    game_id TEXT NOT NULL REFERENCES games (game_id),
+    tag     TEXT NOT NULL,
+    PRIMARY KEY (game_id, tag)
+);
+
+CREATE TABLE game_collections (
+    game_id    TEXT NOT NULL REFERENCES games (game_id),
+    collection TEXT NOT NULL,
+    PRIMARY KEY (game_id, collection)
+);
+
+CREATE INDEX game_tags_tag_idx ON game_tags (tag);
+CREATE INDEX game_collections_collection_idx ON game_collections (collection);
+"#,
+    // Per-game override of the elapsed time display. NULL follows the global setting.
+    r#"
+ALTER TABLE games ADD COLUMN show_elapsed INTEGER;
+"#,
+    // Party shown with the activity. Either all three columns are set or none is.
+    r#"
+ALTER TABLE games ADD COLUMN party_id TEXT;
+ALTER TABLE games ADD COLUMN party_size INTEGER;
+ALTER TABLE games ADD COLUMN party_max INTEGER;
+"#,
+    // Activity templates, custom fields for their placeholders and the total playtime.
+    r#"
+ALTER TABLE games ADD COLUMN state_template TEXT;
+ALTER TABLE games ADD COLUMN details_template TEXT;
+ALTER TABLE games ADD COLUMN playtime INTEGER NOT NULL DEFAULT 0;
+
+CREATE TABLE game_fields (
+    game_id TEXT NOT NULL REFERENCES games (game_id),
+    name    TEXT NOT NULL,
+    value   TEXT NOT NULL,
+    PRIMARY KEY (game_id, name)
+);
+"#,
+    // Secrets for joining and spectating through Discord.
+    r#"
+ALTER TABLE games ADD COLUMN join_secret TEXT;
+ALTER TABLE games ADD COLUMN spectate_secret TEXT;
+"#,
+];
+
+/// The columns of `games`, as a literal so [`GAME_SELECT`] can extend it.
+macro_rules! game_columns {
+    () => {
+        "game_id, name, platform, large_image, large_tooltip, small_image, small_tooltip, \
+        show_elapsed, party_id, party_size, party_max, state_template, details_template, \
+        playtime, join_secret, spectate_secret"
+    };
+}
+
+const GAME_COLUMNS: &str = game_columns!();
+
+/// [`GAME_COLUMNS`] plus the tags, collections and fields, each joined with
+/// [`LABEL_SEPARATOR`]. Field names are separated from their values by [`FIELD_SEPARATOR`].
+const GAME_SELECT: &str = concat!(
+    game_columns!(),
+    ", \
+    (SELECT group_concat(tag, char(31)) FROM game_tags t WHERE t.game_id = games.game_id) \
+    AS tags, \
+    (SELECT group_concat(collection, char(31)) FROM game_collections c \
+    WHERE c.game_id = games.game_id) AS collections, \
+    (SELECT group_concat(name || char(30) || value, char(31)) FROM game_fields f \
+    WHERE f.game_id = games.game_id) AS fields"
+);
+
+const LABEL_SEPARATOR: char = '\u{1f}';
+const FIELD_SEPARATOR: char = '\u{1e}';
+
+/// `GameRepository` backed by an embedded SQLite database.
+#[derive(Debug)]
+pub struct SqliteGameRepository {
+    connection: Connection,
+    created: bool,
+}
+
+impl SqliteGameRepository {
+    /// Opens the database at `path`, creating it and running pending migrations if necessary.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, GameError> {
+        let path = path.as_ref();
+        debug!("database path: {path:#?}");
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        Self::with_connection(Connection::open(path)?)
+    }
+
+    /// Opens a private database that lives only as long as the repository.
+    pub fn open_in_memory() -> Result<Self, GameError> {
+        Self::with_connection(Connection::open_in_memory()?)
+    }
+
+    fn with_connection(connection: Connection) -> Result<Self, GameError> {
+        let mut repository = Self {
+            connection,
+            created: false,
+        };
+        repository.created = repository.migrate()? == 0;
+
+        Ok(repository)
+    }
+
+    /// Runs every pending migration and returns the schema version found before.
+    fn migrate(&self) -> Result<usize, GameError> {
+        let version: usize = self
+            .connection
+            .query_row("PRAGMA user_version", [], |row| row.get(0))?;
+
+        for (index, migration) in MIGRATIONS.iter().enumerate().skip(version) {
+            info!("applying database migration {}", index + 1);
+            let tx = self.connection.unchecked_transaction()?;
+            tx.execute_batch(migration)?;
+            tx.pragma_update(None, "user_version", index + 1)?;
+            tx.commit()?;
+        }
+
+        Ok(version)
+    }
+
+    /// Whether the database was created when this repository was opened.
+    pub const fn is_new(&self) -> bool {
+        self.created
+    }
+
+    /// Copies every game and platform of `source` into this database, keeping game identifiers
+    /// and skipping names that already exist.
+    ///
+    /// Returns the number of imported games.
+    pub fn import_from(&self, source: &impl Repository) -> anyhow::Result<usize> {
+        for platform in source.list_platforms()? {
+            match self.create_platform(&platform) {
+                Ok(_) => {}
+                Err(PlatformCreateError::Duplicate { platform }) => {
+                    debug!("not importing platform {platform}: already exists")
+                }
+                Err(PlatformCreateError::Unknown(err)) => return Err(err),
+            }
+        }
+
+        let mut imported = 0;
+
+        for game in source.list_games()? {
+            match self.insert(&game) {
+                Ok(_) => imported += 1,
+                Err(GameCreateError::Duplicate { game_name }) => {
+                    debug!("not importing {game_name}: already exists")
+                }
+                Err(GameCreateError::Unknown(err)) => return Err(err),
+            }
+        }
+
+        Ok(imported)
+    }
+
+    fn insert(&self, game: &Game) -> Result<Game, GameCreateError> {
+        let result = (|| {
+            let tx = self.connection.unchecked_transaction()?;
+            tx.execute(
+                &format!(
+                    "INSERT INTO games ({GAME_COLUMNS}) \
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16)"
+                ),
+                params![
+                    game.id().to_string(),
+                    game.name().as_str(),
+                    game.platform().as_str(),
+                    game.large_image().map(Image::as_str),
+                    game.large_tooltip().map(Tooltip::as_str),
+                    game.small_image().map(Image::as_str),
+                    game.small_tooltip().map(Tooltip::as_str),
+                    game.show_elapsed(),
+                    game.party().map(Party::id),
+                    game.party().map(Party::size),
+                    game.party().map(Party::max),
+                    game.state().map(Template::as_str),
+                    game.details().map(Template::as_str),
+                    game.playtime().seconds(),
+                    game.secrets().join().map(Secret::as_str),
+                    game.secrets().spectate().map(Secret::as_str),
+                ],
+            )?;
+            Self::write_labels(&tx, game.id(), game.tags(), game.collections())?;
+            Self::write_fields(&tx, game.id(), game.fields())?;
+            tx.commit()
+        })();
+
+        match result {
+            Ok(()) => Ok(game.clone()),
+            Err(err) if Self::is_unique_violation(&err) => Err(GameCreateError::Duplicate {
+                game_name: game.name().clone(),
+            }),
+            Err(err) => Err(anyhow::Error::from(err)
+                .context(format!("failed to insert game {}", game.name()))
+                .into()),
+        }
+    }
+
+    /// Replaces the tags and collections stored for the game `id`.
+    fn write_labels(
+        connection: &Connection,
+        id: GameId,
+        tags: &BTreeSet<Tag>,
+        collections: &BTreeSet<Collection>,
+    ) -> rusqlite::Result<()> {
+        let id = id.to_string();
+        connection.execute("DELETE FROM game_tags WHERE game_id = ?1", params![id])?;
+        connection.execute(
+            "DELETE FROM game_collections WHERE game_id = ?1",
+            params![id],
+        )?;
+
+        let mut statement =
+            connection.prepare_cached("INSERT INTO game_tags (game_id, tag) VALUES (?1, ?2)")?;
+        for tag in tags {
+            statement.execute(params![id, tag.as_str()])?;
+        }
+        let mut statement = connection
+            .prepare_cached("INSERT INTO game_collections (game_id, collection) VALUES (?1, ?2)")?;
+        for collection in collections {
+            statement.execute(params![id, collection.as_str()])?;
+        }
+
+        Ok(())
+    }
+
+    /// Replaces the custom fields stored for the game `id`.
+    fn write_fields(
+        connection: &Connection,
+        id: GameId,
+        fields: &BTreeMap<FieldName, String>,
+    ) -> rusqlite::Result<()> {
+        let id = id.to_string();
+        connection.execute("DELETE FROM game_fields WHERE game_id = ?1", params![id])?;
+
+        let mut statement = connection
+            .prepare_cached("INSERT INTO game_fields (game_id, name, value) VALUES (?1, ?2, ?3)")?;
+        for (name, value) in fields {
+            statement.execute(params![id, name.as_str(), value])?;
+        }
+
+        Ok(())
+    }
+
+    fn row_to_game(row: &Row<'_>) -> rusqlite::Result<Game> {
+        let id: String = row.get("game_id")?;
+        let name: String = row.get("name")?;
+        let platform: String = row.get("platform")?;
+
+        let invalid = |index, err: &dyn std::fmt::Display| {
+            rusqlite::Error::FromSqlConversionFailure(
+                index,
+                rusqlite::types::Type::Text,
+                err.to_string().into(),
+            )
+        };
+
+        Ok(Game::builder()
+            .id(id.parse().map_err(|err| invalid(0, &err))?)
+            .name(Name::new(&name).map_err(|err| invalid(1, &err))?)
+            .platform(Platform::new(&platform).map_err(|err| invalid(2, &err))?)
+            .maybe_large_image(
+                row.get::<_, Option<String>>("large_image")?
+                    .map(Image::from),
+            )
+            .maybe_large_tooltip(
+                row.get::<_, Option<String>>("large_tooltip")?
+                    .map(Tooltip::from),
+            )
+            .maybe_small_image(
+                row.get::<_, Option<String>>("small_image")?
+                    .map(Image::from),
+            )
+            .maybe_small_tooltip(
+                row.get::<_, Option<String>>("small_tooltip")?
+                    .map(Tooltip::from),
+            )
+            .tags(
+                row.get::<_, Option<String>>("tags")?
+                    .iter()
+                    .flat_map(|tags| tags.split(LABEL_SEPARATOR))
+                    .map(|tag| Tag::new(tag).map_err(|err| invalid(16, &err)))
+                    .collect::<Result<_, _>>()?,
+            )
+            .collections(
+                row.get::<_, Option<String>>("collections")?
+                    .iter()
+                    .flat_map(|collections| collections.split(LABEL_SEPARATOR))
+                    .map(|collection| Collection::new(collection).map_err(|err| invalid(17, &err)))
+                    .collect::<Result<_, _>>()?,
+            )
+            .maybe_show_elapsed(row.get("show_elapsed")?)
+            .maybe_party(
+                match (
+                    row.get::<_, Option<String>>("party_id")?,
+                    row.get("party_size")?,
+                    row.get("party_max")?,
+                ) {
+                    (Some(id), Some(size), Some(max)) => {
+                        Some(Party::new(&id, size, max).map_err(|err| invalid(8, &err))?)
+                    }
+                    _ => None,
+                },
+            )
+            .maybe_state(
+                row.get::<_, Option<String>>("state_template")?
+                    .map(|raw| Template::new(&raw).map_err(|err| invalid(11, &err)))
+                    .transpose()?,
+            )
+            .maybe_details(
+                row.get::<_, Option<String>>("details_template")?
+                    .map(|raw| Template::new(&raw).map_err(|err| invalid(12, &err)))
+                    .transpose()?,
+            )
+            .fields(
+                row.get::<_, Option<String>>("fields")?
+                    .iter()
+                    .flat_map(|fields| fields.split(LABEL_SEPARATOR))
+                    .map(|field| {
+                        let (name, value) =
+                            field.split_once(FIELD_SEPARATOR).unwrap_or((field, ""));
+                        let name = FieldName::new(name).map_err(|err| invalid(18, &err))?;
+                        Ok((name, value.to_string()))
+                    })
+                    .collect::<rusqlite::Result<_>>()?,
+            )
+            .playtime(Playtime::from_seconds(row.get("playtime")?))
+            .secrets(Secrets::new(
+                row.get::<_, Option<String>>("join_secret")?
+                    .map(|raw| Secret::new(&raw).map_err(|err| invalid(14, &err)))
+                    .transpose()?,
+                row.get::<_, Option<String>>("spectate_secret")?
+                    .map(|raw| Secret::new(&raw).map_err(|err| invalid(15, &err)))
+                    .transpose()?,
+            ))
+            .build())
+    }
+
+    /// Inserts the aliases of `platform`, which is stored under `platform_id`.
+    fn insert_aliases(
+        connection: &Connection,
+        platform_id: i64,
+        platform: &PlatformDefinition,
+    ) -> rusqlite::Result<()> {
+        let mut statement = connection
+            .prepare_cached("INSERT INTO platform_aliases (alias, platform_id) VALUES (?1, ?2)")?;
+        for alias in platform.aliases() {
+            statement.execute(params![alias.as_str(), platform_id])?;
+        }
+
+        Ok(())
+    }
+
+    fn load_platform(&self, platform_id: i64) -> anyhow::Result<PlatformDefinition> {
+        let (name, small_image, small_tooltip): (String, Option<String>, Option<String>) =
+            self.connection.query_row(
+                "SELECT name, small_image, small_tooltip FROM platforms WHERE id = ?1",
+                params![platform_id],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )?;
+
+        let mut statement = self.connection.prepare_cached(
+            "SELECT alias FROM platform_aliases WHERE platform_id = ?1 ORDER BY rowid",
+        )?;
+        let aliases = statement
+            .query_map(params![platform_id], |row| row.get::<_, String>(0))?
+            .map(|alias| Ok(Platform::new(&alias?)?))
+            .collect::<anyhow::Result<_>>()?;
+
+        Ok(PlatformDefinition::builder()
+            .name(Platform::new(&name)?)
+            .aliases(aliases)
+            .maybe_small_image(small_image.map(Image::from))
+            .maybe_small_tooltip(small_tooltip.map(Tooltip::from))
+            .build())
+    }
+
+    fn platform_id(&self, name: &Platform) -> rusqlite::Result<Option<i64>> {
+        self.connection
+            .query_row(
+                "SELECT id FROM platforms WHERE name = ?1",
+                params![name.as_str()],
+                |row| row.get(0),
+            )
+            .optional()
+    }
+
+    const fn is_unique_violation(err: &rusqlite::Error) -> bool {
+        matches!(
+            err,
+            rusqlite::Error::SqliteFailure(e, _)
+                if e.extended_code == rusqlite::ffi::SQLITE_CONSTRAINT_UNIQUE
+        )
+    }
+}
+
+impl GameRepository for SqliteGameRepository {
+    fn create_game(&self, req: &GameCreateRequest) -> Result<Game, GameCreateError> {
+        self.insert(&Game::new(GameId::generate(), req))
+    }
+
+    fn restore_game(&self, game: &Game) -> Result<Game, GameCreateError> {
+        self.insert(game)
+    }
+
+    fn update_game(&self, req: &GameUpdateRequest) -> Result<Game, GameUpdateError> {
+        let game = req.game();
+        let result = (|| {
+            let tx = self.connection.unchecked_transaction()?;
+            let updated = tx.execute(
+                "UPDATE games SET name = ?1, platform = ?2, large_image = ?3, large_tooltip = ?4, \
+                 small_image = ?5, small_tooltip = ?6, show_elapsed = ?7, party_id = ?8, \
+                 party_size = ?9, party_max = ?10, state_template = ?11, details_template = ?12, \
+                 join_secret = ?13, spectate_secret = ?14 WHERE game_id = ?15",
+                params![
+                    game.name().as_str(),
+                    game.platform().as_str(),
+                    game.large_image().map(Image::as_str),
+                    game.large_tooltip().map(Tooltip::as_str),
+                    game.small_image().map(Image::as_str),
+                    game.small_tooltip().map(Tooltip::as_str),
+                    game.show_elapsed(),
+                    game.party().map(Party::id),
+                    game.party().map(Party::size),
+                    game.party().map(Party::max),
+                    game.state().map(Template::as_str),
+                    game.details().map(Template::as_str),
+                    game.secrets().join().map(Secret::as_str),
+                    game.secrets().spectate().map(Secret::as_str),
+                    req.id().to_string(),
+                ],
+            )?;
+            Self::write_labels(&tx, req.id(), game.tags(), game.collections())?;
+            Self::write_fields(&tx, req.id(), game.fields())?;
+            let playtime: u64 = tx.query_row(
+                "SELECT playtime FROM games WHERE game_id = ?1",
+                params![req.id().to_string()],
+                |row| row.get(0),
+            )?;
+            tx.commit()?;
+            Ok((updated, playtime))
+        })();
+
+        match result {
+            Ok((0, _)) | Err(rusqlite::Error::QueryReturnedNoRows) => {
+                Err(GameUpdateError::NotFound { id: req.id() })
+            }
+            Ok((_, playtime)) => {
+                Ok(Game::new(req.id(), game).with_playtime(Playtime::from_seconds(playtime)))
+            }
+            Err(err) if Self::is_unique_violation(&err) => Err(GameUpdateError::Duplicate {
+                game_name: game.name().clone(),
+            }),
+            Err(err) => Err(anyhow::Error::from(err)
+                .context(format!("failed to update game {}", req.id()))
+                .into()),
+        }
+    }
+
+    fn add_playtime(&self, id: GameId, played: Playtime) -> Result<Game, GameUpdateError> {
+        let updated = self
+            .connection
+            .execute(
+                "UPDATE games SET playtime = playtime + ?1 WHERE game_id = ?2",
+                params![played.seconds(), id.to_string()],
+            )
+            .with_context(|| format!("failed to add playtime to game {id}"))?;
+
+        if updated == 0 {
+            return Err(GameUpdateError::NotFound { id });
+        }
+
+        self.get_game(id).map_err(|err| match err {
+            GameGetError::NotFound { id } => GameUpdateError::NotFound { id },
+            GameGetError::Unknown(err) => err.into(),
+        })
+    }
+
+    fn delete_game(&self, req: &GameDeleteRequest) -> Result<(), GameDeleteError> {
+        let deleted = (|| {
+            let tx = self.connection.unchecked_transaction()?;
+            Self::write_labels(&tx, req.id(), &BTreeSet::new(), &BTreeSet::new())?;
+            Self::write_fields(&tx, req.id(), &BTreeMap::new())?;
+            let deleted = tx.execute(
+                "DELETE FROM games WHERE game_id = ?1",
+                params![req.id().to_string()],
+            )?;
+            tx.commit()?;
+            Ok::<_, rusqlite::Error>(deleted)
+        })()
+        .with_context(|| format!("failed to delete game {}", req.id()))?;
+
+        if deleted == 0 {
+            return Err(GameDeleteError::NotFound { id: req.id() });
+        }
+
+        Ok(())
+    }
+
+    fn get_game(&self, id: GameId) -> Result<Game, GameGetError> {
+        self.connection
+            .query_row(
+                &format!("SELECT {GAME_SELECT} FROM games WHERE game_id = ?1"),
+                params![id.to_string()],
+                Self::row_to_game,
+            )
+            .optional()
+            .with_context(|| format!("failed to load game {id}"))?
+            .ok_or(GameGetError::NotFound { id })
+    }
+
+    fn find_game_by_name(&self, name: &Name) -> Result<Option<Game>, GameGetError> {
+        let game = self
+            .connection
+            .query_row(
+                &format!("SELECT {GAME_SELECT} FROM games WHERE name = ?1"),
+                params![name.as_str()],
+                Self::row_to_game,
+            )
+            .optional()
+            .with_context(|| format!("failed to load game {name}"))?;
+
+        Ok(game)
+    }
+
+    fn list_games(&self) -> Result<Vec<Game>, GameListError> {
+        let mut statement = self
+            .connection
+            .prepare_cached(&format!("SELECT {GAME_SELECT} FROM games ORDER BY id"))
+            .context("failed to prepare game listing")?;
+
+        let games = statement
+            .query_map([], Self::row_to_game)
+            .context("failed to list games")?
+            .collect::<Result<_, _>>()
+            .context("failed to read game")?;
+
+        Ok(games)
+    }
+}
+
+impl PlatformRepository for SqliteGameRepository {
+    fn create_platform(
+        &self,
+        platform: &PlatformDefinition,
+    ) -> Result<PlatformDefinition, PlatformCreateError> {
+        let result = (|| {
+            let tx = self.connection.unchecked_transaction()?;
+            tx.execute(
+                "INSERT INTO platforms (name, small_image, small_tooltip) VALUES (?1, ?2, ?3)",
+                params![
+                    platform.name().as_str(),
+                    platform.small_image().map(Image::as_str),
+                    platform.small_tooltip().map(Tooltip::as_str),
+                ],
+            )?;
+            Self::insert_aliases(&tx, tx.last_insert_rowid(), platform)?;
+            tx.commit()
+        })();
+
+        match result {
+            Ok(()) => Ok(platform.clone()),
+            Err(err) if Self::is_unique_violation(&err) => Err(PlatformCreateError::Duplicate {
+                platform: platform.name().clone(),
+            }),
+            Err(err) => Err(anyhow::Error::from(err)
+                .context(format!("failed to insert platform {}", platform.name()))
+                .into()),
+        }
+    }
+
+    fn update_platform(
+        &self,
+        req: &PlatformUpdateRequest,
+    ) -> Result<PlatformDefinition, PlatformUpdateError> {
+        let platform = req.platform();
+        let platform_id = self
+            .platform_id(req.name())
+            .with_context(|| format!("failed to load platform {}", req.name()))?
+            .ok_or_else(|| PlatformUpdateError::NotFound {
+                platform: req.name().clone(),
+            })?;
+
+        let result = (|| {
+            let tx = self.connection.unchecked_transaction()?;
+            // Before the old name and aliases are replaced, so games spelled any way the old
+            // definition matched follow it.
+            tx.execute(
+                "UPDATE games SET platform = ?1 \
+                 WHERE platform COLLATE NOCASE IN ( \
+                     SELECT name FROM platforms WHERE id = ?2 \
+                     UNION SELECT alias FROM platform_aliases WHERE platform_id = ?2)",
+                params![platform.name().as_str(), platform_id],
+            )?;
+            tx.execute(
+                "UPDATE platforms SET name = ?1, small_image = ?2, small_tooltip = ?3 \
+                 WHERE id = ?4",
+                params![
+                    platform.name().as_str(),
+                    platform.small_image().map(Image::as_str),
+                    platform.small_tooltip().map(Tooltip::as_str),
+                    platform_id,
+                ],
+            )?;
+            tx.execute(
+                "DELETE FROM platform_aliases WHERE platform_id = ?1",
+                params![platform_id],
+            )?;
+            Self::insert_aliases(&tx, platform_id, platform)?;
+            tx.commit()
+        })();
+
+        match result {
+            Ok(()) => Ok(platform.clone()),
+            Err(err) if Self::is_unique_violation(&err) => Err(PlatformUpdateError::Duplicate {
+                platform: platform.name().clone(),
+            }),
+            Err(err) => Err(anyhow::Error::from(err)
+                .context(format!("failed to update platform {}", req.name()))
+                .into()),
+        }
+    }
+
+    fn delete_platform(&self, name: &Platform) -> Result<(), PlatformDeleteError> {
+        let platform_id = self
+            .platform_id(name)
+            .with_context(|| format!("failed to load platform {name}"))?
+            .ok_or_else(|| PlatformDeleteError::NotFound {
+                platform: name.clone(),
+            })?;
+
+        let tx = self
+            .connection
+            .unchecked_transaction()
+            .context("failed to start transaction")?;
+        tx.execute(
+            "DELETE FROM platform_aliases WHERE platform_id = ?1",
+            params![platform_id],
+        )
+        .and_then(|_| tx.execute("DELETE FROM platforms WHERE id = ?1", params![platform_id]))
+        .and_then(|_| tx.commit())
+        .with_context(|| format!("failed to delete platform {name}"))?;
+
+        Ok(())
+    }
+
+    fn find_platform(
+        &self,
+        platform: &Platform,
+    ) -> Result<Option<PlatformDefinition>, PlatformGetError> {
+        let platform_id: Option<i64> = self
+            .connection
+            .query_row(
+                "SELECT id FROM platforms WHERE name = ?1 \
+                 UNION ALL SELECT platform_id FROM platform_aliases WHERE alias = ?1",
+                params![platform.as_str()],
+                |row| row.get(0),
+            )
+            .optional()
+            .with_context(|| format!("failed to look up platform {platform}"))?;
+
+        Ok(platform_id
+            .map(|platform_id| self.load_platform(platform_id))
+            .transpose()?)
+    }
+
+    fn list_platforms(&self) -> Result<Vec<PlatformDefinition>, PlatformListError> {
+        let mut statement = self
+            .connection
+            .prepare_cached("SELECT id FROM platforms ORDER BY id")
+            .context("failed to prepare platform listing")?;
+
+        let platforms = statement
+            .query_map([], |row| row.get(0))
+            .context("failed to list platforms")?
+            .map(|platform_id| self.load_platform(platform_id?))
+            .collect::<anyhow::Result<_>>()?;
+
+        Ok(platforms)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        domain::{
+            game::{
+                Collection, FieldName, GameCreateError, GameCreateRequest, GameRepository,
+                GameUpdateError, GameUpdateRequest, Name, Party, Platform, Playtime, Tag, Template,
+            },
+            platform::{PlatformDefinition, PlatformRepository, PlatformUpdateRequest},
+        },
+        outbound::{InMemoryGameRepository, SqliteGameRepository, sqlite_repository::MIGRATIONS},
+    };
+    use pretty_assertions::{assert_eq, assert_ne};
+    use rusqlite::Connection;
+
+    fn request(name: &str) -> GameCreateRequest {
+        GameCreateRequest::builder()
+            .name(Name::new(name).unwrap())
+            .platform(Platform::new("Nintendo Switch").unwrap())
+            .build()
+    }
+
+    #[test]
+    fn names_are_unique() {
+        let repo = SqliteGameRepository::open_in_memory().unwrap();
+        repo.create_game(&request("mario")).unwrap();
+        let zelda = repo.create_game(&request("zelda")).unwrap();
+
+        let rename = GameUpdateRequest::builder()
+            .id(zelda.id())
+            .game(request("mario"))
+            .build();
+
+        assert!(matches!(
+            repo.create_game(&request("mario")),
+            Err(GameCreateError::Duplicate { .. })
+        ));
+        assert!(matches!(
+            repo.update_game(&rename),
+            Err(GameUpdateError::Duplicate { .. })
+        ));
+    }
+
+    #[test]
+    fn import_skips_existing_games() {
+        let source = InMemoryGameRepository::new();
+        let mario = source.create_game(&request("mario")).unwrap();
+        source.create_game(&request("zelda")).unwrap();
+
+        let repo = SqliteGameRepository::open_in_memory().unwrap();
+        repo.create_game(&request("zelda")).unwrap();
+
+        assert_eq!(repo.import_from(&source).unwrap(), 1);
+        assert_eq!(repo.list_games().unwrap().len(), 2);
+        assert_eq!(repo.get_game(mario.id()).unwrap(), mario);
+    }
+
+    #[test]
+    fn migration_assigns_ids_to_existing_rows() {
+        let connection = Connection::open_in_memory().unwrap();
+        connection.execute_batch(MIGRATIONS[0]).unwrap();
+        connection
+            .execute_batch(
+                "INSERT INTO games (name, platform) VALUES ('mario', 'Switch'), ('zelda', 'Switch');
+                 PRAGMA user_version = 1;",
+            )
+            .unwrap();
+
+        let repo = SqliteGameRepository::with_connection(connection).unwrap();
+        assert!(!repo.is_new());
+        let games = repo.list_games().unwrap();
+        assert_eq!(games.len(), 2);
+        assert_ne!(games[0].id(), games[1].id());
+    }
+
+    #[test]
+    fn labels_are_stored_per_game() {
+        let repo = SqliteGameRepository::open_in_memory().unwrap();
+        let req = request("mario").with_labels(
+            [Tag::new("co-op").unwrap(), Tag::new("racing").unwrap()].into(),
+            [Collection::new("Summer backlog").unwrap()].into(),
+        );
+        let mario = repo.create_game(&req).unwrap();
+        repo.create_game(&request("zelda")).unwrap();
+
+        assert_eq!(repo.get_game(mario.id()).unwrap(), mario);
+
+        let update = GameUpdateRequest::builder()
+            .id(mario.id())
+            .game(req.with_labels([Tag::new("racing").unwrap()].into(), [].into()))
+            .build();
+        let updated = repo.update_game(&update).unwrap();
+        assert_eq!(repo.list_games().unwrap()[0], updated);
+        assert!(repo.list_games().unwrap()[1].tags().is_empty());
+    }
+
+    #[test]
+    fn elapsed_override_is_stored() {
+        let repo = SqliteGameRepository::open_in_memory().unwrap();
+        let hidden = GameCreateRequest::builder()
+            .name(Name::new("mario").unwrap())
+            .platform(Platform::new("Nintendo Switch").unwrap())
+            .show_elapsed(false)
+            .build();
+        let mario = repo.create_game(&hidden).unwrap();
+        let zelda = repo.create_game(&request("zelda")).unwrap();
+
+        assert_eq!(
+            repo.get_game(mario.id()).unwrap().show_elapsed(),
+            Some(false)
+        );
+        assert_eq!(repo.get_game(zelda.id()).unwrap().show_elapsed(), None);
+    }
+
+    #[test]
+    fn party_and_secrets_are_stored() {
+        let repo = SqliteGameRepository::open_in_memory().unwrap();
+        let couch = GameCreateRequest::builder()
+            .name(Name::new("mario").unwrap())
+            .platform(Platform::new("Nintendo Switch").unwrap())
+            .party(Party::new("couch", 2, 4).unwrap())
+            .secrets("join=room-42, spectate=room-42-tv".parse().unwrap())
+            .build();
+        let mario = repo.create_game(&couch).unwrap();
+        assert_eq!(repo.get_game(mario.id()).unwrap(), mario);
+
+        let update = GameUpdateRequest::builder()
+            .id(mario.id())
+            .game(request("mario"))
+            .build();
+        repo.update_game(&update).unwrap();
+        let mario = repo.get_game(mario.id()).unwrap();
+        assert_eq!(mario.party(), None);
+        assert!(mario.secrets().is_empty());
+    }
+
+    #[test]
+    fn templates_fields_and_playtime_are_stored() {
+        let repo = SqliteGameRepository::open_in_memory().unwrap();
+        let req = GameCreateRequest::builder()
+            .name(Name::new("zelda").unwrap())
+            .platform(Platform::new("Nintendo Switch").unwrap())
+            .state(Template::new("{name} — {status}").unwrap())
+            .fields([(FieldName::new("status").unwrap(), String::from("Shrines"))].into())
+            .build();
+        let zelda = repo.create_game(&req).unwrap();
+        assert_eq!(repo.get_game(zelda.id()).unwrap(), zelda);
+
+        let played = repo
+            .add_playtime(zelda.id(), Playtime::from_seconds(90))
+            .unwrap();
+        assert_eq!(played.playtime(), Playtime::from_seconds(90));
+
+        // Edits keep the playtime.
+        let update = GameUpdateRequest::builder()
+            .id(zelda.id())
+            .game(request("zelda"))
+            .build();
+        let updated = repo.update_game(&update).unwrap();
+        assert_eq!(updated.playtime(), Playtime::from_seconds(90));
+        assert_eq!(repo.get_game(zelda.id()).unwrap(), updated);
+        assert!(updated.fields().is_empty());
+    }
+
+    #[test]
+    fn platform_renames_cascade_to_games() {
+        let repo = SqliteGameRepository::open_in_memory().unwrap();
+        let switch = PlatformDefinition::builder()
+            .name(Platform::new("Nintendo Switch").unwrap())
+            .aliases(vec![Platform::new("Switch").unwrap()])
+            .build();
+        repo.create_platform(&switch).unwrap();
+        let mario = repo.create_game(&request("mario")).unwrap();
+        let spelled = |name, platform| {
+            GameCreateRequest::builder()
+                .name(Name::new(name).unwrap())
+                .platform(Platform::new(platform).unwrap())
+                .build()
+        };
+        let zelda = repo
+            .create_game(&spelled("zelda", "nintendo switch"))
+            .unwrap();
+        let kirby = repo.create_game(&spelled("kirby", "SWITCH")).unwrap();
+
+        let req = PlatformUpdateRequest::builder()
+            .name(switch.name().clone())
+            .platform(switch.renamed(Platform::new("Switch 1").unwrap()))
+            .build();
+        repo.update_platform(&req).unwrap();
+
+        let found = repo
+            .find_platform(&Platform::new("switch").unwrap())
+            .unwrap()
+            .unwrap();
+        assert_eq!(found, req.platform().clone());
+        for game in [mario, zelda, kirby] {
+            assert_eq!(
+                repo.get_game(game.id()).unwrap().platform().as_str(),
+                "Switch 1"
+            );
+        }
+    }
+}
@@ -0,0 +1,530 @@
+use std::{cell::RefCell, env, fs, mem, path::PathBuf};
+
+use anyhow::Context;
+use log::debug;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    domain::{
+        game::{
+            Game, GameCreateError, GameCreateRequest, GameDeleteError, GameDeleteRequest,
+            GameGetError, GameId, GameListError, GameRepository, GameUpdateError,
+            GameUpdateRequest, Name, Platform, Playtime,
+        },
+        platform::{
+            PlatformCreateError, PlatformDefinition, PlatformDeleteError, PlatformGetError,
+            PlatformListError, PlatformRepository, PlatformUpdateError, PlatformUpdateRequest,
+        },
+    },
+    error::GameError,
+};
+
+/// On-disk layout of `consoleplayergames.toml`: lists of `[[game]]` and `[[platform]]` tables.
+#[derive(Debug, Default, Deserialize, Serialize)]
+struct GameFile {
+    #[serde(default)]
+    game: Vec<GameEntry>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    platform: Vec<PlatformDefinition>,
+}
+
+/// A `[[game]]` table. Files written before games had identifiers lack the `id` key.
+#[derive(Debug, Deserialize, Serialize)]
+struct GameEntry {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    id: Option<GameId>,
+    #[serde(flatten)]
+    game: GameCreateRequest,
+}
+
+impl From<&Game> for GameEntry {
+    fn from(game: &Game) -> Self {
+        Self {
+            id: Some(game.id()),
+            game: GameCreateRequest::from(game),
+        }
+    }
+}
+
+/// `GameRepository` backed by the `consoleplayergames.toml` file.
+///
+/// The whole library is kept in memory and written back after every change.
+#[derive(Debug)]
+pub struct TomlGameRepository {
+    path: PathBuf,
+    games: RefCell<Vec<Game>>,
+    platforms: RefCell<Vec<PlatformDefinition>>,
+}
+
+impl TomlGameRepository {
+    const CONFIG_FILE: &str = ".config/consoleplayergames.toml";
+
+    /// Opens the game file in the user's home directory, creating it if necessary.
+    pub fn new() -> Result<Self, GameError> {
+        Self::open(Self::default_path()?)
+    }
+
+    /// Opens the game file at `path`, creating it if necessary.
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self, GameError> {
+        let path = path.into();
+        debug!("game file path: {path:#?}");
+
+        if !path.try_exists()? {
+            debug!("creating game file");
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(&path, "")?;
+        }
+
+        let content = fs::read_to_string(&path)?;
+        debug!("Read {} bytes from the game file", content.len());
+
+        let mut file: GameFile = toml::from_str(&content)?;
+        let mut games: Vec<Game> = Vec::with_capacity(file.game.len());
+        let mut assigned = false;
+        for entry in &mut file.game {
+            let id = *entry.id.get_or_insert_with(|| {
+                assigned = true;
+                GameId::generate()
+            });
+            let game = Game::new(id, &entry.game);
+            // Refused rather than skipped: the next write would drop the skipped entry for good.
+            if games
+                .iter()
+                .any(|existing| existing.name() == game.name() || existing.id() == game.id())
+            {
+                return Err(GameError::DuplicateGame {
+                    path,
+                    name: game.name().clone(),
+                });
+            }
+            games.push(game);
+        }
+
+        // The entries as read, with nothing but their new ids added.
+        if assigned {
+            debug!("writing back newly assigned game ids");
+            fs::write(&path, toml::to_string_pretty(&file)?)?;
+        }
+
+        Ok(Self {
+            path,
+            games: RefCell::new(games),
+            platforms: RefCell::new(file.platform),
+        })
+    }
+
+    /// Location of the game file in the user's home directory.
+    pub fn default_path() -> Result<PathBuf, GameError> {
+        let home = env::var("HOME")?;
+
+        let mut path = PathBuf::new();
+        path.push(home);
+        path.push(Self::CONFIG_FILE);
+
+        Ok(path)
+    }
+
+    fn position(&self, id: GameId) -> Option<usize> {
+        self.games.borrow().iter().position(|game| game.id() == id)
+    }
+
+    fn find(&self, name: &Name) -> Option<Game> {
+        self.games
+            .borrow()
+            .iter()
+            .find(|game| game.name() == name)
+            .cloned()
+    }
+
+    fn platform_position(&self, name: &Platform) -> Option<usize> {
+        self.platforms
+            .borrow()
+            .iter()
+            .position(|platform| platform.name() == name)
+    }
+
+    /// Writes `games` and `platforms` to disk and, only if that succeeded, makes them the current
+    /// state.
+    fn commit(&self, games: Vec<Game>, platforms: Vec<PlatformDefinition>) -> anyhow::Result<()> {
+        let file = GameFile {
+            game: games.iter().map(GameEntry::from).collect(),
+            platform: platforms,
+        };
+        let content = toml::to_string_pretty(&file)?;
+        fs::write(&self.path, content)
+            .with_context(|| format!("failed to write {}", self.path.display()))?;
+
+        *self.games.borrow_mut() = games;
+        *self.platforms.borrow_mut() = file.platform;
+
+        Ok(())
+    }
+}
+
+impl GameRepository for TomlGameRepository {
+    fn create_game(&self, req: &GameCreateRequest) -> Result<Game, GameCreateError> {
+        self.restore_game(&Game::new(GameId::generate(), req))
+    }
+
+    fn restore_game(&self, game: &Game) -> Result<Game, GameCreateError> {
+        if self.find(game.name()).is_some() || self.position(game.id()).is_some() {
+            return Err(GameCreateError::Duplicate {
+                game_name: game.name().clone(),
+            });
+        }
+
+        let game = game.clone();
+        let mut games = self.games.borrow().clone();
+        games.push(game.clone());
+        let platforms = self.platforms.borrow().clone();
+        self.commit(games, platforms)?;
+
+        Ok(game)
+    }
+
+    fn update_game(&self, req: &GameUpdateRequest) -> Result<Game, GameUpdateError> {
+        let index = self
+            .position(req.id())
+            .ok_or(GameUpdateError::NotFound { id: req.id() })?;
+
+        let mut games = self.games.borrow().clone();
+        let game = Game::new(req.id(), req.game()).with_playtime(games[index].playtime());
+        games[index] = game.clone();
+        let platforms = self.platforms.borrow().clone();
+        self.commit(games, platforms)?;
+
+        Ok(game)
+    }
+
+    fn add_playtime(&self, id: GameId, played: Playtime) -> Result<Game, GameUpdateError> {
+        let index = self.position(id).ok_or(GameUpdateError::NotFound { id })?;
+
+        let mut games = self.games.borrow().clone();
+        let playtime = games[index].playtime() + played;
+        games[index] = games[index].clone().with_playtime(playtime);
+        let game = games[index].clone();
+        let platforms = self.platforms.borrow().clone();
+        self.commit(games, platforms)?;
+
+        Ok(game)
+    }
+
+    fn delete_game(&self, req: &GameDeleteRequest) -> Result<(), GameDeleteError> {
+        let index = self
+            .position(req.id())
+            .ok_or(GameDeleteError::NotFound { id: req.id() })?;
+
+        let mut games = self.games.borrow().clone();
+        games.remove(index);
+        let platforms = self.platforms.borrow().clone();
+        self.commit(games, platforms)?;
+
+        Ok(())
+    }
+
+    fn get_game(&self, id: GameId) -> Result<Game, GameGetError> {
+        let index = self.position(id).ok_or(GameGetError::NotFound { id })?;
+
+        Ok(self.games.borrow()[index].clone())
+    }
+
+    fn find_game_by_name(&self, name: &Name) -> Result<Option<Game>, GameGetError> {
+        Ok(self.find(name))
+    }
+
+    fn list_games(&self) -> Result<Vec<Game>, GameListError> {
+        Ok(self.games.borrow().clone())
+    }
+}
+
+impl PlatformRepository for TomlGameRepository {
+    fn create_platform(
+        &self,
+        platform: &PlatformDefinition,
+    ) -> Result<PlatformDefinition, PlatformCreateError> {
+        if self.platform_position(platform.name()).is_some() {
+            return Err(PlatformCreateError::Duplicate {
+                platform: platform.name().clone(),
+            });
+        }
+
+        let mut platforms = self.platforms.borrow().clone();
+        platforms.push(platform.clone());
+        let games = self.games.borrow().clone();
+        self.commit(games, platforms)?;
+
+        Ok(platform.clone())
+    }
+
+    fn update_platform(
+        &self,
+        req: &PlatformUpdateRequest,
+    ) -> Result<PlatformDefinition, PlatformUpdateError> {
+        let index =
+            self.platform_position(req.name())
+                .ok_or_else(|| PlatformUpdateError::NotFound {
+                    platform: req.name().clone(),
+                })?;
+
+        let mut platforms = self.platforms.borrow().clone();
+        let old = mem::replace(&mut platforms[index], req.platform().clone());
+
+        let renamed = req.platform().name();
+        let games = self
+            .games
+            .borrow()
+            .iter()
+            .map(|game| {
+                if old.matches(game.platform()) {
+                    Game::new(
+                        game.id(),
+                        &GameCreateRequest::from(game).with_platform(renamed.clone()),
+                    )
+                } else {
+                    game.clone()
+                }
+            })
+            .collect();
+        self.commit(games, platforms)?;
+
+        Ok(req.platform().clone())
+    }
+
+    fn delete_platform(&self, name: &Platform) -> Result<(), PlatformDeleteError> {
+        let index = self
+            .platform_position(name)
+            .ok_or_else(|| PlatformDeleteError::NotFound {
+                platform: name.clone(),
+            })?;
+
+        let mut platforms = self.platforms.borrow().clone();
+        platforms.remove(index);
+        let games = self.games.borrow().clone();
+        self.commit(games, platforms)?;
+
+        Ok(())
+    }
+
+    fn find_platform(
+        &self,
+        platform: &Platform,
+    ) -> Result<Option<PlatformDefinition>, PlatformGetError> {
+        Ok(self
+            .platforms
+            .borrow()
+            .iter()
+            .find(|definition| definition.matches(platform))
+            .cloned())
+    }
+
+    fn list_platforms(&self) -> Result<Vec<PlatformDefinition>, PlatformListError> {
+        Ok(self.platforms.borrow().clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::PathBuf};
+
+    use crate::{
+        domain::{
+            game::{
+                GameCreateRequest, GameDeleteRequest, GameRepository, Image, Name, Platform,
+                Playtime,
+            },
+            platform::{PlatformDefinition, PlatformRepository, PlatformUpdateRequest},
+            presence::Presence,
+        },
+        outbound::TomlGameRepository,
+    };
+    use pretty_assertions::assert_eq;
+
+    fn temp_path(test: &str) -> PathBuf {
+        let mut path = std::env::temp_dir();
+        path.push(format!("console-player-{}-{test}", std::process::id()));
+        path.push("consoleplayergames.toml");
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn reads_existing_file_format() {
+        let path = temp_path("reads_existing_file_format");
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(
+            &path,
+            r#"
+[[game]]
+name = "Mario Kart 8"
+platform = "Nintendo Switch"
+large_image_key = "mariokart8"
+state = "{name} — {status}"
+playtime = 3600
+
+[game.fields]
+status = "Grand Prix"
+
+[game.party]
+id = "couch"
+size = 2
+max = 4
+
+[game.secrets]
+join = "room-42"
+
+[[game]]
+name = "Tetris"
+
+[[platform]]
+name = "Nintendo Switch"
+aliases = ["Switch"]
+small_image_key = "switch"
+"#,
+        )
+        .unwrap();
+
+        let repo = TomlGameRepository::open(&path).unwrap();
+        let games = repo.list_games().unwrap();
+
+        assert_eq!(games.len(), 2);
+        assert_eq!(
+            games[0].large_image().map(Image::as_str),
+            Some("mariokart8")
+        );
+        assert_eq!(
+            games[0].party().map(ToString::to_string).as_deref(),
+            Some("couch 2/4")
+        );
+        assert_eq!(
+            Presence::from(&games[0]).state(),
+            "Mario Kart 8 — Grand Prix"
+        );
+        assert_eq!(games[0].playtime(), Playtime::from_seconds(3600));
+        assert_eq!(games[0].secrets().to_string(), "join=room-42");
+        assert_eq!(games[1].platform().as_str(), "No platform");
+        assert_eq!(
+            repo.find_platform(&Platform::new("switch").unwrap())
+                .unwrap()
+                .and_then(|platform| platform.small_image().cloned()),
+            Some(Image::from(String::from("switch")))
+        );
+
+        // Identifiers assigned on first load are persisted and stay stable, and every entry is
+        // kept when they are written back.
+        let written: toml::Table = toml::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        let entries = written["game"].as_array().unwrap();
+        assert_eq!(
+            entries
+                .iter()
+                .map(|entry| (entry["name"].as_str().unwrap(), entry.get("id").is_some()))
+                .collect::<Vec<_>>(),
+            [("Mario Kart 8", true), ("Tetris", true)]
+        );
+        assert_eq!(written["platform"].as_array().unwrap().len(), 1);
+        let reopened = TomlGameRepository::open(&path).unwrap();
+        assert_eq!(reopened.list_games().unwrap(), games);
+    }
+
+    #[test]
+    fn refuses_duplicate_games_without_touching_the_file() {
+        let path = temp_path("refuses_duplicate_games_without_touching_the_file");
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        let content = r#"
+[[game]]
+name = "Tetris"
+
+[[game]]
+name = "Zelda"
+
+[[game]]
+name = "Tetris"
+platform = "Game Boy"
+"#;
+        fs::write(&path, content).unwrap();
+
+        let err = TomlGameRepository::open(&path).unwrap_err();
+        assert!(err.to_string().contains("lists Tetris twice"), "{err}");
+        assert_eq!(fs::read_to_string(&path).unwrap(), content);
+    }
+
+    #[test]
+    fn rejects_games_without_a_name() {
+        let path = temp_path("rejects_games_without_a_name");
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, "[[game]]\nname = \"  \"\n").unwrap();
+
+        assert!(TomlGameRepository::open(&path).is_err());
+    }
+
+    #[test]
+    fn rejects_parties_larger_than_their_maximum() {
+        let path = temp_path("rejects_parties_larger_than_their_maximum");
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(
+            &path,
+            "[[game]]\nname = \"Tetris\"\nparty = { id = \"couch\", size = 5, max = 4 }\n",
+        )
+        .unwrap();
+
+        assert!(TomlGameRepository::open(&path).is_err());
+    }
+
+    #[test]
+    fn changes_are_written_back() {
+        let path = temp_path("changes_are_written_back");
+        let repo = TomlGameRepository::open(&path).unwrap();
+
+        for name in ["Mario Kart 8", "Tetris"] {
+            let req = GameCreateRequest::builder()
+                .name(Name::new(name).unwrap())
+                .platform(Platform::new("Nintendo Switch").unwrap())
+                .build();
+            repo.create_game(&req).unwrap();
+        }
+        let tetris = repo
+            .find_game_by_name(&Name::new("Tetris").unwrap())
+            .unwrap()
+            .unwrap();
+        repo.delete_game(&GameDeleteRequest::from(tetris.id()))
+            .unwrap();
+
+        let reopened = TomlGameRepository::open(&path).unwrap();
+        assert_eq!(reopened.list_games().unwrap(), repo.list_games().unwrap());
+        assert_eq!(reopened.list_games().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn platform_renames_reach_every_spelling() {
+        let path = temp_path("platform_renames_reach_every_spelling");
+        let repo = TomlGameRepository::open(&path).unwrap();
+        let switch = PlatformDefinition::builder()
+            .name(Platform::new("Nintendo Switch").unwrap())
+            .aliases(vec![Platform::new("Switch").unwrap()])
+            .build();
+        repo.create_platform(&switch).unwrap();
+        for (name, platform) in [("Mario Kart 8", "nintendo switch"), ("Tetris", "SWITCH")] {
+            let req = GameCreateRequest::builder()
+                .name(Name::new(name).unwrap())
+                .platform(Platform::new(platform).unwrap())
+                .build();
+            repo.create_game(&req).unwrap();
+        }
+
+        let req = PlatformUpdateRequest::builder()
+            .name(switch.name().clone())
+            .platform(switch.renamed(Platform::new("Switch 1").unwrap()))
+            .build();
+        repo.update_platform(&req).unwrap();
+
+        let reopened = TomlGameRepository::open(&path).unwrap();
+        let platforms: Vec<String> = reopened
+            .list_games()
+            .unwrap()
+            .iter()
+            .map(|game| game.platform().to_string())
+            .collect();
+        assert_eq!(platforms, ["Switch 1", "Switch 1"]);
+    }
+}
@@ -1,8 +1,7 @@
-use ratatui::
-    DefaultTerminal
-;
+use ratatui::DefaultTerminal;
 
 mod data;
+pub mod filter;
 pub mod input;
 mod state;
 mod tabs;
@@ -1,10 +1,9 @@
 use log::info;
 use ratatui::widgets::ListState;
 
-use crate::game::{Game, gamefile::GameFile, gamelist::GameList};
+use crate::{domain::game::Game, game::gamelist::GameList};
 
 pub struct Data {
-    gamefile: GameFile,
     gamelist: GameList,
     list_state: ListState,
 }
@@ -52,12 +51,4 @@ impl Data {
     pub(super) fn selected_game(&self) -> Option<Game> {
         self.gamelist.get(self.list_state.selected()?).cloned()
     }
-
-    #[expect(dead_code)]
-    pub(super) fn update_gamefile(&mut self) -> Result<(), crate::Error> {
-        self.gamefile = GameFile::try_from(&self.gamelist)?;
-        self.gamefile.write()?;
-
-        Ok(())
-    }
 }
@@ -0,0 +1,62 @@
+use ratatui::{
+    Frame,
+    crossterm::event::Event,
+    layout::Rect,
+    style::Color,
+    widgets::{Block, Paragraph},
+};
+use tui_input::{Input as TuiInput, backend::crossterm::EventHandler};
+
+use crate::domain::game::{GameFilter, GameValidationError};
+
+/// One-line prompt for a [`GameFilter`] in its text form.
+#[derive(Debug, Default)]
+pub struct FilterInput {
+    input: TuiInput,
+}
+
+impl FilterInput {
+    pub fn new(filter: &GameFilter) -> Self {
+        Self {
+            input: TuiInput::new(filter.to_string()),
+        }
+    }
+
+    pub fn filter(&self) -> Result<GameFilter, GameValidationError> {
+        self.input.value().parse()
+    }
+
+    pub fn render(&self, area: Rect, frame: &mut Frame) {
+        let (style, title) = match self.filter() {
+            Ok(_) => (
+                Color::Yellow,
+                String::from("Filter: words, #tag, @collection"),
+            ),
+            Err(err) => (Color::Red, format!("Filter: {err}")),
+        };
+
+        // keep 2 for borders and 1 for cursor
+        let width = area.width.max(3) - 3;
+        let scroll = self.input.visual_scroll(width as usize);
+        let input = Paragraph::new(self.input.value())
+            .style(style)
+            .scroll((0, scroll as u16))
+            .block(Block::bordered().title(title));
+        frame.render_widget(input, area);
+
+        let x = self.input.visual_cursor().max(scroll) - scroll + 1;
+        frame.set_cursor_position((area.x + x as u16, area.y + 1))
+    }
+}
+
+impl PartialEq for FilterInput {
+    fn eq(&self, other: &Self) -> bool {
+        self.input.value() == other.input.value()
+    }
+}
+
+impl EventHandler for FilterInput {
+    fn handle_event(&mut self, evt: &Event) -> Option<tui_input::StateChanged> {
+        self.input.handle_event(evt)
+    }
+}
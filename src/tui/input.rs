@@ -7,7 +7,16 @@ use ratatui::{
 };
 use tui_input::{Input as TuiInput, backend::crossterm::EventHandler};
 
-use crate::game::Game;
+use std::collections::BTreeMap;
+
+use crate::domain::{
+    asset::{AssetKeyError, AssetManifest},
+    game::{
+        Collection, FieldName, Game, GameCreateRequest, GameFieldInvalidError,
+        GameFlagInvalidError, GameId, GamePartyInvalidError, GameValidationError, Image, Name,
+        Party, Placeholders, Platform, Playtime, Secrets, Tag, Template, TemplateError, Tooltip,
+    },
+};
 
 #[derive(Debug, Default, PartialEq, PartialOrd, Ord, Eq, Clone, Copy, Hash)]
 pub enum Selection {
@@ -18,6 +27,14 @@ pub enum Selection {
     LargeImageTooltip,
     SmallImageKey,
     SmallImageTooltip,
+    Tags,
+    Collections,
+    ShowElapsed,
+    Party,
+    State,
+    Details,
+    Fields,
+    Secrets,
 }
 
 impl Selection {
@@ -28,18 +45,34 @@ impl Selection {
             Self::LargeImageKey => Self::LargeImageTooltip,
             Self::LargeImageTooltip => Self::SmallImageKey,
             Self::SmallImageKey => Self::SmallImageTooltip,
-            Self::SmallImageTooltip => Self::Name,
+            Self::SmallImageTooltip => Self::Tags,
+            Self::Tags => Self::Collections,
+            Self::Collections => Self::ShowElapsed,
+            Self::ShowElapsed => Self::Party,
+            Self::Party => Self::State,
+            Self::State => Self::Details,
+            Self::Details => Self::Fields,
+            Self::Fields => Self::Secrets,
+            Self::Secrets => Self::Name,
         }
     }
 
     pub const fn previous(&mut self) -> Self {
         match self {
-            Self::Name => Self::SmallImageTooltip,
+            Self::Name => Self::Secrets,
             Self::Platform => Self::Name,
             Self::LargeImageKey => Self::Platform,
             Self::LargeImageTooltip => Self::LargeImageKey,
             Self::SmallImageKey => Self::LargeImageTooltip,
             Self::SmallImageTooltip => Self::SmallImageKey,
+            Self::Tags => Self::SmallImageTooltip,
+            Self::Collections => Self::Tags,
+            Self::ShowElapsed => Self::Collections,
+            Self::Party => Self::ShowElapsed,
+            Self::State => Self::Party,
+            Self::Details => Self::State,
+            Self::Fields => Self::Details,
+            Self::Secrets => Self::Fields,
         }
     }
 }
@@ -52,6 +85,14 @@ pub struct Input {
     large_image_tooltip: TuiInput,
     small_image_key: TuiInput,
     small_image_tooltip: TuiInput,
+    tags: TuiInput,
+    collections: TuiInput,
+    show_elapsed: TuiInput,
+    party: TuiInput,
+    state: TuiInput,
+    details: TuiInput,
+    fields: TuiInput,
+    secrets: TuiInput,
     current_selection: Selection,
 }
 
@@ -88,6 +129,53 @@ impl Input {
         }
     }
 
+    /// Moves the cursor to the `selection` field.
+    pub const fn select(&mut self, selection: Selection) {
+        self.current_selection = selection;
+    }
+
+    /// Checks the entered image keys against `assets`, reporting the first invalid one.
+    pub fn check_assets(&self, assets: &AssetManifest) -> Result<(), (Selection, AssetKeyError)> {
+        for (selection, key) in [
+            (Selection::LargeImageKey, self.large_image_key()),
+            (Selection::SmallImageKey, self.small_image_key()),
+        ] {
+            if let Some(key) = key {
+                assets
+                    .check(&Image::from(key))
+                    .map_err(|err| (selection, err))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Checks the entered templates, reporting the first one that does not parse, uses a
+    /// placeholder the game lacks or renders longer than Discord allows.
+    pub fn check_templates(&self) -> Result<(), (Selection, TemplateError)> {
+        // Problems elsewhere are reported when the game is saved.
+        let game = GameCreateRequest::try_from(self)
+            .ok()
+            .map(|req| Game::new(GameId::generate(), &req));
+
+        for (selection, input) in [
+            (Selection::State, &self.state),
+            (Selection::Details, &self.details),
+        ] {
+            let Some(template) = parse_template(input.value()).map_err(|err| (selection, err))?
+            else {
+                continue;
+            };
+            if let Some(game) = &game {
+                template
+                    .validate(&Placeholders::new(game, Playtime::default()))
+                    .map_err(|err| (selection, err))?;
+            }
+        }
+
+        Ok(())
+    }
+
     pub fn reset(&mut self) {
         self.game = Default::default();
         self.platform = Default::default();
@@ -95,6 +183,14 @@ impl Input {
         self.large_image_tooltip = Default::default();
         self.small_image_key = Default::default();
         self.small_image_tooltip = Default::default();
+        self.tags = Default::default();
+        self.collections = Default::default();
+        self.show_elapsed = Default::default();
+        self.party = Default::default();
+        self.state = Default::default();
+        self.details = Default::default();
+        self.fields = Default::default();
+        self.secrets = Default::default();
         self.current_selection = Default::default();
     }
 
@@ -105,15 +201,22 @@ impl Input {
         area: Rect,
         selection: Selection,
         title: &str,
+        problem: Option<String>,
     ) {
         // keep 2 for borders and 1 for cursor
         let width = area.width.max(3) - 3;
         let scroll = text_input.visual_scroll(width as usize);
-        let style = if self.current_selection == selection {
+        let style = if problem.is_some() {
+            Color::Red
+        } else if self.current_selection == selection {
             Color::Yellow
         } else {
             Color::White
         };
+        let title = match problem {
+            Some(problem) => format!("{title} ({problem})"),
+            None => title.to_string(),
+        };
         let input = Paragraph::new(text_input.value())
             .style(style)
             .scroll((0, scroll as u16))
@@ -128,7 +231,22 @@ impl Input {
         }
     }
 
-    pub fn render(&self, area: Rect, frame: &mut Frame) {
+    pub fn render(&self, area: Rect, frame: &mut Frame, assets: &AssetManifest) {
+        let problem = |key: Option<String>| {
+            key.and_then(|key| assets.check(&Image::from(key)).err())
+                .as_ref()
+                .map(AssetKeyError::to_string)
+        };
+        let large_image_problem = problem(self.large_image_key());
+        let small_image_problem = problem(self.small_image_key());
+        let template_problem = self.check_templates().err();
+        let template_problem = |selection| {
+            template_problem
+                .as_ref()
+                .filter(|(problem_at, _)| *problem_at == selection)
+                .map(|(_, problem)| problem.to_string())
+        };
+
         let layout = Layout::default()
             .direction(ratatui::layout::Direction::Vertical)
             .constraints([
@@ -138,16 +256,25 @@ impl Input {
                 Constraint::Min(3),
                 Constraint::Min(3),
                 Constraint::Min(3),
+                Constraint::Min(3),
+                Constraint::Min(3),
+                Constraint::Min(3),
+                Constraint::Min(3),
+                Constraint::Min(3),
+                Constraint::Min(3),
+                Constraint::Min(3),
+                Constraint::Min(3),
             ])
             .split(area);
 
-        self.render_input(&self.game, frame, layout[0], Selection::Name, "Game");
+        self.render_input(&self.game, frame, layout[0], Selection::Name, "Game", None);
         self.render_input(
             &self.platform,
             frame,
             layout[1],
             Selection::Platform,
             "Platform",
+            None,
         );
         self.render_input(
             &self.large_image_key,
@@ -155,6 +282,7 @@ impl Input {
             layout[2],
             Selection::LargeImageKey,
             "Large Image Key",
+            large_image_problem,
         );
         self.render_input(
             &self.large_image_tooltip,
@@ -162,6 +290,7 @@ impl Input {
             layout[3],
             Selection::LargeImageTooltip,
             "Large Image Tooltip",
+            None,
         );
         self.render_input(
             &self.small_image_key,
@@ -169,6 +298,7 @@ impl Input {
             layout[4],
             Selection::SmallImageKey,
             "Small Image Key",
+            small_image_problem,
         );
         self.render_input(
             &self.small_image_tooltip,
@@ -176,6 +306,71 @@ impl Input {
             layout[5],
             Selection::SmallImageTooltip,
             "Small Image Tooltip",
+            None,
+        );
+        self.render_input(
+            &self.tags,
+            frame,
+            layout[6],
+            Selection::Tags,
+            "Tags (comma separated)",
+            None,
+        );
+        self.render_input(
+            &self.collections,
+            frame,
+            layout[7],
+            Selection::Collections,
+            "Collections (comma separated)",
+            None,
+        );
+        self.render_input(
+            &self.show_elapsed,
+            frame,
+            layout[8],
+            Selection::ShowElapsed,
+            "Show elapsed time (yes/no, empty follows --timestamp)",
+            None,
+        );
+        self.render_input(
+            &self.party,
+            frame,
+            layout[9],
+            Selection::Party,
+            "Party (id size/max, e.g. couch 2/4)",
+            None,
+        );
+        self.render_input(
+            &self.state,
+            frame,
+            layout[10],
+            Selection::State,
+            "State template, e.g. {name} — {status} (empty follows --state-template)",
+            template_problem(Selection::State),
+        );
+        self.render_input(
+            &self.details,
+            frame,
+            layout[11],
+            Selection::Details,
+            "Details template, e.g. {platform} · {playtime} (empty follows --details-template)",
+            template_problem(Selection::Details),
+        );
+        self.render_input(
+            &self.fields,
+            frame,
+            layout[12],
+            Selection::Fields,
+            "Fields for templates (name=value, comma separated)",
+            None,
+        );
+        self.render_input(
+            &self.secrets,
+            frame,
+            layout[13],
+            Selection::Secrets,
+            "Secrets for joining through Discord (join=…, spectate=…, needs a party)",
+            None,
         );
     }
 }
@@ -188,61 +383,135 @@ impl PartialEq for Input {
             && self.large_image_tooltip.value() == other.large_image_tooltip.value()
             && self.small_image_key.value() == other.small_image_key.value()
             && self.small_image_tooltip.value() == other.small_image_tooltip.value()
+            && self.tags.value() == other.tags.value()
+            && self.collections.value() == other.collections.value()
+            && self.show_elapsed.value() == other.show_elapsed.value()
+            && self.party.value() == other.party.value()
+            && self.state.value() == other.state.value()
+            && self.details.value() == other.details.value()
+            && self.fields.value() == other.fields.value()
+            && self.secrets.value() == other.secrets.value()
             && self.current_selection == other.current_selection
     }
 }
 
-impl From<&Input> for Game {
-    fn from(input: &Input) -> Self {
-        Self::builder()
-            .name(input.game.to_string())
-            .platform(input.platform.to_string())
-            .maybe_large_image_key(input.large_image_key())
-            .maybe_large_image_tooltip(input.large_image_tooltip())
-            .maybe_small_image_key(input.small_image_key())
-            .maybe_small_image_tooltip(input.small_image_tooltip())
-            .build()
+/// Non-empty entries of a comma separated list.
+fn split_list(raw: &str) -> impl Iterator<Item = &str> {
+    raw.split(',').filter(|entry| !entry.trim().is_empty())
+}
+
+/// `yes` or `no` in a few spellings, empty for `None`.
+fn parse_flag(raw: &str) -> Result<Option<bool>, GameFlagInvalidError> {
+    match raw.trim().to_lowercase().as_str() {
+        "" => Ok(None),
+        "y" | "yes" | "on" | "true" => Ok(Some(true)),
+        "n" | "no" | "off" | "false" => Ok(Some(false)),
+        _ => Err(GameFlagInvalidError(raw.to_string())),
+    }
+}
+
+/// `id size/max`, empty for `None`.
+fn parse_party(raw: &str) -> Result<Option<Party>, GamePartyInvalidError> {
+    if raw.trim().is_empty() {
+        Ok(None)
+    } else {
+        raw.parse().map(Some)
     }
 }
 
-impl From<&mut Input> for Game {
-    fn from(input: &mut Input) -> Self {
-        Self::builder()
-            .name(input.game.to_string())
-            .platform(input.platform.to_string())
-            .maybe_large_image_key(input.large_image_key())
-            .maybe_large_image_tooltip(input.large_image_tooltip())
-            .maybe_small_image_key(input.small_image_key())
-            .maybe_small_image_tooltip(input.small_image_tooltip())
-            .build()
+/// A template, empty for `None`.
+fn parse_template(raw: &str) -> Result<Option<Template>, TemplateError> {
+    if raw.trim().is_empty() {
+        Ok(None)
+    } else {
+        Template::new(raw).map(Some)
     }
 }
 
-impl From<Game> for Input {
-    fn from(game: Game) -> Self {
+/// `name=value` entries of a comma separated list.
+fn parse_fields(raw: &str) -> Result<BTreeMap<FieldName, String>, GameFieldInvalidError> {
+    split_list(raw)
+        .map(|entry| {
+            let (name, value) = entry
+                .split_once('=')
+                .ok_or_else(|| GameFieldInvalidError(entry.trim().to_string()))?;
+            Ok((FieldName::new(name)?, value.trim().to_string()))
+        })
+        .collect()
+}
+
+const fn format_flag(flag: Option<bool>) -> &'static str {
+    match flag {
+        None => "",
+        Some(true) => "yes",
+        Some(false) => "no",
+    }
+}
+
+fn join_list(entries: impl IntoIterator<Item = impl ToString>) -> String {
+    entries
+        .into_iter()
+        .map(|entry| entry.to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+impl TryFrom<&Input> for GameCreateRequest {
+    type Error = GameValidationError;
+
+    fn try_from(input: &Input) -> Result<Self, Self::Error> {
+        Ok(Self::builder()
+            .name(Name::new(input.game.value())?)
+            .platform(Platform::new(input.platform.value())?)
+            .maybe_large_image(input.large_image_key().map(Image::from))
+            .maybe_large_tooltip(input.large_image_tooltip().map(Tooltip::from))
+            .maybe_small_image(input.small_image_key().map(Image::from))
+            .maybe_small_tooltip(input.small_image_tooltip().map(Tooltip::from))
+            .tags(
+                split_list(input.tags.value())
+                    .map(Tag::new)
+                    .collect::<Result<_, _>>()?,
+            )
+            .collections(
+                split_list(input.collections.value())
+                    .map(Collection::new)
+                    .collect::<Result<_, _>>()?,
+            )
+            .maybe_show_elapsed(parse_flag(input.show_elapsed.value())?)
+            .maybe_party(parse_party(input.party.value())?)
+            .maybe_state(parse_template(input.state.value())?)
+            .maybe_details(parse_template(input.details.value())?)
+            .fields(parse_fields(input.fields.value())?)
+            .secrets(input.secrets.value().parse::<Secrets>()?)
+            .build())
+    }
+}
+
+impl From<&Game> for Input {
+    fn from(game: &Game) -> Self {
+        let optional = |value: Option<String>| TuiInput::new(value.unwrap_or_default());
+
         Self {
-            game: TuiInput::new(game.name().into()),
-            platform: TuiInput::new(game.platform().into()),
-            large_image_key: TuiInput::new(
-                game.large_image_key()
-                    .unwrap_or(&String::from(""))
-                    .to_string(),
-            ),
-            large_image_tooltip: TuiInput::new(
-                game.large_image_tooltip()
-                    .unwrap_or(&String::from(""))
-                    .to_string(),
-            ),
-            small_image_key: TuiInput::new(
-                game.small_image_key()
-                    .unwrap_or(&String::from(""))
-                    .to_string(),
-            ),
-            small_image_tooltip: TuiInput::new(
-                game.small_image_tooltip()
-                    .unwrap_or(&String::from(""))
-                    .to_string(),
-            ),
+            game: TuiInput::new(game.name().to_string()),
+            platform: TuiInput::new(game.platform().to_string()),
+            large_image_key: optional(game.large_image().map(Image::to_string)),
+            large_image_tooltip: optional(game.large_tooltip().map(Tooltip::to_string)),
+            small_image_key: optional(game.small_image().map(Image::to_string)),
+            small_image_tooltip: optional(game.small_tooltip().map(Tooltip::to_string)),
+            tags: TuiInput::new(join_list(game.tags().iter().map(Tag::as_str))),
+            collections: TuiInput::new(join_list(
+                game.collections().iter().map(Collection::as_str),
+            )),
+            show_elapsed: TuiInput::new(format_flag(game.show_elapsed()).to_string()),
+            party: optional(game.party().map(Party::to_string)),
+            state: optional(game.state().map(Template::to_string)),
+            details: optional(game.details().map(Template::to_string)),
+            fields: TuiInput::new(join_list(
+                game.fields()
+                    .iter()
+                    .map(|(name, value)| format!("{name}={value}")),
+            )),
+            secrets: TuiInput::new(game.secrets().to_string()),
             current_selection: Selection::default(),
         }
     }
@@ -270,6 +539,14 @@ impl EventHandler for Input {
             Selection::LargeImageTooltip => self.large_image_tooltip.handle_event(evt),
             Selection::SmallImageKey => self.small_image_key.handle_event(evt),
             Selection::SmallImageTooltip => self.small_image_tooltip.handle_event(evt),
+            Selection::Tags => self.tags.handle_event(evt),
+            Selection::Collections => self.collections.handle_event(evt),
+            Selection::ShowElapsed => self.show_elapsed.handle_event(evt),
+            Selection::Party => self.party.handle_event(evt),
+            Selection::State => self.state.handle_event(evt),
+            Selection::Details => self.details.handle_event(evt),
+            Selection::Fields => self.fields.handle_event(evt),
+            Selection::Secrets => self.secrets.handle_event(evt),
         }
     }
 }
@@ -7,6 +7,7 @@ use ratatui::{
 #[expect(dead_code)]
 pub struct Controls;
 
+#[expect(dead_code)]
 impl Controls {
     const TITLE: &str = "Controls";
 